@@ -0,0 +1,83 @@
+use crate::ansatt_profil::response::{AnsattProfil, KildeFeil, verifiser_identitet};
+use crate::error::{ApiError, Result};
+use crate::orgenhet::OrgEnhetClient;
+use futures::stream::{self, StreamExt};
+use lib_entra::application_permissions::{get_user_from_employee_id, get_user_groups};
+use secrecy::SecretString;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+/// Slår opp ansatte i både org_enhet og Entra og returnerer én samlet profil.
+///
+/// Entra-oppslagene krever et graph-token med *application permission*.
+pub struct AnsattProfilClient {
+    orgenhet_client: OrgEnhetClient,
+}
+
+impl AnsattProfilClient {
+    pub fn new(orgenhet_client: OrgEnhetClient) -> Self {
+        AnsattProfilClient { orgenhet_client }
+    }
+
+    /// Henter profilen til en ansatt. Feiler dersom ingen av kildene kjenner brukernavnet;
+    /// delvise feil ligger i `AnsattProfil::kilde_feil`. Grupper hentes bare for en Entra-bruker
+    /// med samme employeeId som brukernavnet.
+    #[instrument(
+        name = "Henter ansattprofil fra org_enhet og Entra",
+        skip(self, graph_token),
+        fields(request_id = %Uuid::new_v4())
+    )]
+    pub async fn hent_ansatt_profil(
+        &self,
+        graph_token: &SecretString,
+        brukernavn: &str,
+    ) -> Result<AnsattProfil> {
+        let (ansatt, entra_bruker) = tokio::join!(
+            self.orgenhet_client
+                .hent_ansatt_med_brukernavn(brukernavn.to_string()),
+            get_user_from_employee_id(graph_token.clone(), brukernavn),
+        );
+
+        let ansatt = ansatt.map_err(|e| KildeFeil::OrgEnhet(e.to_string()));
+        let entra_bruker = entra_bruker
+            .map_err(|e| KildeFeil::Entra(e.to_string()))
+            .and_then(|bruker| verifiser_identitet(bruker, brukernavn));
+
+        let grupper = match entra_bruker.as_ref().ok().and_then(|b| b.id.as_deref()) {
+            Some(user_id) => get_user_groups(graph_token.clone(), user_id)
+                .await
+                .map(|grupper| grupper.into_iter().map(|g| g.id).collect())
+                .map_err(|e| KildeFeil::Grupper(e.to_string())),
+            None => Ok(vec![]),
+        };
+
+        let profil = AnsattProfil::fra_kilder(brukernavn, ansatt, entra_bruker, grupper).map_err(
+            |kilde_feil| ApiError::ClientError {
+                resource: "ansatt_profil".to_string(),
+                error_message: format!("Fant ikke ansatt {brukernavn}: {kilde_feil:?}"),
+            },
+        )?;
+        if !profil.er_komplett() {
+            warn!(
+                "Ansattprofil for {brukernavn} er ufullstendig: {:?}",
+                profil.kilde_feil
+            );
+        }
+        Ok(profil)
+    }
+
+    /// Henter profiler for flere ansatte med maks `samtidighet` oppslag i gang om gangen.
+    /// Resultatene returneres i samme rekkefølge som `brukernavn`.
+    pub async fn hent_ansatt_profiler(
+        &self,
+        graph_token: &SecretString,
+        brukernavn: &[String],
+        samtidighet: usize,
+    ) -> Vec<(String, Result<AnsattProfil>)> {
+        stream::iter(brukernavn)
+            .map(|b| async move { (b.clone(), self.hent_ansatt_profil(graph_token, b).await) })
+            .buffered(samtidighet.max(1))
+            .collect()
+            .await
+    }
+}
@@ -0,0 +1,5 @@
+pub mod ansatt_profil_client;
+pub mod response;
+
+pub use ansatt_profil_client::AnsattProfilClient;
+pub use response::{AnsattProfil, KildeFeil};
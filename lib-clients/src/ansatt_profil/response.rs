@@ -0,0 +1,221 @@
+use crate::orgenhet::response::Ansatt;
+use lib_entra::types::GraphUser;
+use serde::Serialize;
+
+/// Sammenslått profil for en ansatt, bygget fra org_enhet og Entra.
+///
+/// Feltene fra kilden som ikke svarte er `None`/tomme, og årsaken ligger i `kilde_feil`.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct AnsattProfil {
+    pub brukernavn: String,
+    pub navn: Option<String>,
+    pub tittel: Option<String>,
+    pub kontor_id: Option<String>,
+    pub kontor_navn: Option<String>,
+    pub seksjon_id: Option<String>,
+    pub avdeling_id: Option<String>,
+    pub region_id: Option<String>,
+    pub entra_object_id: Option<String>,
+    pub mail: Option<String>,
+    pub user_principal_name: Option<String>,
+    pub gruppe_ids: Vec<String>,
+    pub kilde_feil: Vec<KildeFeil>,
+}
+
+/// Feil fra én av kildene som ikke stoppet oppslaget.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub enum KildeFeil {
+    OrgEnhet(String),
+    Entra(String),
+    Grupper(String),
+    /// Entra returnerte en bruker med annen employeeId enn brukernavnet det ble søkt på.
+    UlikIdentitet {
+        brukernavn: String,
+        employee_id: Option<String>,
+    },
+}
+
+impl AnsattProfil {
+    /// Returnerer `true` når både org_enhet og Entra bidro til profilen.
+    pub fn er_komplett(&self) -> bool {
+        self.kilde_feil.is_empty()
+    }
+
+    /// Bygger profilen fra kildene. `entra_bruker` må allerede være sjekket med
+    /// [`verifiser_identitet`]. Gir `Err` med feilene fra begge kilder dersom ingen av dem
+    /// identifiserte brukeren.
+    pub(crate) fn fra_kilder(
+        brukernavn: &str,
+        ansatt: Result<Ansatt, KildeFeil>,
+        entra_bruker: Result<GraphUser, KildeFeil>,
+        grupper: Result<Vec<String>, KildeFeil>,
+    ) -> Result<Self, Vec<KildeFeil>> {
+        if let (Err(orgenhet_feil), Err(entra_feil)) = (&ansatt, &entra_bruker) {
+            return Err(vec![orgenhet_feil.clone(), entra_feil.clone()]);
+        }
+
+        let mut profil = AnsattProfil {
+            brukernavn: brukernavn.to_string(),
+            ..Default::default()
+        };
+
+        match ansatt {
+            Ok(ansatt) => {
+                profil.brukernavn = ansatt.brukernavn;
+                profil.navn = Some(ansatt.navn);
+                profil.tittel = ansatt.tittel;
+                profil.kontor_id = ansatt.kontor_id;
+                profil.kontor_navn = ansatt.kontor_navn;
+                profil.seksjon_id = ansatt.seksjon_id;
+                profil.avdeling_id = ansatt.avdeling_id;
+                profil.region_id = ansatt.region_id;
+            }
+            Err(feil) => profil.kilde_feil.push(feil),
+        }
+
+        match entra_bruker {
+            Ok(bruker) => {
+                profil.navn = profil.navn.or(bruker.display_name);
+                profil.tittel = profil.tittel.or(bruker.job_title);
+                profil.entra_object_id = bruker.id;
+                profil.mail = bruker.mail;
+                profil.user_principal_name = bruker.user_principal_name;
+                match grupper {
+                    Ok(grupper) => profil.gruppe_ids = grupper,
+                    Err(feil) => profil.kilde_feil.push(feil),
+                }
+            }
+            Err(feil) => profil.kilde_feil.push(feil),
+        }
+
+        Ok(profil)
+    }
+}
+
+/// Entra sitt `$search` er ikke eksakt, så vi krever at employeeId er lik brukernavnet.
+pub(crate) fn verifiser_identitet(
+    bruker: GraphUser,
+    brukernavn: &str,
+) -> Result<GraphUser, KildeFeil> {
+    let matcher = bruker
+        .employeeid
+        .as_deref()
+        .is_some_and(|employee_id| employee_id.trim().eq_ignore_ascii_case(brukernavn.trim()));
+    if matcher {
+        Ok(bruker)
+    } else {
+        Err(KildeFeil::UlikIdentitet {
+            brukernavn: brukernavn.to_string(),
+            employee_id: bruker.employeeid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ansatt() -> Ansatt {
+        Ansatt {
+            brukernavn: "OLANO".to_string(),
+            navn: "Ola Nordmann".to_string(),
+            tittel: Some("Inspektør".to_string()),
+            telefonnummer: None,
+            kontor_id: Some("K1".to_string()),
+            kontor_navn: Some("Brumunddal".to_string()),
+            seksjon_id: Some("S1".to_string()),
+            avdeling_id: Some("A1".to_string()),
+            region_id: Some("R1".to_string()),
+            orgenhet_id: None,
+        }
+    }
+
+    fn entra_bruker(employee_id: &str) -> GraphUser {
+        GraphUser {
+            id: Some("00000000-0000-0000-0000-000000000001".to_string()),
+            display_name: Some("Nordmann, Ola".to_string()),
+            mail: Some("ola.nordmann@mattilsynet.no".to_string()),
+            user_principal_name: Some("ola.nordmann@mattilsynet.no".to_string()),
+            given_name: None,
+            surname: None,
+            job_title: None,
+            employeeid: Some(employee_id.to_string()),
+            groups: None,
+            photo: None,
+        }
+    }
+
+    #[test]
+    fn slaar_sammen_begge_kilder() {
+        let profil = AnsattProfil::fra_kilder(
+            "olano",
+            Ok(ansatt()),
+            verifiser_identitet(entra_bruker("OLANO"), "olano"),
+            Ok(vec!["gruppe-1".to_string()]),
+        )
+        .unwrap();
+
+        assert!(profil.er_komplett());
+        assert_eq!(profil.navn.as_deref(), Some("Ola Nordmann"));
+        assert_eq!(profil.kontor_navn.as_deref(), Some("Brumunddal"));
+        assert_eq!(profil.mail.as_deref(), Some("ola.nordmann@mattilsynet.no"));
+        assert_eq!(profil.gruppe_ids, vec!["gruppe-1".to_string()]);
+    }
+
+    #[test]
+    fn beholder_entra_data_naar_orgenhet_feiler() {
+        let profil = AnsattProfil::fra_kilder(
+            "OLANO",
+            Err(KildeFeil::OrgEnhet("404".to_string())),
+            Ok(entra_bruker("OLANO")),
+            Ok(vec![]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            profil.kilde_feil,
+            vec![KildeFeil::OrgEnhet("404".to_string())]
+        );
+        assert_eq!(profil.navn.as_deref(), Some("Nordmann, Ola"));
+        assert!(profil.entra_object_id.is_some());
+    }
+
+    #[test]
+    fn forkaster_entra_bruker_med_annen_employee_id() {
+        let entra_bruker = verifiser_identitet(entra_bruker("OLANOR"), "OLANO");
+        let ulik_identitet = KildeFeil::UlikIdentitet {
+            brukernavn: "OLANO".to_string(),
+            employee_id: Some("OLANOR".to_string()),
+        };
+        assert_eq!(entra_bruker.as_ref().err(), Some(&ulik_identitet));
+
+        let profil =
+            AnsattProfil::fra_kilder("OLANO", Ok(ansatt()), entra_bruker, Ok(vec![])).unwrap();
+
+        assert_eq!(profil.kilde_feil, vec![ulik_identitet]);
+        assert!(profil.entra_object_id.is_none());
+        assert!(profil.gruppe_ids.is_empty());
+    }
+
+    #[test]
+    fn feiler_naar_ingen_kilde_identifiserer_brukeren() {
+        let feil = AnsattProfil::fra_kilder(
+            "OLANO",
+            Err(KildeFeil::OrgEnhet("404".to_string())),
+            verifiser_identitet(entra_bruker("OLANOR"), "OLANO"),
+            Ok(vec![]),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            feil,
+            vec![
+                KildeFeil::OrgEnhet("404".to_string()),
+                KildeFeil::UlikIdentitet {
+                    brukernavn: "OLANO".to_string(),
+                    employee_id: Some("OLANOR".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -13,6 +13,7 @@ use uuid::Uuid;
 
 pub struct ArkivClient {
     api_client: ApiClient,
+    fullstendig_pdf_sjekk: bool,
 }
 
 #[derive(Deserialize)]
@@ -34,9 +35,33 @@ impl ArkivClient {
 
         ArkivClient {
             api_client: ApiClient::new(base, auth).await,
+            fullstendig_pdf_sjekk: false,
         }
     }
 
+    pub fn from_api_client(api_client: ApiClient) -> Self {
+        ArkivClient {
+            api_client,
+            fullstendig_pdf_sjekk: false,
+        }
+    }
+
+    /// Leser hele PDF-en før den arkiveres, i stedet for bare headeren. Fanger avkuttede filer,
+    /// men koster en full parsing av dokumentet.
+    pub fn med_fullstendig_pdf_sjekk(mut self) -> Self {
+        self.fullstendig_pdf_sjekk = true;
+        self
+    }
+
+    fn verifiser_dokument(&self, journalpost: &ArkiverDokument) -> Result<()> {
+        if self.fullstendig_pdf_sjekk {
+            journalpost.inspiser_pdf()?;
+        } else {
+            journalpost.verifiser_pdf()?;
+        }
+        Ok(())
+    }
+
     #[tracing::instrument(
     name = "Henter arkiv sak",
     skip(self),
@@ -190,6 +215,8 @@ impl ArkivClient {
         &self,
         journalpost: &ArkiverDokument,
     ) -> Result<ArkivPdfKvittering> {
+        self.verifiser_dokument(journalpost)?;
+
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, "application/json".parse().unwrap());
 
@@ -236,6 +263,8 @@ impl ArkivClient {
         &self,
         journalpost: &ArkiverDokument,
     ) -> Result<ArkivPdfKvittering> {
+        self.verifiser_dokument(journalpost)?;
+
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, "application/json".parse().unwrap());
 
@@ -1,3 +1,4 @@
+#[cfg(feature = "arkiv")]
 pub mod arkiv_client;
 pub mod model;
 pub mod response;
@@ -8,6 +8,8 @@ use crate::arkiv::{
     },
     remove_jens_suffix,
 };
+#[cfg(feature = "arkiv")]
+use crate::pdf::{PdfFeil, PdfInfo, inspiser, sjekk_header};
 use core::fmt;
 use serde::{Deserialize, Deserializer, Serialize};
 
@@ -515,6 +517,31 @@ impl ArkiverDokument {
     }
 }
 
+#[cfg(feature = "arkiv")]
+impl ArkiverDokument {
+    /// Sjekker PDF-headeren når filnavnet slutter på `.pdf`, så vi ikke arkiverer en
+    /// HTML-feilside. Andre filtyper slipper gjennom.
+    pub fn verifiser_pdf(&self) -> Result<(), PdfFeil> {
+        if !self.er_pdf() {
+            return Ok(());
+        }
+        sjekk_header(&self.dokument_innhold)
+    }
+
+    /// Leser hele PDF-en lokalt, så også en avkuttet fil blir oppdaget. Gir `Ok(None)` for
+    /// andre filtyper.
+    pub fn inspiser_pdf(&self) -> Result<Option<PdfInfo>, PdfFeil> {
+        if !self.er_pdf() {
+            return Ok(None);
+        }
+        inspiser(&self.dokument_innhold).map(Some)
+    }
+
+    fn er_pdf(&self) -> bool {
+        self.dokument_filnavn.to_ascii_lowercase().ends_with(".pdf")
+    }
+}
+
 // Implement Display for Kodeverk
 impl fmt::Display for Kodeverk {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1,8 +1,17 @@
-use crate::bilde::response::ImageMetaData;
+use crate::bilde::request::{BildeOppdatering, Bildetilknytning, NyttBilde};
+use crate::bilde::response::{
+    Bilde, BildeSvar, Bildestorrelse, Bildestrom, FotoApp, ImageMetaData,
+};
 use crate::client::ApiClient;
 use crate::error::ApiError;
 use crate::error::Result;
+use bytes::Bytes;
+use chrono::Utc;
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Response, StatusCode};
 use tracing::{error, info};
+use uuid::Uuid;
 
 pub struct BildeClient {
     api_client: ApiClient,
@@ -15,41 +24,34 @@ impl BildeClient {
         }
     }
 
-    pub async fn hent_bilde(
+    /// Sender GET for bildet. `304 Not Modified` regnes som suksess og må håndteres av kalleren.
+    async fn send_bilde_request(
         &self,
-        bilde_id: String,
-        storrelse: String,
-        filter: String,
-    ) -> Result<(Vec<u8>, String)> {
+        bilde_id: &str,
+        storrelse: Bildestorrelse,
+        app: FotoApp,
+        etag: Option<&str>,
+    ) -> Result<Response> {
         let url = format!(
             "{}/kategorier/bilder/{}/{}?filter.app={}",
             self.api_client.get_base_url(),
             bilde_id,
             storrelse,
-            filter,
+            app,
         );
         info!("Henter bilde fra: {url:?}");
-        let request = self
+        let mut request = self
             .api_client
             .get_client()
             .get(&url)
             .bearer_auth(self.api_client.get_token().await);
+        if let Some(etag) = etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
         let response = self.api_client.send_request_with_refresh(request).await?;
 
-        if response.status().is_success() {
-            let content_type = response
-                .headers()
-                .get("content-type")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("application/octet-stream")
-                .to_string();
-
-            let bilde_data = response.bytes().await.map_err(|e| ApiError::ClientError {
-                resource: "reqwest".to_string(),
-                error_message: e.to_string(),
-            })?;
-
-            Ok((bilde_data.to_vec(), content_type))
+        if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+            Ok(response)
         } else {
             let status = response.status();
             let error_message = response
@@ -68,38 +70,80 @@ impl BildeClient {
         }
     }
 
-    pub async fn hent_bilde_hvittkjott(
+    pub async fn hent_bilde(
         &self,
-        bilde_id: String,
-        storrelse: String,
-    ) -> Result<(Vec<u8>, String)> {
-        let bilder = self
-            .hent_bilde(bilde_id, storrelse, "MAKKS_HK".to_string())
+        bilde_id: &str,
+        storrelse: Bildestorrelse,
+        app: FotoApp,
+    ) -> Result<Bilde> {
+        let response = self
+            .send_bilde_request(bilde_id, storrelse, app, None)
             .await?;
-        Ok(bilder)
+        let content_type = les_content_type(&response);
+        let etag = les_etag(&response);
+
+        let bilde_data = response.bytes().await.map_err(|e| ApiError::ClientError {
+            resource: "reqwest".to_string(),
+            error_message: e.to_string(),
+        })?;
+
+        Ok(Bilde {
+            data: bilde_data.to_vec(),
+            content_type,
+            etag,
+        })
     }
 
-    pub async fn hent_bilde_rodtkjott(
+    /// Henter bildet som en strøm, slik at store bilder kan sendes videre uten å lastes inn i
+    /// minnet. Med `etag` gjøres en betinget GET, og et uendret bilde gir
+    /// [`BildeSvar::IkkeEndret`].
+    pub async fn hent_bilde_strom(
         &self,
-        bilde_id: String,
-        storrelse: String,
-    ) -> Result<(Vec<u8>, String)> {
-        let bilder = self
-            .hent_bilde(bilde_id, storrelse, "MAKKS".to_string())
+        bilde_id: &str,
+        storrelse: Bildestorrelse,
+        app: FotoApp,
+        etag: Option<&str>,
+    ) -> Result<BildeSvar> {
+        let response = self
+            .send_bilde_request(bilde_id, storrelse, app, etag)
             .await?;
-        Ok(bilder)
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(BildeSvar::IkkeEndret {
+                etag: les_etag(&response),
+            });
+        }
+
+        Ok(BildeSvar::Hentet(Bildestrom {
+            content_type: les_content_type(&response),
+            content_length: response.content_length(),
+            etag: les_etag(&response),
+            response,
+        }))
     }
 
-    pub async fn hent_bilde_metadata(
+    pub async fn hent_bilde_hvittkjott(
         &self,
-        bilde_id: String,
-        filter: String,
-    ) -> Result<ImageMetaData> {
+        bilde_id: &str,
+        storrelse: Bildestorrelse,
+    ) -> Result<Bilde> {
+        self.hent_bilde(bilde_id, storrelse, FotoApp::MakksHk).await
+    }
+
+    pub async fn hent_bilde_rodtkjott(
+        &self,
+        bilde_id: &str,
+        storrelse: Bildestorrelse,
+    ) -> Result<Bilde> {
+        self.hent_bilde(bilde_id, storrelse, FotoApp::MAKKS).await
+    }
+
+    pub async fn hent_bilde_metadata(&self, bilde_id: &str, app: FotoApp) -> Result<ImageMetaData> {
         let url = format!(
             "{}/kategorier/bilder/{}?filter.app={}",
             self.api_client.get_base_url(),
             bilde_id,
-            filter,
+            app,
         );
         info!("Henter bilde metadata fra: {url:?}");
         let response = self.api_client.api_get(&url).await?;
@@ -129,17 +173,206 @@ impl BildeClient {
         }
     }
 
-    pub async fn hent_bilde_metadata_rodtkjott(&self, bilde_id: String) -> Result<ImageMetaData> {
-        let bilde_meta_data = self
-            .hent_bilde_metadata(bilde_id, "MAKKS".to_string())
-            .await?;
-        Ok(bilde_meta_data)
+    pub async fn hent_bilde_metadata_rodtkjott(&self, bilde_id: &str) -> Result<ImageMetaData> {
+        self.hent_bilde_metadata(bilde_id, FotoApp::MAKKS).await
     }
 
-    pub async fn hent_bilde_metadata_hvittkjott(&self, bilde_id: String) -> Result<ImageMetaData> {
-        let bilde_meta_data = self
-            .hent_bilde_metadata(bilde_id, "MAKKS_HK".to_string())
-            .await?;
-        Ok(bilde_meta_data)
+    pub async fn hent_bilde_metadata_hvittkjott(&self, bilde_id: &str) -> Result<ImageMetaData> {
+        self.hent_bilde_metadata(bilde_id, FotoApp::MakksHk).await
+    }
+
+    /// Laster opp et bilde med metadata som multipart (`metadata` som JSON og `file`).
+    #[tracing::instrument(
+        name = "Laster opp bilde til bilde-api",
+        skip(self, bilde, metadata),
+        fields(request_id = %Uuid::new_v4(), app = %metadata.app)
+    )]
+    pub async fn last_opp_bilde(
+        &self,
+        bilde: Bytes,
+        content_type: &str,
+        metadata: &NyttBilde,
+    ) -> Result<ImageMetaData> {
+        let url = format!("{}/kategorier/bilder", self.api_client.get_base_url());
+        let metadata_json = metadata.til_json().to_string();
+        let filnavn = format!(
+            "bilde.{}",
+            metadata.file_extension.as_deref().unwrap_or("jpg")
+        );
+        let lag_skjema = || -> Result<Form> {
+            let metadata = Part::text(metadata_json.clone())
+                .mime_str("application/json")
+                .map_err(|e| ApiError::ValidationError(e.to_string()))?;
+            let fil = Part::stream(bilde.clone())
+                .file_name(filnavn.clone())
+                .mime_str(content_type)
+                .map_err(|e| ApiError::ValidationError(e.to_string()))?;
+            Ok(Form::new().part("metadata", metadata).part("file", fil))
+        };
+
+        // Multipart-kropper kan ikke klones, så vi bygger requesten på nytt ved token-fornying
+        // i stedet for å bruke `send_request_with_refresh`.
+        let send = |token: String, skjema: Form| {
+            self.api_client
+                .get_client()
+                .post(&url)
+                .bearer_auth(token)
+                .multipart(skjema)
+                .send()
+        };
+        let mut response = send(self.api_client.get_token().await, lag_skjema()?)
+            .await
+            .map_err(|e| ApiError::ClientError {
+                resource: "HTTP Request".to_string(),
+                error_message: format!("Failed to send request: {}", e),
+            })?;
+        if response.status() == StatusCode::UNAUTHORIZED
+            || response.status() == StatusCode::FORBIDDEN
+        {
+            response = send(self.api_client.refresh_token().await, lag_skjema()?)
+                .await
+                .map_err(|e| ApiError::ClientError {
+                    resource: "HTTP Request".to_string(),
+                    error_message: format!("Failed to send request after token refresh: {}", e),
+                })?;
+        }
+
+        self.les_metadata(response, "Failed to upload bilde").await
+    }
+
+    /// Lister metadata for alle bilder knyttet til et tilsynsobjekt eller en tilsynskvittering.
+    pub async fn hent_bilder(
+        &self,
+        tilknytning: &Bildetilknytning,
+        app: FotoApp,
+    ) -> Result<Vec<ImageMetaData>> {
+        let url = format!(
+            "{}/kategorier/bilder?filter.app={}&filter.{}={}",
+            self.api_client.get_base_url(),
+            app,
+            tilknytning.felt(),
+            urlencoding::encode(tilknytning.id()),
+        );
+        info!("Henter bilder fra: {url:?}");
+        let response = self.api_client.api_get(&url).await?;
+
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .map_err(|e| ApiError::ParseError(e.to_string()))
+        } else {
+            let status = response.status();
+            let error_message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!(
+                "Klarte ikke hente bilder for {tilknytning:?}, error code {status}, error message {error_message}"
+            );
+            Err(ApiError::ClientError {
+                resource: "bilde-api".to_string(),
+                error_message: format!(
+                    "Failed to fetch bilder. HTTP Status: {status}, response: {error_message}"
+                ),
+            })
+        }
+    }
+
+    pub async fn hent_bilder_for_tilsynsobjekt(
+        &self,
+        tilsynsobjekt_id: &str,
+        app: FotoApp,
+    ) -> Result<Vec<ImageMetaData>> {
+        self.hent_bilder(
+            &Bildetilknytning::Tilsynsobjekt(tilsynsobjekt_id.to_string()),
+            app,
+        )
+        .await
+    }
+
+    pub async fn hent_bilder_for_tilsynskvittering(
+        &self,
+        tilsynskvittering_id: &str,
+        app: FotoApp,
+    ) -> Result<Vec<ImageMetaData>> {
+        self.hent_bilder(
+            &Bildetilknytning::Tilsynskvittering(tilsynskvittering_id.to_string()),
+            app,
+        )
+        .await
     }
+
+    /// Oppdaterer beskrivelse og/eller posisjon på et bilde.
+    pub async fn oppdater_bilde(
+        &self,
+        bilde_id: &str,
+        app: FotoApp,
+        oppdatering: &BildeOppdatering,
+    ) -> Result<ImageMetaData> {
+        let url = format!(
+            "{}/kategorier/bilder/{}?filter.app={}",
+            self.api_client.get_base_url(),
+            bilde_id,
+            app,
+        );
+        info!("Oppdaterer bilde: {url:?}");
+        let request = self
+            .api_client
+            .get_client()
+            .patch(&url)
+            .bearer_auth(self.api_client.get_token().await)
+            .json(oppdatering);
+        let response = self.api_client.send_request_with_refresh(request).await?;
+
+        self.les_metadata(response, "Failed to update bilde").await
+    }
+
+    /// Markerer et bilde for sletting. Bildet slettes av bilde-api-et senere.
+    pub async fn marker_for_sletting(&self, bilde_id: &str, app: FotoApp) -> Result<ImageMetaData> {
+        let oppdatering = BildeOppdatering {
+            for_deletion: Some(Utc::now()),
+            ..Default::default()
+        };
+        self.oppdater_bilde(bilde_id, app, &oppdatering).await
+    }
+
+    async fn les_metadata(&self, response: Response, feilmelding: &str) -> Result<ImageMetaData> {
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .map_err(|e| ApiError::ParseError(e.to_string()))
+        } else {
+            let status = response.status();
+            let error_message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!("{feilmelding}. error code {status}, error message {error_message}");
+            Err(ApiError::ClientError {
+                resource: "bilde-api".to_string(),
+                error_message: format!(
+                    "{feilmelding}. HTTP Status: {status}, response: {error_message}"
+                ),
+            })
+        }
+    }
+}
+
+fn les_content_type(response: &Response) -> String {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string()
+}
+
+fn les_etag(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
 }
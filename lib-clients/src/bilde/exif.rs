@@ -0,0 +1,585 @@
+use crate::bilde::request::{Bildeposisjon, NyttBilde};
+use crate::bilde::response::FotoApp;
+use crate::error::{ApiError, Result};
+use crate::geonorge::{GeoNorgeClient, Koordinater};
+use bytes::Bytes;
+use chrono::{
+    DateTime, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc,
+};
+use chrono_tz::Europe::Oslo;
+use exif::experimental::Writer;
+use exif::{Exif, Field, In, Reader, Tag, Value};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use tracing::warn;
+
+/// Bildeformatene vi kan lese og vaske EXIF fra.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bildeformat {
+    Jpeg,
+    Heic,
+}
+
+impl Bildeformat {
+    /// Finner formatet fra de første bytene i filen.
+    pub fn fra_bytes(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return Some(Bildeformat::Jpeg);
+        }
+        let brand = data.get(4..12)?;
+        match brand {
+            b"ftypheic" | b"ftypheix" | b"ftypheif" | b"ftypmif1" | b"ftyphevc" => {
+                Some(Bildeformat::Heic)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Bildeformat::Jpeg => "image/jpeg",
+            Bildeformat::Heic => "image/heic",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Bildeformat::Jpeg => "jpg",
+            Bildeformat::Heic => "heic",
+        }
+    }
+}
+
+/// Det vi bruker fra EXIF-dataene i et bilde.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BildeExif {
+    pub capture_time: Option<DateTime<Utc>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Horisontal nøyaktighet for posisjonen i meter.
+    pub accuracy: Option<f32>,
+    /// EXIF-orientering (1-8). Beholdes når resten av EXIF fjernes.
+    pub orientation: Option<u16>,
+}
+
+impl BildeExif {
+    fn les(exif: &Exif) -> Self {
+        Self {
+            capture_time: capture_time(exif),
+            latitude: grader(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S'),
+            longitude: grader(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W'),
+            accuracy: rasjonal(exif, Tag::GPSHPositioningError).map(|a| a as f32),
+            orientation: exif
+                .get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|f| f.value.get_uint(0))
+                .and_then(|o| u16::try_from(o).ok()),
+        }
+    }
+
+    pub fn koordinater(&self) -> Option<Koordinater> {
+        Some(Koordinater::new(self.latitude?, self.longitude?))
+    }
+}
+
+/// Et bilde klart for opplasting: sensitiv EXIF er fjernet og sha-verdien er beregnet.
+#[derive(Debug, Clone)]
+pub struct ForberedtBilde {
+    pub data: Bytes,
+    pub format: Bildeformat,
+    pub exif: BildeExif,
+    /// SHA-256 av de vaskede bildedataene, som hex.
+    pub sha_value: String,
+}
+
+impl ForberedtBilde {
+    /// Leser EXIF, fjerner alt unntatt orienteringen og beregner sha-verdien.
+    ///
+    /// EXIF-blokken skrives over med samme lengde, slik at offsets i HEIC-containeren fortsatt
+    /// stemmer. For JPEG fjernes i tillegg XMP, som kan inneholde GPS-posisjon.
+    pub fn fra_bytes(data: &[u8]) -> Result<Self> {
+        let format = Bildeformat::fra_bytes(data).ok_or_else(|| {
+            ApiError::ValidationError("Bildet er verken JPEG eller HEIC".to_string())
+        })?;
+
+        let exif = match Reader::new().read_from_container(&mut Cursor::new(data)) {
+            Ok(exif) => Some(exif),
+            Err(exif::Error::NotFound(_)) => None,
+            Err(e) => {
+                return Err(ApiError::ValidationError(format!(
+                    "Klarte ikke lese EXIF fra bildet: {e}"
+                )));
+            }
+        };
+
+        let bilde_exif = exif.as_ref().map(BildeExif::les).unwrap_or_default();
+        let mut vasket = data.to_vec();
+        if let Some(exif) = &exif {
+            vask_exif(&mut vasket, exif, bilde_exif.orientation)?;
+        }
+        if format == Bildeformat::Jpeg {
+            vasket = fjern_jpeg_xmp(&vasket)?;
+        }
+
+        let sha_value = format!("{:x}", Sha256::digest(&vasket));
+        Ok(Self {
+            data: Bytes::from(vasket),
+            format,
+            exif: bilde_exif,
+            sha_value,
+        })
+    }
+
+    /// Metadata for opplasting, forhåndsutfylt fra EXIF.
+    pub fn nytt_bilde(&self, app: FotoApp) -> NyttBilde {
+        let mut bilde = NyttBilde::new(app)
+            .file_extension(self.format.file_extension())
+            .sha_value(&self.sha_value);
+        if let Some(capture_time) = self.exif.capture_time {
+            bilde = bilde.capture_time(capture_time);
+        }
+        if let (Some(latitude), Some(longitude)) = (self.exif.latitude, self.exif.longitude) {
+            bilde = bilde.posisjon(Bildeposisjon {
+                latitude: latitude as f32,
+                longitude: longitude as f32,
+                accuracy: self.exif.accuracy,
+            });
+        }
+        bilde
+    }
+
+    /// Som [`ForberedtBilde::nytt_bilde`], men slår også opp nærmeste adresse som
+    /// `location_description`. Feil ved oppslaget logges og ignoreres.
+    pub async fn nytt_bilde_med_stedsbeskrivelse(
+        &self,
+        app: FotoApp,
+        geonorge: &GeoNorgeClient,
+    ) -> NyttBilde {
+        let bilde = self.nytt_bilde(app);
+        let Some(koordinater) = self.exif.koordinater() else {
+            return bilde;
+        };
+        match geonorge.get_addresse_fra_koordinater(&koordinater).await {
+            Ok(Some(adresse)) => bilde.location_description(adresse.full_address()),
+            Ok(None) => bilde,
+            Err(e) => {
+                warn!("Fant ikke adresse for bildeposisjon {koordinater:?}: {e}");
+                bilde
+            }
+        }
+    }
+}
+
+/// Skriver over EXIF-blokken med en like lang blokk som bare inneholder orienteringen.
+fn vask_exif(data: &mut [u8], exif: &Exif, orientation: Option<u16>) -> Result<()> {
+    let original = exif.buf();
+    let start = data
+        .windows(original.len())
+        .position(|vindu| vindu == original)
+        .ok_or_else(|| ApiError::ValidationError("Fant ikke EXIF-blokken i bildet".to_string()))?;
+
+    // Et IFD må ha minst ett felt, så vi skriver normal orientering (1) når bildet mangler den.
+    let felt = Field {
+        tag: Tag::Orientation,
+        ifd_num: In::PRIMARY,
+        value: Value::Short(vec![orientation.unwrap_or(1)]),
+    };
+    let mut writer = Writer::new();
+    writer.push_field(&felt);
+    let mut buf = Cursor::new(Vec::new());
+    writer
+        .write(&mut buf, exif.little_endian())
+        .map_err(|e| ApiError::ValidationError(format!("Klarte ikke skrive EXIF: {e}")))?;
+    let mut ny = buf.into_inner();
+    if ny.len() > original.len() {
+        return Err(ApiError::ValidationError(
+            "EXIF-blokken er for liten til å vaskes".to_string(),
+        ));
+    }
+    ny.resize(original.len(), 0);
+    data[start..start + original.len()].copy_from_slice(&ny);
+    Ok(())
+}
+
+const XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// Fjerner APP1-segmenter med XMP fra en JPEG. Resten av filen kopieres uendret.
+///
+/// Fyllbytes (`0xFF`) foran en markør og markører uten lengde (RSTn og TEM) kopieres som de
+/// er. Segmenter med ugyldig lengde eller som går forbi slutten av filen gir
+/// `ApiError::ValidationError`.
+fn fjern_jpeg_xmp(data: &[u8]) -> Result<Vec<u8>> {
+    let mut ut = Vec::with_capacity(data.len());
+    ut.extend_from_slice(&data[..2]);
+    let mut pos = 2;
+    while data.get(pos) == Some(&0xFF) {
+        let mut markor_pos = pos + 1;
+        while data.get(markor_pos) == Some(&0xFF) {
+            markor_pos += 1;
+        }
+        let Some(&markor) = data.get(markor_pos) else {
+            break;
+        };
+        // Etter start of scan kommer bildedataene, og det er ingen flere metadata-segmenter.
+        if markor == 0xDA || markor == 0xD9 {
+            break;
+        }
+        if markor == 0x01 || (0xD0..=0xD7).contains(&markor) {
+            ut.extend_from_slice(&data[pos..=markor_pos]);
+            pos = markor_pos + 1;
+            continue;
+        }
+        let lengde = match data.get(markor_pos + 1..markor_pos + 3) {
+            Some(&[hoy, lav]) => usize::from(u16::from_be_bytes([hoy, lav])),
+            _ => {
+                return Err(ApiError::ValidationError(format!(
+                    "JPEG-segment {markor:#04X} mangler lengde"
+                )));
+            }
+        };
+        if lengde < 2 {
+            return Err(ApiError::ValidationError(format!(
+                "JPEG-segment {markor:#04X} har ugyldig lengde {lengde}"
+            )));
+        }
+        let slutt = markor_pos + 1 + lengde;
+        if slutt > data.len() {
+            return Err(ApiError::ValidationError(format!(
+                "JPEG-segment {markor:#04X} går forbi slutten av bildet"
+            )));
+        }
+        let innhold = &data[markor_pos + 3..slutt];
+        if !(markor == 0xE1 && innhold.starts_with(XMP_HEADER)) {
+            ut.extend_from_slice(&data[pos..slutt]);
+        }
+        pos = slutt;
+    }
+    ut.extend_from_slice(&data[pos..]);
+    Ok(ut)
+}
+
+fn rasjonal(exif: &Exif, tag: Tag) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(verdier) => verdier.first().map(|r| r.to_f64()),
+        _ => None,
+    }
+}
+
+/// Leser en GPS-koordinat lagret som grader, minutter og sekunder.
+fn grader(exif: &Exif, tag: Tag, ref_tag: Tag, negativ_ref: u8) -> Option<f64> {
+    let Value::Rational(dms) = &exif.get_field(tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    let [g, m, s] = dms.as_slice() else {
+        return None;
+    };
+    let verdi = g.to_f64() + m.to_f64() / 60.0 + s.to_f64() / 3600.0;
+    let negativ = match &exif.get_field(ref_tag, In::PRIMARY)?.value {
+        Value::Ascii(ref_verdi) => ref_verdi.first().and_then(|r| r.first()) == Some(&negativ_ref),
+        _ => false,
+    };
+    verdi
+        .is_finite()
+        .then_some(if negativ { -verdi } else { verdi })
+}
+
+/// Opptakstidspunktet. Bruker `OffsetTimeOriginal` hvis den finnes, ellers GPS-tiden (UTC),
+/// og til slutt `DateTimeOriginal` tolket som norsk tid.
+fn capture_time(exif: &Exif) -> Option<DateTime<Utc>> {
+    let ascii = |tag| match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(verdier) => verdier.first().cloned(),
+        _ => None,
+    };
+
+    let mut original = exif::DateTime::from_ascii(&ascii(Tag::DateTimeOriginal)?).ok();
+    if let (Some(dt), Some(offset)) = (original.as_mut(), ascii(Tag::OffsetTimeOriginal)) {
+        let _ = dt.parse_offset(&offset);
+    }
+    let lokal = original.as_ref().and_then(|dt| {
+        NaiveDate::from_ymd_opt(dt.year.into(), dt.month.into(), dt.day.into())?.and_hms_opt(
+            dt.hour.into(),
+            dt.minute.into(),
+            dt.second.into(),
+        )
+    });
+
+    if let (Some(lokal), Some(offset)) = (lokal, original.as_ref().and_then(|dt| dt.offset)) {
+        let offset = FixedOffset::east_opt(i32::from(offset) * 60)?;
+        return offset
+            .from_local_datetime(&lokal)
+            .single()
+            .map(|t| t.with_timezone(&Utc));
+    }
+    gps_tid(exif).or_else(|| lokal.map(norsk_tid_til_utc))
+}
+
+fn gps_tid(exif: &Exif) -> Option<DateTime<Utc>> {
+    let Value::Ascii(dato) = &exif.get_field(Tag::GPSDateStamp, In::PRIMARY)?.value else {
+        return None;
+    };
+    let dato =
+        NaiveDate::parse_from_str(std::str::from_utf8(dato.first()?).ok()?, "%Y:%m:%d").ok()?;
+    let Value::Rational(tid) = &exif.get_field(Tag::GPSTimeStamp, In::PRIMARY)?.value else {
+        return None;
+    };
+    let [h, m, s] = tid.as_slice() else {
+        return None;
+    };
+    let sekunder = h.to_f64() * 3600.0 + m.to_f64() * 60.0 + s.to_f64();
+    let tidspunkt = dato.and_hms_opt(0, 0, 0)? + Duration::milliseconds((sekunder * 1000.0) as i64);
+    Some(tidspunkt.and_utc())
+}
+
+/// Tolker `lokal` som norsk tid. Timen som finnes to ganger når klokka stilles tilbake om
+/// høsten tolkes som sommertid, og timen som mangler om våren tolkes som vintertid, slik en
+/// kameraklokke som ikke er stilt ennå ville vist den.
+fn norsk_tid_til_utc(lokal: NaiveDateTime) -> DateTime<Utc> {
+    match Oslo.from_local_datetime(&lokal) {
+        LocalResult::Single(tid) | LocalResult::Ambiguous(tid, _) => tid.with_timezone(&Utc),
+        LocalResult::None => (lokal - Duration::hours(1)).and_utc(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exif::Rational;
+
+    fn rasjonaler(verdier: &[(u32, u32)]) -> Value {
+        Value::Rational(
+            verdier
+                .iter()
+                .map(|&(num, denom)| Rational { num, denom })
+                .collect(),
+        )
+    }
+
+    /// En minimal JPEG med EXIF (tid, GPS, orientering og kameraserienummer) og XMP.
+    fn jpeg_med_exif(dato: &str, offset: Option<&str>) -> Vec<u8> {
+        let mut felter = vec![
+            Field {
+                tag: Tag::Orientation,
+                ifd_num: In::PRIMARY,
+                value: Value::Short(vec![6]),
+            },
+            Field {
+                tag: Tag::BodySerialNumber,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![b"SN-123456".to_vec()]),
+            },
+            Field {
+                tag: Tag::DateTimeOriginal,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![dato.as_bytes().to_vec()]),
+            },
+            Field {
+                tag: Tag::GPSLatitudeRef,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![b"N".to_vec()]),
+            },
+            Field {
+                tag: Tag::GPSLatitude,
+                ifd_num: In::PRIMARY,
+                value: rasjonaler(&[(60, 1), (52, 1), (3900, 100)]),
+            },
+            Field {
+                tag: Tag::GPSLongitudeRef,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![b"E".to_vec()]),
+            },
+            Field {
+                tag: Tag::GPSLongitude,
+                ifd_num: In::PRIMARY,
+                value: rasjonaler(&[(10, 1), (55, 1), (4500, 100)]),
+            },
+            Field {
+                tag: Tag::GPSHPositioningError,
+                ifd_num: In::PRIMARY,
+                value: rasjonaler(&[(5, 1)]),
+            },
+        ];
+        if let Some(offset) = offset {
+            felter.push(Field {
+                tag: Tag::OffsetTimeOriginal,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![offset.as_bytes().to_vec()]),
+            });
+        }
+        let mut writer = Writer::new();
+        for felt in &felter {
+            writer.push_field(felt);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+
+        let segment = |markor: u8, innhold: &[u8]| {
+            let mut s = vec![0xFF, markor];
+            s.extend_from_slice(&((innhold.len() + 2) as u16).to_be_bytes());
+            s.extend_from_slice(innhold);
+            s
+        };
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&tiff.into_inner());
+        let mut xmp = XMP_HEADER.to_vec();
+        xmp.extend_from_slice(b"<x:xmpmeta><exif:GPSLatitude>60,52.65N</exif:GPSLatitude>");
+
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend(segment(0xE1, &app1));
+        jpeg.extend(segment(0xE1, &xmp));
+        jpeg.extend(segment(0xDA, &[0, 0]));
+        jpeg.extend_from_slice(&[0x12, 0x34, 0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[test]
+    fn leser_tid_og_posisjon_fra_exif() {
+        let bilde = ForberedtBilde::fra_bytes(&jpeg_med_exif("2026:06:15 10:30:00", None)).unwrap();
+
+        assert_eq!(bilde.format, Bildeformat::Jpeg);
+        assert_eq!(
+            bilde.exif.capture_time,
+            Some(Utc.with_ymd_and_hms(2026, 6, 15, 8, 30, 0).unwrap())
+        );
+        assert!((bilde.exif.latitude.unwrap() - 60.8775).abs() < 1e-9);
+        assert!((bilde.exif.longitude.unwrap() - 10.929166666).abs() < 1e-6);
+        assert_eq!(bilde.exif.accuracy, Some(5.0));
+        assert_eq!(bilde.exif.orientation, Some(6));
+
+        let metadata = bilde.nytt_bilde(FotoApp::TILSYNSKVITTERING).til_json();
+        assert_eq!(metadata["capture_time"], "2026-06-15T08:30:00Z");
+        assert_eq!(metadata["fileExtension"], "jpg");
+        assert_eq!(metadata["shaValue"], bilde.sha_value.as_str());
+    }
+
+    #[test]
+    fn offset_i_exif_vinner_over_norsk_tid() {
+        let bilde =
+            ForberedtBilde::fra_bytes(&jpeg_med_exif("2026:01:15 10:30:00", Some("+05:00")))
+                .unwrap();
+        assert_eq!(
+            bilde.exif.capture_time,
+            Some(Utc.with_ymd_and_hms(2026, 1, 15, 5, 30, 0).unwrap())
+        );
+        assert_eq!(
+            norsk_tid_til_utc(
+                NaiveDate::from_ymd_opt(2026, 1, 15)
+                    .unwrap()
+                    .and_hms_opt(10, 30, 0)
+                    .unwrap()
+            ),
+            Utc.with_ymd_and_hms(2026, 1, 15, 9, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn norsk_tid_rundt_sommertid() {
+        let lokal = |maaned, dag, time, minutt| {
+            NaiveDate::from_ymd_opt(2026, maaned, dag)
+                .unwrap()
+                .and_hms_opt(time, minutt, 0)
+                .unwrap()
+        };
+        let utc = |maaned, dag, time, minutt| {
+            Utc.with_ymd_and_hms(2026, maaned, dag, time, minutt, 0)
+                .unwrap()
+        };
+
+        assert_eq!(norsk_tid_til_utc(lokal(6, 15, 10, 30)), utc(6, 15, 8, 30));
+        // 02:30 finnes ikke natt til 29. mars
+        assert_eq!(norsk_tid_til_utc(lokal(3, 29, 2, 30)), utc(3, 29, 1, 30));
+        assert_eq!(norsk_tid_til_utc(lokal(3, 29, 3, 30)), utc(3, 29, 1, 30));
+        // 02:30 finnes to ganger natt til 25. oktober
+        assert_eq!(norsk_tid_til_utc(lokal(10, 25, 2, 30)), utc(10, 25, 0, 30));
+        assert_eq!(norsk_tid_til_utc(lokal(10, 25, 3, 30)), utc(10, 25, 2, 30));
+    }
+
+    #[test]
+    fn fjerner_sensitiv_exif_og_xmp() {
+        let original = jpeg_med_exif("2026:06:15 10:30:00", None);
+        let bilde = ForberedtBilde::fra_bytes(&original).unwrap();
+
+        let vasket = Reader::new()
+            .read_from_container(&mut Cursor::new(bilde.data.as_ref()))
+            .unwrap();
+        assert!(vasket.get_field(Tag::GPSLatitude, In::PRIMARY).is_none());
+        assert!(
+            vasket
+                .get_field(Tag::BodySerialNumber, In::PRIMARY)
+                .is_none()
+        );
+        assert!(
+            vasket
+                .get_field(Tag::DateTimeOriginal, In::PRIMARY)
+                .is_none()
+        );
+        assert_eq!(
+            vasket
+                .get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|f| f.value.get_uint(0)),
+            Some(6)
+        );
+        assert!(!bilde.data.windows(5).any(|w| w == b"SN-12"));
+        assert!(!bilde.data.windows(9).any(|w| w == b"x:xmpmeta"));
+        assert!(bilde.data.ends_with(&[0x12, 0x34, 0xFF, 0xD9]));
+        assert_eq!(
+            bilde.sha_value,
+            format!("{:x}", Sha256::digest(bilde.data.as_ref()))
+        );
+    }
+
+    #[test]
+    fn xmp_fjernes_forbi_fyllbytes_og_markorer_uten_lengde() {
+        let mut jpeg = vec![
+            0xFF, 0xD8, 0xFF, 0xFF, 0xE0, 0x00, 0x04, 0xAA, 0xBB, 0xFF, 0xD0,
+        ];
+        let xmp = [XMP_HEADER, b"x:xmpmeta"].concat();
+        jpeg.extend_from_slice(&[0xFF, 0xE1]);
+        jpeg.extend_from_slice(&u16::try_from(xmp.len() + 2).unwrap().to_be_bytes());
+        jpeg.extend_from_slice(&xmp);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x12, 0x34, 0xFF, 0xD9]);
+
+        assert_eq!(
+            fjern_jpeg_xmp(&jpeg).unwrap(),
+            [
+                0xFF, 0xD8, 0xFF, 0xFF, 0xE0, 0x00, 0x04, 0xAA, 0xBB, 0xFF, 0xD0, 0xFF, 0xDA, 0x12,
+                0x34, 0xFF, 0xD9
+            ]
+        );
+    }
+
+    #[test]
+    fn avviser_avkortet_jpeg_segment() {
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE1, 0x00, 0x40, 0x01, 0x02];
+
+        assert!(matches!(
+            fjern_jpeg_xmp(&jpeg),
+            Err(ApiError::ValidationError(_))
+        ));
+        assert!(matches!(
+            fjern_jpeg_xmp(&jpeg[..5]),
+            Err(ApiError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn avviser_jpeg_segment_med_lengde_null() {
+        for lengde in [0x00, 0x01] {
+            let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, lengde, 0xFF, 0xD9];
+
+            assert!(matches!(
+                fjern_jpeg_xmp(&jpeg),
+                Err(ApiError::ValidationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn avviser_ukjent_format() {
+        assert!(matches!(
+            ForberedtBilde::fra_bytes(b"GIF89a..."),
+            Err(ApiError::ValidationError(_))
+        ));
+        let mut heic = vec![0, 0, 0, 24];
+        heic.extend_from_slice(b"ftypheic");
+        assert_eq!(Bildeformat::fra_bytes(&heic), Some(Bildeformat::Heic));
+    }
+}
@@ -1,2 +1,5 @@
 pub mod bilde_client;
+#[cfg(feature = "bilde")]
+pub mod exif;
+pub mod request;
 pub mod response;
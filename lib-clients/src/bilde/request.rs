@@ -0,0 +1,195 @@
+use crate::bilde::response::FotoApp;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Hva et bilde er knyttet til. Brukes både ved opplasting og listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bildetilknytning {
+    Tilsynsobjekt(String),
+    Tilsynskvittering(String),
+}
+
+impl Bildetilknytning {
+    /// Navnet på feltet i bilde-api-et, både i metadata og som `filter.<felt>`.
+    pub(crate) fn felt(&self) -> &'static str {
+        match self {
+            Bildetilknytning::Tilsynsobjekt(_) => "tilsynsobjektId",
+            Bildetilknytning::Tilsynskvittering(_) => "tilsynskvitteringId",
+        }
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        match self {
+            Bildetilknytning::Tilsynsobjekt(id) | Bildetilknytning::Tilsynskvittering(id) => id,
+        }
+    }
+}
+
+/// Hvor et bilde er tatt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bildeposisjon {
+    #[serde(rename = "locationLatitude")]
+    pub latitude: f32,
+    #[serde(rename = "locationLongitude")]
+    pub longitude: f32,
+    /// Nøyaktighet i meter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f32>,
+}
+
+/// Metadata som sendes med når et bilde lastes opp.
+///
+/// `samaccountName` settes av bilde-api-et fra tokenet og kan ikke sendes inn, mens
+/// `shaValue` bare sendes inn og ikke leses tilbake.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NyttBilde {
+    pub app: FotoApp,
+    #[serde(rename = "capture_time", skip_serializing_if = "Option::is_none")]
+    pub capture_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_extension: Option<String>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub posisjon: Option<Bildeposisjon>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha_value: Option<String>,
+    #[serde(skip)]
+    pub tilknytning: Option<Bildetilknytning>,
+}
+
+impl NyttBilde {
+    pub fn new(app: FotoApp) -> Self {
+        Self {
+            app,
+            capture_time: None,
+            description: None,
+            file_extension: None,
+            posisjon: None,
+            location_description: None,
+            sha_value: None,
+            tilknytning: None,
+        }
+    }
+
+    pub fn capture_time(mut self, capture_time: DateTime<Utc>) -> Self {
+        self.capture_time = Some(capture_time);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn file_extension(mut self, file_extension: impl Into<String>) -> Self {
+        self.file_extension = Some(file_extension.into());
+        self
+    }
+
+    pub fn posisjon(mut self, posisjon: Bildeposisjon) -> Self {
+        self.posisjon = Some(posisjon);
+        self
+    }
+
+    pub fn location_description(mut self, location_description: impl Into<String>) -> Self {
+        self.location_description = Some(location_description.into());
+        self
+    }
+
+    pub fn sha_value(mut self, sha_value: impl Into<String>) -> Self {
+        self.sha_value = Some(sha_value.into());
+        self
+    }
+
+    pub fn tilknytning(mut self, tilknytning: Bildetilknytning) -> Self {
+        self.tilknytning = Some(tilknytning);
+        self
+    }
+
+    /// Metadata slik bilde-api-et forventer dem, med tilknytningen som eget felt.
+    pub(crate) fn til_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).expect("NyttBilde kan alltid serialiseres");
+        if let Some(tilknytning) = &self.tilknytning {
+            json[tilknytning.felt()] = tilknytning.id().into();
+        }
+        json
+    }
+}
+
+/// Endringer på et eksisterende bilde. Felter som er `None` blir ikke endret.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BildeOppdatering {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub posisjon: Option<Bildeposisjon>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub for_deletion: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nytt_bilde_med_posisjon_og_tilknytning() {
+        let bilde = NyttBilde::new(FotoApp::TILSYNSKVITTERING)
+            .description("Skitten gulvflate")
+            .posisjon(Bildeposisjon {
+                latitude: 60.8775,
+                longitude: 10.9291,
+                accuracy: Some(5.0),
+            })
+            .sha_value("abc123")
+            .tilknytning(Bildetilknytning::Tilsynskvittering("kv-1".to_string()));
+
+        let json = bilde.til_json();
+
+        assert_eq!(json["app"], "TILSYNSKVITTERING");
+        assert_eq!(json["description"], "Skitten gulvflate");
+        assert_eq!(json["locationLatitude"], 60.8775_f32 as f64);
+        assert_eq!(json["accuracy"], 5.0);
+        assert_eq!(json["shaValue"], "abc123");
+        assert_eq!(json["tilsynskvitteringId"], "kv-1");
+        assert!(json.get("samaccountName").is_none());
+        assert!(json.get("capture_time").is_none());
+    }
+
+    #[test]
+    fn nytt_bilde_sender_capture_time_som_api_et() {
+        let json = NyttBilde::new(FotoApp::FOTO)
+            .capture_time("2026-06-15T08:30:00Z".parse().unwrap())
+            .til_json();
+
+        assert_eq!(json["capture_time"], "2026-06-15T08:30:00Z");
+        assert!(json.get("captureTime").is_none());
+    }
+
+    #[test]
+    fn nytt_bilde_fra_makks_hk() {
+        let json = NyttBilde::new(FotoApp::MakksHk).til_json();
+
+        assert_eq!(json, serde_json::json!({ "app": "MAKKS_HK" }));
+    }
+
+    #[test]
+    fn oppdatering_sender_bare_endrede_felt() {
+        let oppdatering = BildeOppdatering {
+            description: Some("Ny tekst".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(&oppdatering).unwrap(),
+            serde_json::json!({ "description": "Ny tekst" })
+        );
+    }
+}
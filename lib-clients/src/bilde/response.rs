@@ -1,7 +1,17 @@
+use crate::error::{ApiError, Result};
+use bytes::Bytes;
 use chrono::{DateTime, Utc};
+use futures::{Stream, stream};
+use reqwest::Response;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Metadata for et bilde slik bilde-api-et returnerer dem. Se [`NyttBilde`] og
+/// [`BildeOppdatering`] for det som sendes inn.
+///
+/// [`NyttBilde`]: crate::bilde::request::NyttBilde
+/// [`BildeOppdatering`]: crate::bilde::request::BildeOppdatering
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageMetaData {
     pub accuracy: Option<f32>,
     pub app: FotoApp,
@@ -47,10 +57,142 @@ pub struct ImageMetaData {
 }
 
 /// Enum representing FotoApp in Rust
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FotoApp {
     FOTO,
     MAKKS,
+    #[serde(rename = "MAKKS_HK", alias = "MakksHk")]
     MakksHk,
     TILSYNSKVITTERING,
 }
+
+impl FotoApp {
+    /// Verdien bilde-api-et forventer i `filter.app`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FotoApp::FOTO => "FOTO",
+            FotoApp::MAKKS => "MAKKS",
+            FotoApp::MakksHk => "MAKKS_HK",
+            FotoApp::TILSYNSKVITTERING => "TILSYNSKVITTERING",
+        }
+    }
+}
+
+impl fmt::Display for FotoApp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Størrelsene bilde-api-et kan levere et bilde i.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bildestorrelse {
+    #[default]
+    Original,
+    Stor,
+    Middels,
+    Liten,
+}
+
+impl Bildestorrelse {
+    /// Path-segmentet for størrelsen, for eksempel `/kategorier/bilder/{id}/small`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bildestorrelse::Original => "original",
+            Bildestorrelse::Stor => "large",
+            Bildestorrelse::Middels => "medium",
+            Bildestorrelse::Liten => "small",
+        }
+    }
+}
+
+impl fmt::Display for Bildestorrelse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Et bilde lastet helt inn i minnet.
+#[derive(Debug, Clone)]
+pub struct Bilde {
+    pub data: Vec<u8>,
+    pub content_type: String,
+    pub etag: Option<String>,
+}
+
+/// Et bilde som strømmes fra bilde-api-et uten å lastes inn i minnet.
+#[derive(Debug)]
+pub struct Bildestrom {
+    pub content_type: String,
+    pub content_length: Option<u64>,
+    pub etag: Option<String>,
+    pub(crate) response: Response,
+}
+
+impl Bildestrom {
+    /// Bildedataene som en strøm av biter, klar til å sendes videre til en frontend.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Bytes>> + Send {
+        stream::try_unfold(self.response, |mut response| async move {
+            let bit = response.chunk().await.map_err(|e| ApiError::ClientError {
+                resource: "bilde-api".to_string(),
+                error_message: format!("Failed to read bilde stream: {e}"),
+            })?;
+            Ok(bit.map(|bit| (bit, response)))
+        })
+    }
+}
+
+/// Svaret på en betinget henting av et bilde.
+#[derive(Debug)]
+pub enum BildeSvar {
+    /// Bildet er endret, eller det ble ikke sendt med noen ETag.
+    Hentet(Bildestrom),
+    /// Bildet er uendret siden ETag-en som ble sendt med (`304 Not Modified`).
+    IkkeEndret { etag: Option<String> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn foto_app_filterverdi_og_serde() {
+        assert_eq!(FotoApp::MakksHk.to_string(), "MAKKS_HK");
+        assert_eq!(
+            serde_json::to_value(FotoApp::MakksHk).unwrap(),
+            serde_json::json!("MAKKS_HK")
+        );
+        assert_eq!(
+            serde_json::from_str::<FotoApp>("\"MAKKS_HK\"").unwrap(),
+            FotoApp::MakksHk
+        );
+        assert_eq!(
+            serde_json::from_str::<FotoApp>("\"MakksHk\"").unwrap(),
+            FotoApp::MakksHk
+        );
+    }
+
+    #[test]
+    fn metadata_holder_interne_felt_utenfor_serde() {
+        let metadata: ImageMetaData = serde_json::from_value(serde_json::json!({
+            "id": "bilde-1",
+            "app": "FOTO",
+            "forDeletion": "2025-03-01T00:00:00Z",
+            "samaccountName": "olanor",
+            "shaValue": "abc123"
+        }))
+        .unwrap();
+
+        assert_eq!(metadata.samaccount_name.as_deref(), Some("olanor"));
+        assert!(metadata.for_deletion.is_none());
+        assert!(metadata.sha_value.is_none());
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert!(json.get("samaccountName").is_none());
+    }
+
+    #[test]
+    fn bildestorrelse_som_path_segment() {
+        assert_eq!(Bildestorrelse::default().as_str(), "original");
+        assert_eq!(Bildestorrelse::Liten.to_string(), "small");
+    }
+}
@@ -8,10 +8,12 @@ use reqwest_middleware::{
 };
 use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
 use secrecy::ExposeSecret;
+use serde::de::DeserializeOwned;
 use std::env;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, RwLock};
+use tracing::error;
 
 pub struct Token {
     value: String,
@@ -94,6 +96,12 @@ impl ApiClient {
             .await
             .expect("Failed to build client configuration");
 
+        Self::from_config(base_url, &client_config).await
+    }
+
+    /// Like [`ApiClient::new`], with the base url and credentials given directly instead of
+    /// read from the environment.
+    pub async fn from_config(base_url: String, client_config: &ClientConfiguration) -> Self {
         let token_provider = Arc::new(
             TokenProvider::new(
                 client_config.client_id.expose_secret().to_string(),
@@ -203,3 +211,29 @@ impl ApiClient {
         Ok(response)
     }
 }
+
+/// Parses a successful JSON response, or returns [`ApiError::ClientError`] with the status and
+/// body when the call failed. `resource` names the API in the error.
+pub(crate) async fn les_json<T: DeserializeOwned>(
+    response: Response,
+    resource: &str,
+    feilmelding: &str,
+) -> crate::error::Result<T> {
+    if response.status().is_success() {
+        let text = response.text().await?;
+        serde_json::from_str(&text).map_err(|e| ApiError::ParseError(e.to_string()))
+    } else {
+        let status = response.status();
+        let error_message = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        error!("{feilmelding}. error code {status}, error message {error_message}");
+        Err(ApiError::ClientError {
+            resource: resource.to_string(),
+            error_message: format!(
+                "{feilmelding}. HTTP Status: {status}, response: {error_message}"
+            ),
+        })
+    }
+}
@@ -1,9 +1,13 @@
 use crate::client::ApiClient;
+use crate::document_generator::format::{
+    Dokumentformat, GenerertDokument, Renderingsvalg, er_gyldig_mal_id,
+};
 use crate::document_generator::response::{InterntDokument, VedleggDokument};
 use crate::error::ApiError;
 use crate::error::Result;
 use reqwest_middleware::reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap};
-use tracing::{info, instrument};
+use serde::Serialize;
+use tracing::{error, info, instrument};
 use uuid::Uuid;
 
 pub struct DokumentGeneratorClient {
@@ -21,46 +25,27 @@ impl DokumentGeneratorClient {
         }
     }
 
+    pub async fn create_interndokument(&self, interndokument: InterntDokument) -> Result<Vec<u8>> {
+        self.create_interndokument_med_valg(interndokument, &Renderingsvalg::default())
+            .await
+            .map(GenerertDokument::into_vec)
+    }
+
+    /// Som [`DokumentGeneratorClient::create_interndokument`], med valgt format, for eksempel
+    /// [`Dokumentformat::PdfA`] for arkivet. Interndokumentet har ikke språkvalg, så
+    /// `valg.locale` sendes ikke.
     #[instrument(
         name = "Creating interndokument",
         skip(self, interndokument),
         fields(request_id = %Uuid::new_v4())
     )]
-    pub async fn create_interndokument(&self, interndokument: InterntDokument) -> Result<Vec<u8>> {
-        let json_body =
-            serde_json::to_string(&interndokument).expect("Failed to serialize dokument");
-
-        let mut headers = HeaderMap::new();
-        headers.insert(ACCEPT, "application/pdf".parse().unwrap());
-        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
-
-        let request = self
-            .api_client
-            .get_client()
-            .post(format!("{}/v2/interntdokument", self.api_client.get_base_url()).as_str())
-            .headers(headers)
-            .bearer_auth(self.api_client.get_token().await)
-            .body(json_body);
-        let response = self.api_client.send_request_with_refresh(request).await?;
-
-        let status = response.status();
-
-        if response.status().is_success() {
-            let bytes = response.bytes().await?;
-            let byte_array: Vec<u8> = bytes.to_vec();
-
-            info!("Byte array length: {}", byte_array.len());
-            Ok(byte_array)
-        } else {
-            Err(ApiError::ClientError {
-                resource: "DokumentGenerator".to_string(),
-                error_message: format!(
-                    "Failed to generate interndokument, HTTP Status: {}, response {:?}",
-                    status,
-                    response.text().await
-                ),
-            })
-        }
+    pub async fn create_interndokument_med_valg(
+        &self,
+        interndokument: InterntDokument,
+        valg: &Renderingsvalg,
+    ) -> Result<GenerertDokument> {
+        self.generer("v2/interntdokument", &interndokument, valg, false)
+            .await
     }
 
     #[instrument(
@@ -69,16 +54,73 @@ impl DokumentGeneratorClient {
         fields(request_id = %Uuid::new_v4())
     )]
     pub async fn create_vedlegg(&self, vedlegg: VedleggDokument) -> Result<Vec<u8>> {
-        let json_body = serde_json::to_string(&vedlegg).expect("Failed to serialize dokument");
+        self.generer("v1/vedlegg", &vedlegg, &Renderingsvalg::default(), false)
+            .await
+            .map(GenerertDokument::into_vec)
+    }
+
+    /// Genererer et dokument fra malen `template_id` med `data` som flettedata, på bokmål
+    /// og som vanlig PDF.
+    pub async fn render(
+        &self,
+        template_id: &str,
+        data: impl Serialize,
+    ) -> Result<GenerertDokument> {
+        self.render_med_valg(template_id, data, &Renderingsvalg::default())
+            .await
+    }
+
+    /// Som [`DokumentGeneratorClient::render`], med valgt språk og format.
+    #[instrument(
+        name = "Rendering dokument fra mal",
+        skip(self, data),
+        fields(request_id = %Uuid::new_v4())
+    )]
+    pub async fn render_med_valg(
+        &self,
+        template_id: &str,
+        data: impl Serialize,
+        valg: &Renderingsvalg,
+    ) -> Result<GenerertDokument> {
+        if !er_gyldig_mal_id(template_id) {
+            return Err(ApiError::ValidationError(format!(
+                "Ugyldig mal-id: {template_id:?}"
+            )));
+        }
+        self.generer(&format!("v1/{template_id}"), &data, valg, true)
+            .await
+    }
+
+    /// Poster `data` til `path`. `format` sendes bare når det ikke er vanlig PDF, og `locale`
+    /// bare når `med_locale` er satt, slik at de faste endepunktene får samme request som før.
+    async fn generer<T: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        data: &T,
+        valg: &Renderingsvalg,
+        med_locale: bool,
+    ) -> Result<GenerertDokument> {
+        let json_body = serde_json::to_string(data).map_err(|e| {
+            ApiError::ValidationError(format!("Kunne ikke serialisere data til {path}: {e}"))
+        })?;
 
         let mut headers = HeaderMap::new();
-        headers.insert(ACCEPT, "application/pdf".parse().unwrap());
+        headers.insert(ACCEPT, valg.format.content_type().parse().unwrap());
         headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
 
+        let mut query = Vec::new();
+        if valg.format != Dokumentformat::Pdf {
+            query.push(("format", valg.format.query_verdi()));
+        }
+        if med_locale {
+            query.push(("locale", valg.locale.as_str()));
+        }
+
         let request = self
             .api_client
             .get_client()
-            .post(format!("{}/v1/vedlegg", self.api_client.get_base_url()).as_str())
+            .post(format!("{}/{}", self.api_client.get_base_url(), path).as_str())
+            .query(&query)
             .headers(headers)
             .bearer_auth(self.api_client.get_token().await)
             .body(json_body);
@@ -86,19 +128,44 @@ impl DokumentGeneratorClient {
 
         let status = response.status();
 
-        if response.status().is_success() {
-            let bytes = response.bytes().await?;
-            let byte_array: Vec<u8> = bytes.to_vec();
+        if status.is_success() {
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let innhold = response.bytes().await?;
 
-            info!("Byte array length: {}", byte_array.len());
-            Ok(byte_array)
+            info!("Byte array length: {}", innhold.len());
+            let pdf = valg.format.verifiser(&innhold).map_err(|feil| {
+                error!(
+                    "Dokumentgeneratoren svarte {status} uten gyldig dokument for {path}: {feil}"
+                );
+                ApiError::ClientError {
+                    resource: "DokumentGenerator".to_string(),
+                    error_message: format!(
+                        "Generated {path} is not a valid document, HTTP Status: {status}: {feil}"
+                    ),
+                }
+            })?;
+            Ok(GenerertDokument {
+                innhold,
+                format: valg.format,
+                content_type,
+                pdf,
+            })
         } else {
+            let error_message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!(
+                "Klarte ikke generere {path}. error code {status}, error message {error_message}"
+            );
             Err(ApiError::ClientError {
                 resource: "DokumentGenerator".to_string(),
                 error_message: format!(
-                    "Failed to generate interndokument, HTTP Status: {}, response {:?}",
-                    status,
-                    response.text().await
+                    "Failed to generate {path}, HTTP Status: {status}, response {error_message}"
                 ),
             })
         }
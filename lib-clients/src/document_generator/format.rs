@@ -0,0 +1,169 @@
+use crate::pdf::{PdfFeil, PdfInfo, trekk_ut_tekst};
+use bytes::Bytes;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Språket dokumentet genereres på. Deserialiseres med [`FromStr`], så `nb-NO` godtas også.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Nb,
+    Nn,
+    En,
+}
+
+impl Locale {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::Nb => "nb",
+            Locale::Nn => "nn",
+            Locale::En => "en",
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// Godtar både `nb` og `nb-NO`/`nb_NO`, og `no` som bokmål.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let spraak = s.split(['-', '_']).next().unwrap_or_default();
+        match spraak.to_ascii_lowercase().as_str() {
+            "nb" | "no" => Ok(Locale::Nb),
+            "nn" => Ok(Locale::Nn),
+            "en" => Ok(Locale::En),
+            _ => Err(format!("Ukjent locale: {s}")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Locale {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Formatet dokumentgeneratoren skal levere.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Dokumentformat {
+    #[default]
+    Pdf,
+    /// PDF/A-2b, som arkivet krever for langtidslagring.
+    PdfA,
+    /// Den ferdig flettede HTML-en, uten PDF-konvertering. Nyttig for forhåndsvisning.
+    Html,
+}
+
+impl Dokumentformat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Dokumentformat::Pdf | Dokumentformat::PdfA => "application/pdf",
+            Dokumentformat::Html => "text/html",
+        }
+    }
+
+    /// Sjekker at `innhold` faktisk er i dette formatet. Generatoren kan svare 200 med en
+    /// HTML-feilside, så PDF-er leses lokalt før de brukes.
+    pub(crate) fn verifiser(self, innhold: &[u8]) -> Result<Option<PdfInfo>, PdfFeil> {
+        match self {
+            Dokumentformat::Pdf => crate::pdf::inspiser(innhold).map(Some),
+            Dokumentformat::PdfA => crate::pdf::verifiser_pdfa(innhold).map(Some),
+            Dokumentformat::Html => Ok(None),
+        }
+    }
+
+    /// Verdien til query-parameteren `format`.
+    pub(crate) fn query_verdi(self) -> &'static str {
+        match self {
+            Dokumentformat::Pdf => "pdf",
+            Dokumentformat::PdfA => "pdfa",
+            Dokumentformat::Html => "html",
+        }
+    }
+}
+
+/// Valg for én generering. `Default` gir bokmål og vanlig PDF.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Renderingsvalg {
+    pub locale: Locale,
+    pub format: Dokumentformat,
+}
+
+impl Renderingsvalg {
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub fn format(mut self, format: Dokumentformat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+/// Et dokument fra dokumentgeneratoren.
+#[derive(Debug, Clone)]
+pub struct GenerertDokument {
+    pub innhold: Bytes,
+    pub format: Dokumentformat,
+    /// `Content-Type` slik generatoren oppga den.
+    pub content_type: Option<String>,
+    /// Resultatet av den lokale sjekken av PDF-en. `None` for HTML.
+    pub pdf: Option<PdfInfo>,
+}
+
+impl GenerertDokument {
+    pub fn into_vec(self) -> Vec<u8> {
+        self.innhold.to_vec()
+    }
+
+    /// Teksten i PDF-en, for assertions i tester.
+    pub fn tekst(&self) -> Result<String, PdfFeil> {
+        trekk_ut_tekst(&self.innhold)
+    }
+}
+
+/// Mal-id-er brukes rett i url-en, så vi godtar bare små bokstaver, tall, `-` og `_`.
+pub(crate) fn er_gyldig_mal_id(mal_id: &str) -> bool {
+    !mal_id.is_empty()
+        && mal_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_fra_streng_og_serde() {
+        assert_eq!("nb-NO".parse::<Locale>(), Ok(Locale::Nb));
+        assert_eq!("NN".parse::<Locale>(), Ok(Locale::Nn));
+        assert_eq!("no".parse::<Locale>(), Ok(Locale::Nb));
+        assert!("se".parse::<Locale>().is_err());
+        assert_eq!(serde_json::to_value(Locale::En).unwrap(), "en");
+        assert_eq!(
+            serde_json::from_str::<Locale>("\"nb_NO\"").unwrap(),
+            Locale::Nb
+        );
+        assert!(serde_json::from_str::<Locale>("\"se\"").is_err());
+    }
+
+    #[test]
+    fn mal_id_kan_ikke_endre_url_en() {
+        assert!(er_gyldig_mal_id("vedtak_smitte-2"));
+        assert!(!er_gyldig_mal_id(""));
+        assert!(!er_gyldig_mal_id("../admin"));
+        assert!(!er_gyldig_mal_id("mal?x=1"));
+    }
+}
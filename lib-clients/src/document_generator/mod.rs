@@ -1,2 +1,3 @@
 pub mod document_generator_client;
+pub mod format;
 pub mod response;
@@ -1,9 +1,12 @@
+use crate::document_generator::format::Locale;
+use crate::error::ApiError;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VedleggDokument {
     pub body: String,
-    pub locale: String,
+    pub locale: Locale,
     #[serde(rename(serialize = "vaarRef"))]
     pub vaar_ref: String,
     #[serde(rename(serialize = "refPrefix"))]
@@ -106,17 +109,70 @@ impl InterntDokumentBuilder {
         self
     }
 
-    pub fn build(self) -> Result<InterntDokument, &'static str> {
+    pub fn build(self) -> Result<InterntDokument, InterntDokumentFeil> {
         Ok(InterntDokument {
-            body: self.body.ok_or("Missing body")?,
-            from: self.from.ok_or("Missing from")?,
-            hjemmel_for_unntatt_offentlighet: self
-                .hjemmel_for_unntatt_offentlighet
-                .ok_or("Missing hjemmel_for_unntatt_offentlighet")?,
+            body: self.body.ok_or(InterntDokumentFeil::Mangler("body"))?,
+            from: self.from.ok_or(InterntDokumentFeil::Mangler("from"))?,
+            hjemmel_for_unntatt_offentlighet: self.hjemmel_for_unntatt_offentlighet.ok_or(
+                InterntDokumentFeil::Mangler("hjemmel_for_unntatt_offentlighet"),
+            )?,
             saksbehandler: self.saksbehandler,
-            title: self.title.ok_or("Missing title")?,
-            to: self.to.ok_or("Missing to")?,
-            vaar_ref: self.vaar_ref.ok_or("Missing vaar_ref")?,
+            title: self.title.ok_or(InterntDokumentFeil::Mangler("title"))?,
+            to: self.to.ok_or(InterntDokumentFeil::Mangler("to"))?,
+            vaar_ref: self
+                .vaar_ref
+                .ok_or(InterntDokumentFeil::Mangler("vaar_ref"))?,
         })
     }
 }
+
+/// Hvorfor [`InterntDokumentBuilder::build`] feilet.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InterntDokumentFeil {
+    #[error("Missing {0}")]
+    Mangler(&'static str),
+}
+
+impl InterntDokumentFeil {
+    /// Feltet som mangler.
+    pub fn felt(&self) -> &'static str {
+        match self {
+            InterntDokumentFeil::Mangler(felt) => felt,
+        }
+    }
+}
+
+impl From<InterntDokumentFeil> for ApiError {
+    fn from(feil: InterntDokumentFeil) -> Self {
+        ApiError::ValidationError(feil.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn avsender() -> Avsender {
+        Avsender {
+            avsender_navn: Some("Mattilsynet".to_string()),
+            avsender_linje1: None,
+            avsender_linje2: None,
+        }
+    }
+
+    #[test]
+    fn build_sier_hvilket_felt_som_mangler() {
+        let feil = InterntDokumentBuilder::new()
+            .body("Innhold".to_string())
+            .from(avsender())
+            .build()
+            .unwrap_err();
+
+        assert_eq!(
+            feil,
+            InterntDokumentFeil::Mangler("hjemmel_for_unntatt_offentlighet")
+        );
+        assert_eq!(feil.felt(), "hjemmel_for_unntatt_offentlighet");
+        assert!(matches!(ApiError::from(feil), ApiError::ValidationError(_)));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::ejb::response_begrensninger::Begrensning;
+use crate::ejb::tilfelle::Kode;
+use crate::kodeverk::response::Kodenavn;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Europe::Oslo;
+use serde::Serialize;
+
+/// En begrensning som gjelder på en gitt dato, med årsakene slått opp i kodeverket.
+#[derive(Debug, Clone, Serialize)]
+pub struct AktivBegrensning {
+    pub begrensning: Begrensning,
+    pub begrensningsaarsak: Kode,
+    pub aarsak: Kode,
+}
+
+/// Begrensningene som gjelder for en produksjonsplass på `dato`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AktiveBegrensninger {
+    pub dato: NaiveDate,
+    pub begrensninger: Vec<AktivBegrensning>,
+    /// Første dato fra og med `dato` der ingen begrensning gjelder, altså dagen etter siste
+    /// `tildato`. `None` betyr at en
+    /// begrensning uten tildato holder plassen begrenset på ubestemt tid.
+    pub fri_fra: Option<NaiveDate>,
+}
+
+impl AktiveBegrensninger {
+    pub fn er_begrenset(&self) -> bool {
+        !self.begrensninger.is_empty()
+    }
+
+    /// Filtrerer `begrensninger` ned til de som gjelder på `dato`.
+    pub fn beregn(begrensninger: Vec<Begrensning>, dato: NaiveDate, kodenavn: &Kodenavn) -> Self {
+        let fri_fra = fri_fra(&begrensninger, dato);
+        let begrensninger = begrensninger
+            .into_iter()
+            .filter(|b| b.er_aktiv(dato))
+            .map(|begrensning| AktivBegrensning {
+                begrensningsaarsak: Kode::fra(&begrensning.begrensningsaarsakid, kodenavn),
+                aarsak: Kode::fra(&begrensning.aarsakid, kodenavn),
+                begrensning,
+            })
+            .collect();
+        AktiveBegrensninger {
+            dato,
+            begrensninger,
+            fri_fra,
+        }
+    }
+}
+
+impl Begrensning {
+    /// En begrensning gjelder fra og med `fradato` til og med `tildato`. Manglende `fradato`
+    /// eller `tildato` betyr at den er åpen i den enden.
+    ///
+    /// EJB-api-et sier ikke om `tildato` er inklusiv. Vi regner den som siste dag begrensningen
+    /// gjelder, så en produksjonsplass heller meldes begrenset én dag for lenge enn fri én dag
+    /// for tidlig. Tidspunktene gjøres om til norsk tid før de kuttes til dato.
+    pub fn er_aktiv(&self, dato: NaiveDate) -> bool {
+        let startet = self.fradato.is_none_or(|fra| norsk_dato(fra) <= dato);
+        let ikke_opphevet = self.tildato.is_none_or(|til| dato <= norsk_dato(til));
+        startet && ikke_opphevet
+    }
+
+    /// Dagen etter `tildato`, første dag begrensningen ikke lenger gjelder.
+    fn opphevet_fra(&self) -> Option<NaiveDate> {
+        self.tildato
+            .and_then(|til| norsk_dato(til).checked_add_days(Days::new(1)))
+    }
+}
+
+/// Datoen `tidspunkt` faller på i Norge.
+pub fn norsk_dato(tidspunkt: DateTime<Utc>) -> NaiveDate {
+    tidspunkt.with_timezone(&Oslo).date_naive()
+}
+
+/// Første dato fra og med `dato` der ingen av begrensningene gjelder.
+///
+/// Settet av aktive begrensninger endrer seg bare på fradatoer og dagen etter tildatoer, og det
+/// kan bare bli tomt på `dato` selv eller dagen etter en tildato, så det holder å sjekke disse.
+pub fn fri_fra(begrensninger: &[Begrensning], dato: NaiveDate) -> Option<NaiveDate> {
+    let mut kandidater: Vec<NaiveDate> = begrensninger
+        .iter()
+        .filter_map(Begrensning::opphevet_fra)
+        .filter(|opphevet| *opphevet > dato)
+        .collect();
+    kandidater.push(dato);
+    kandidater.sort();
+    kandidater.dedup();
+
+    kandidater
+        .into_iter()
+        .find(|kandidat| !begrensninger.iter().any(|b| b.er_aktiv(*kandidat)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Days, TimeZone, Utc};
+    use proptest::prelude::*;
+
+    fn dag(n: u64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap() + Days::new(n)
+    }
+
+    fn begrensning(fra: Option<u64>, til: Option<u64>) -> Begrensning {
+        let tidspunkt = |n: u64| Utc.from_utc_datetime(&dag(n).and_hms_opt(0, 0, 0).unwrap());
+        Begrensning {
+            idstring: "B1".to_string(),
+            version: 1,
+            typeid: "BEGRENSNINGTYPE$FORBUD".to_string(),
+            fradato: fra.map(tidspunkt),
+            tildato: til.map(tidspunkt),
+            handlingsloepref: None,
+            soeknadref: None,
+            begrensningsaarsakid: "BEGRENSNINGSAARSAK$SMITTE".to_string(),
+            createddate: None,
+            lastmodifieddate: None,
+            gbrnummerref: Some("GBR1".to_string()),
+            aarsakid: "AARSAK$AI".to_string(),
+            beskrivelse: String::new(),
+        }
+    }
+
+    fn begrensning_strategi() -> impl Strategy<Value = Begrensning> {
+        (
+            proptest::option::of(0u64..60),
+            proptest::option::of(0u64..60),
+        )
+            .prop_map(|(fra, lengde)| begrensning(fra, lengde.map(|l| fra.unwrap_or(0) + l)))
+    }
+
+    #[test]
+    fn tildato_er_siste_dag_begrensningen_gjelder() {
+        let b = begrensning(Some(10), Some(20));
+        assert!(!b.er_aktiv(dag(9)));
+        assert!(b.er_aktiv(dag(10)));
+        assert!(b.er_aktiv(dag(20)));
+        assert!(!b.er_aktiv(dag(21)));
+    }
+
+    #[test]
+    fn datoer_regnes_i_norsk_tid() {
+        let mut b = begrensning(None, None);
+        // 23:30 UTC er 00:30 neste dag i Norge, både sommer og vinter.
+        b.fradato = Some(Utc.with_ymd_and_hms(2025, 1, 10, 23, 30, 0).unwrap());
+        b.tildato = Some(Utc.with_ymd_and_hms(2025, 7, 10, 23, 30, 0).unwrap());
+
+        assert!(!b.er_aktiv(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()));
+        assert!(b.er_aktiv(NaiveDate::from_ymd_opt(2025, 1, 11).unwrap()));
+        assert!(b.er_aktiv(NaiveDate::from_ymd_opt(2025, 7, 11).unwrap()));
+        assert!(!b.er_aktiv(NaiveDate::from_ymd_opt(2025, 7, 12).unwrap()));
+    }
+
+    #[test]
+    fn fri_fra_venter_paa_overlappende_begrensninger() {
+        let begrensninger = vec![
+            begrensning(Some(0), Some(10)),
+            begrensning(Some(5), Some(15)),
+            begrensning(Some(20), None),
+        ];
+        assert_eq!(fri_fra(&begrensninger, dag(1)), Some(dag(16)));
+        assert_eq!(fri_fra(&begrensninger, dag(16)), Some(dag(16)));
+        assert_eq!(fri_fra(&begrensninger, dag(20)), None);
+    }
+
+    #[test]
+    fn beregn_slaar_opp_aarsaker() {
+        let kodenavn: Kodenavn = [crate::arkiv::response::Kodeverk {
+            id: "AARSAK$AI".to_string(),
+            beskrivelse: "Aviær influensa".to_string(),
+        }]
+        .into_iter()
+        .collect();
+
+        let aktive = AktiveBegrensninger::beregn(
+            vec![begrensning(Some(0), Some(10)), begrensning(Some(30), None)],
+            dag(3),
+            &kodenavn,
+        );
+
+        assert!(aktive.er_begrenset());
+        assert_eq!(aktive.begrensninger.len(), 1);
+        assert_eq!(
+            aktive.begrensninger[0].aarsak.navn.as_deref(),
+            Some("Aviær influensa")
+        );
+        assert_eq!(aktive.fri_fra, Some(dag(11)));
+    }
+
+    proptest! {
+        #[test]
+        fn aktiv_stemmer_med_intervallet(fra in proptest::option::of(0u64..60), lengde in proptest::option::of(0u64..60), d in 0u64..150) {
+            let til = lengde.map(|l| fra.unwrap_or(0) + l);
+            let b = begrensning(fra, til);
+            let forventet = fra.is_none_or(|f| f <= d) && til.is_none_or(|t| d <= t);
+            prop_assert_eq!(b.er_aktiv(dag(d)), forventet);
+        }
+
+        #[test]
+        fn beregn_tar_med_akkurat_de_aktive(begrensninger in proptest::collection::vec(begrensning_strategi(), 0..8), d in 0u64..150) {
+            let forventet = begrensninger.iter().filter(|b| b.er_aktiv(dag(d))).count();
+            let aktive = AktiveBegrensninger::beregn(begrensninger, dag(d), &Kodenavn::default());
+            prop_assert_eq!(aktive.begrensninger.len(), forventet);
+            prop_assert_eq!(aktive.er_begrenset(), forventet > 0);
+        }
+
+        #[test]
+        fn fri_fra_er_foerste_frie_dag(begrensninger in proptest::collection::vec(begrensning_strategi(), 0..8), d in 0u64..150) {
+            let er_begrenset = |dato: NaiveDate| begrensninger.iter().any(|b| b.er_aktiv(dato));
+            match fri_fra(&begrensninger, dag(d)) {
+                Some(fri) => {
+                    prop_assert!(fri >= dag(d));
+                    prop_assert!(!er_begrenset(fri));
+                    let mut dato = dag(d);
+                    while dato < fri {
+                        prop_assert!(er_begrenset(dato));
+                        dato = dato + Days::new(1);
+                    }
+                }
+                None => {
+                    for n in d..200 {
+                        prop_assert!(er_begrenset(dag(n)));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,20 +1,26 @@
-use crate::ejb::response_tilfeller::ALLOWED_FIELDS_TILFELLER;
+use crate::ejb::aktive_begrensninger::AktiveBegrensninger;
+use crate::ejb::filter::{BegrensningFelt, Filter, FilterFelt, TilfelleFelt};
 use crate::error::ApiError;
 use crate::error::Result;
-use serde::Deserialize;
-use serde::Serialize;
-use serde_json::Value;
-use std::collections::HashMap;
-use urlencoding::encode;
+use crate::kodeverk::response::Kodenavn;
+use chrono::NaiveDate;
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
 
 use crate::client::ApiClient;
-use crate::ejb::response_begrensninger::{
-    ALLOWED_FIELDS_BEGRENSNINGER, ApiResponseBegrensninger, Begrensning,
-};
+use crate::ejb::response_begrensninger::{ApiResponseBegrensninger, Begrensning};
 use crate::ejb::response_tilfeller::{ApiResponseTilfelle, Sykdomstilfelle};
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 use uuid::Uuid;
 
+/// Øvre grense for antall begrensninger som hentes for én produksjonsplass. Treffer vi grensen
+/// feiler oppslaget, i stedet for å beregne aktive begrensninger fra et ufullstendig utvalg.
+const MAKS_BEGRENSNINGER: u16 = 1000;
+
+/// Antall tilfeller per kall når alle tilfellene for et tilsynsobjekt hentes.
+const SIDESTORRELSE_TILFELLER: u16 = 200;
+
 pub struct EjbClient {
     api_client: ApiClient,
 }
@@ -27,67 +33,65 @@ impl EjbClient {
         }
     }
 
+    pub fn from_api_client(api_client: ApiClient) -> Self {
+        EjbClient { api_client }
+    }
+
+    /// Henter første side med tilfeller for filteret, maks `limit` stykker.
+    pub async fn hent_tilfelle(
+        &self,
+        filter: Filter<TilfelleFelt>,
+        limit: u16,
+    ) -> Result<Vec<Sykdomstilfelle>> {
+        self.hent_tilfelle_side(&filter, limit, 0).await
+    }
+
     #[tracing::instrument(
         name = "Henter tilfeller for filter",
         skip(self),
         fields(
             request_id = %Uuid::new_v4(),
             limit = %limit,
+            offset = %offset,
         )
     )]
-    pub async fn hent_tilfelle(
+    pub async fn hent_tilfelle_side(
         &self,
-        filter: HashMap<String, FilterCondition>,
+        filter: &Filter<TilfelleFelt>,
         limit: u16,
+        offset: u32,
     ) -> Result<Vec<Sykdomstilfelle>> {
-        let filter = match validate_filter_map(filter, ALLOWED_FIELDS_TILFELLER) {
-            Ok(f) => f,
-            Err(e) => return Err(e),
-        };
-
         let url = format!("{}/v1/tilfeller", self.api_client.get_base_url());
-        let url = append_filter_to_url(&url, &filter, Some(limit)).map_err(|e| {
-            ApiError::ValidationError(format!("Failed to append filter to URL: {e}"))
-        })?;
-
-        info!("Henter tilfelle fra url : {:?}", url);
+        let url = format!("{}&offset={offset}", filter.to_url(&url, Some(limit))?);
 
-        let request = self
-            .api_client
-            .get_client()
-            .get(&url)
-            .bearer_auth(self.api_client.get_token().await);
-        let response = self.api_client.send_request_with_refresh(request).await?;
+        let response: ApiResponseTilfelle = self.hent_og_deserialiser(&url, "tilfeller").await?;
+        debug!("Hentet {} tilfeller", response.results.len());
+        Ok(response.results)
+    }
 
-        let r = response.bytes().await?.clone();
-        let raw_json: serde_json::Value =
-            serde_json::from_slice(&r).map_err(|e| ApiError::ClientError {
-                resource: "serde_json".to_string(),
-                error_message: e.to_string(),
-            })?;
-        println!(
-            "Rå respons:\n{}",
-            serde_json::to_string_pretty(&raw_json).unwrap_or_default()
-        );
-
-        let de = &mut serde_json::Deserializer::from_slice(&r);
-        let result: std::result::Result<ApiResponseTilfelle, _> =
-            serde_path_to_error::deserialize(de);
-        match result {
-            Ok(obj) => {
-                println!("Deserialized: {:?}", obj.results.first());
-                Ok(obj.results)
-            }
-            Err(e) => {
-                // e.path() tells you exactly where the error occurred!
-                println!("Failed at path: {}", e.path());
-                println!("Serde error: {}", e.inner());
-                Err(ApiError::ClientError {
-                    resource: "hent_tilfelle".to_string(),
-                    error_message: "Fant ikke tilfelle i response".to_string(),
-                })
-            }
-        }
+    /// Henter alle tilfeller for filteret, side for side med `sidestorrelse` per kall.
+    ///
+    /// Neste side hentes først når forrige er lest ut av strømmen, og strømmen avsluttes
+    /// etter første side som er kortere enn `sidestorrelse`.
+    pub fn hent_alle_tilfeller(
+        &self,
+        filter: Filter<TilfelleFelt>,
+        sidestorrelse: u16,
+    ) -> impl Stream<Item = Result<Sykdomstilfelle>> + '_ {
+        let sidestorrelse = sidestorrelse.max(1);
+        stream::try_unfold((filter, Some(0u32)), move |(filter, offset)| async move {
+            let Some(offset) = offset else {
+                return Ok::<_, ApiError>(None);
+            };
+            let side = self
+                .hent_tilfelle_side(&filter, sidestorrelse, offset)
+                .await?;
+            let neste_offset = (side.len() == usize::from(sidestorrelse))
+                .then(|| offset + u32::from(sidestorrelse));
+            let side = stream::iter(side.into_iter().map(Ok::<_, ApiError>));
+            Ok(Some((side, (filter, neste_offset))))
+        })
+        .try_flatten()
     }
 
     #[tracing::instrument(
@@ -101,97 +105,150 @@ impl EjbClient {
     )]
     pub async fn hent_begrensninger(
         &self,
-        filter: HashMap<String, FilterCondition>,
+        filter: Filter<BegrensningFelt>,
         dato: Option<String>,
         limit: u16,
     ) -> Result<Vec<Begrensning>> {
-        let filter = match validate_filter_map(filter, ALLOWED_FIELDS_BEGRENSNINGER) {
-            Ok(f) => f,
-            Err(e) => return Err(e),
-        };
-
         let url = format!("{}/v1/begrensninger", self.api_client.get_base_url());
-        let mut url = append_filter_to_url(&url, &filter, Some(limit)).map_err(|e| {
-            ApiError::ValidationError(format!("Failed to append filter to URL: {e}"))
-        })?;
+        let mut url = filter.to_url(&url, Some(limit))?;
 
         if let Some(dato_verdi) = dato {
             url.push_str(&format!("&dato={dato_verdi}"));
         }
 
-        info!("Henter begrensinger fra url: {:?}", url);
+        let response: ApiResponseBegrensninger =
+            self.hent_og_deserialiser(&url, "begrensninger").await?;
+        debug!("Hentet {} begrensninger", response.results.len());
+        Ok(response.results)
+    }
+
+    /// Henter begrensningene som gjelder for produksjonsplassen `gbrnummer` på `dato`,
+    /// med årsakene slått opp i `kodenavn`.
+    pub async fn hent_aktive_begrensninger(
+        &self,
+        gbrnummer: &str,
+        dato: NaiveDate,
+        kodenavn: &Kodenavn,
+    ) -> Result<AktiveBegrensninger> {
+        let begrensninger = self
+            .hent_alle_begrensninger(BegrensningFelt::Gbrnummerref.eq(gbrnummer))
+            .await?;
+        Ok(AktiveBegrensninger::beregn(begrensninger, dato, kodenavn))
+    }
+
+    /// Som [`EjbClient::hent_aktive_begrensninger`], for alle produksjonsplasser som er
+    /// registrert på tilfeller for tilsynsobjektet.
+    pub async fn hent_aktive_begrensninger_for_tilsynsobjekt(
+        &self,
+        tilsynsobjekt_id: &str,
+        dato: NaiveDate,
+        kodenavn: &Kodenavn,
+    ) -> Result<AktiveBegrensninger> {
+        let tilfeller: Vec<Sykdomstilfelle> = self
+            .hent_alle_tilfeller(
+                TilfelleFelt::Tilsynsobjektref.eq(tilsynsobjekt_id),
+                SIDESTORRELSE_TILFELLER,
+            )
+            .try_collect()
+            .await?;
+        self.hent_aktive_begrensninger_for_tilfeller(&tilfeller, dato, kodenavn)
+            .await
+    }
+
+    /// Som [`EjbClient::hent_aktive_begrensninger`], for alle produksjonsplassene som er
+    /// registrert på `tilfeller`. Nyttig når tilfellene allerede er hentet.
+    pub async fn hent_aktive_begrensninger_for_tilfeller(
+        &self,
+        tilfeller: &[Sykdomstilfelle],
+        dato: NaiveDate,
+        kodenavn: &Kodenavn,
+    ) -> Result<AktiveBegrensninger> {
+        let gbrnumre: BTreeSet<String> = tilfeller
+            .iter()
+            .filter_map(|tilfelle| tilfelle.gbrnummerref.clone())
+            .collect();
+
+        let begrensninger = if gbrnumre.is_empty() {
+            vec![]
+        } else {
+            self.hent_alle_begrensninger(BegrensningFelt::Gbrnummerref.in_(gbrnumre))
+                .await?
+        };
+        Ok(AktiveBegrensninger::beregn(begrensninger, dato, kodenavn))
+    }
+
+    /// Henter begrensningene for filteret, og feiler dersom det er flere enn
+    /// `MAKS_BEGRENSNINGER`.
+    async fn hent_alle_begrensninger(
+        &self,
+        filter: Filter<BegrensningFelt>,
+    ) -> Result<Vec<Begrensning>> {
+        let begrensninger = self
+            .hent_begrensninger(filter, None, MAKS_BEGRENSNINGER + 1)
+            .await?;
+        if begrensninger.len() > usize::from(MAKS_BEGRENSNINGER) {
+            return Err(ApiError::ClientError {
+                resource: "ejb".to_string(),
+                error_message: format!(
+                    "Fant flere enn {MAKS_BEGRENSNINGER} begrensninger, kan ikke beregne aktive begrensninger fra et ufullstendig utvalg"
+                ),
+            });
+        }
+        Ok(begrensninger)
+    }
+
+    async fn hent_og_deserialiser<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        resource: &str,
+    ) -> Result<T> {
+        info!("Henter {resource} fra url: {url:?}");
 
         let request = self
             .api_client
             .get_client()
-            .get(&url)
+            .get(url)
             .bearer_auth(self.api_client.get_token().await);
         let response = self.api_client.send_request_with_refresh(request).await?;
 
-        let r = response.bytes().await?.clone();
-        let raw_json: serde_json::Value =
-            serde_json::from_slice(&r).map_err(|e| ApiError::ClientError {
-                resource: "serde_json".to_string(),
-                error_message: e.to_string(),
-            })?;
-        debug!(
-            "Rå respons:\n{}",
-            serde_json::to_string_pretty(&raw_json).unwrap_or_default()
-        );
-
-        let de = &mut serde_json::Deserializer::from_slice(&r);
-        let result: std::result::Result<ApiResponseBegrensninger, _> =
-            serde_path_to_error::deserialize(de);
-        match result {
-            Ok(obj) => {
-                debug!("Deserialized: {:?}", obj.results.first());
-                Ok(obj.results)
-            }
-            Err(e) => {
-                println!("Failed at path: {}", e.path());
-                println!("Serde error: {}", e.inner());
-                Err(ApiError::ClientError {
-                    resource: "hent_tilfelle".to_string(),
-                    error_message: "Fant ikke tilfelle i response".to_string(),
-                })
-            }
+        let status = response.status();
+        if !status.is_success() {
+            let error_message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!(
+                "Klarte ikke hente {resource}. error code {status}, error message {error_message}"
+            );
+            return Err(ApiError::ClientError {
+                resource: "ejb".to_string(),
+                error_message: format!(
+                    "Failed to fetch {resource}. HTTP Status: {status}, response: {error_message}"
+                ),
+            });
         }
-    }
-}
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(untagged)]
-pub enum FilterCondition {
-    SingleOp { eq: Value },
-    MultiOp(HashMap<String, Value>),
-}
-
-pub fn validate_filter_map(
-    input: HashMap<String, FilterCondition>,
-    allowed_fields: &[&str],
-) -> Result<HashMap<String, FilterCondition>> {
-    for key in input.keys() {
-        if !allowed_fields.contains(&key.as_str()) {
-            return Err(ApiError::ValidationError(format!(
-                "Ugyldig filterfelt: {key}"
-            )));
-        }
-    }
-    Ok(input)
-}
-
-pub fn append_filter_to_url(
-    base_url: &str,
-    filters: &HashMap<String, FilterCondition>,
-    limit: Option<u16>,
-) -> Result<String> {
-    let json_value = serde_json::to_value(filters)
-        .map_err(|e| ApiError::ValidationError(format!("Failed to serialize filters: {e}")))?;
-    let json_string = json_value.to_string();
-    let encoded_filter = encode(&json_string);
-    let mut url = format!("{base_url}?filter={encoded_filter}");
-    if let Some(lim) = limit {
-        url.push_str(&format!("&limit={lim}"));
+        let body = response.bytes().await?;
+        let de = &mut serde_json::Deserializer::from_slice(&body);
+        serde_path_to_error::deserialize(de).map_err(|e| {
+            // Feilteksten fra serde kan inneholde verdier fra payloaden, så vi logger bare
+            // hvor feilen oppsto.
+            let path = e.path().to_string();
+            let (linje, kolonne) = (e.inner().line(), e.inner().column());
+            error!(
+                path = %path,
+                linje,
+                kolonne,
+                "Klarte ikke deserialisere {resource} fra ejb"
+            );
+            ApiError::DeserializeError {
+                resource: resource.to_string(),
+                path,
+                error_message: format!(
+                    "{:?} error at line {linje} column {kolonne}",
+                    e.inner().classify()
+                ),
+            }
+        })
     }
-    Ok(url)
 }
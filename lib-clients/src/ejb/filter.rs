@@ -0,0 +1,496 @@
+use crate::error::{ApiError, Result};
+use chrono::NaiveDate;
+use serde_json::{Map, Value, json};
+use urlencoding::encode;
+
+const FORMAT_DATE: &str = "%Y-%m-%d";
+
+/// Typen til et felt i EJB-api-et. Brukes for å sjekke at operator og verdi passer feltet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeltType {
+    Tekst,
+    Heltall,
+    Dato,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterVerdi {
+    Tekst(String),
+    Heltall(i64),
+    Dato(NaiveDate),
+}
+
+impl FilterVerdi {
+    fn felt_type(&self) -> FeltType {
+        match self {
+            FilterVerdi::Tekst(_) => FeltType::Tekst,
+            FilterVerdi::Heltall(_) => FeltType::Heltall,
+            FilterVerdi::Dato(_) => FeltType::Dato,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            FilterVerdi::Tekst(s) => json!(s),
+            FilterVerdi::Heltall(i) => json!(i),
+            FilterVerdi::Dato(d) => json!(d.format(FORMAT_DATE).to_string()),
+        }
+    }
+}
+
+impl From<&str> for FilterVerdi {
+    fn from(value: &str) -> Self {
+        FilterVerdi::Tekst(value.to_string())
+    }
+}
+
+impl From<String> for FilterVerdi {
+    fn from(value: String) -> Self {
+        FilterVerdi::Tekst(value)
+    }
+}
+
+impl From<i32> for FilterVerdi {
+    fn from(value: i32) -> Self {
+        FilterVerdi::Heltall(value.into())
+    }
+}
+
+impl From<i64> for FilterVerdi {
+    fn from(value: i64) -> Self {
+        FilterVerdi::Heltall(value)
+    }
+}
+
+impl From<NaiveDate> for FilterVerdi {
+    fn from(value: NaiveDate) -> Self {
+        FilterVerdi::Dato(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operator {
+    Eq(FilterVerdi),
+    Ne(FilterVerdi),
+    Gt(FilterVerdi),
+    Lt(FilterVerdi),
+    In(Vec<FilterVerdi>),
+    Like(String),
+    IsNull(bool),
+}
+
+impl Operator {
+    fn navn(&self) -> &'static str {
+        match self {
+            Operator::Eq(_) => "eq",
+            Operator::Ne(_) => "ne",
+            Operator::Gt(_) => "gt",
+            Operator::Lt(_) => "lt",
+            Operator::In(_) => "in",
+            Operator::Like(_) => "like",
+            Operator::IsNull(_) => "is_null",
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Operator::Eq(v) | Operator::Ne(v) | Operator::Gt(v) | Operator::Lt(v) => v.to_json(),
+            Operator::In(verdier) => Value::Array(verdier.iter().map(|v| v.to_json()).collect()),
+            Operator::Like(s) => json!(s),
+            Operator::IsNull(b) => json!(b),
+        }
+    }
+
+    fn valider(&self, felt: &str, felt_type: FeltType) -> Result<()> {
+        let sjekk_verdi = |verdi: &FilterVerdi| {
+            if verdi.felt_type() == felt_type {
+                Ok(())
+            } else {
+                Err(ApiError::ValidationError(format!(
+                    "Feltet {felt} er {felt_type:?}, men fikk {:?} i '{}'",
+                    verdi.felt_type(),
+                    self.navn()
+                )))
+            }
+        };
+
+        match self {
+            Operator::Eq(v) | Operator::Ne(v) => sjekk_verdi(v),
+            Operator::Gt(v) | Operator::Lt(v) => {
+                if felt_type == FeltType::Tekst {
+                    return Err(ApiError::ValidationError(format!(
+                        "Operator '{}' støttes ikke for tekstfeltet {felt}",
+                        self.navn()
+                    )));
+                }
+                sjekk_verdi(v)
+            }
+            Operator::In(verdier) => {
+                if verdier.is_empty() {
+                    return Err(ApiError::ValidationError(format!(
+                        "Operator 'in' på {felt} må ha minst én verdi"
+                    )));
+                }
+                verdier.iter().try_for_each(sjekk_verdi)
+            }
+            Operator::Like(_) if felt_type != FeltType::Tekst => Err(ApiError::ValidationError(
+                format!("Operator 'like' støttes bare for tekstfelt, {felt} er {felt_type:?}"),
+            )),
+            Operator::Like(_) | Operator::IsNull(_) => Ok(()),
+        }
+    }
+}
+
+/// Et felt som kan filtreres på i EJB-api-et.
+///
+/// Operatorene lager et [`Filter`] som typesjekkes mot feltet når det serialiseres.
+pub trait FilterFelt: Copy + Sized {
+    fn navn(&self) -> &'static str;
+    fn felt_type(&self) -> FeltType;
+
+    fn eq(self, verdi: impl Into<FilterVerdi>) -> Filter<Self> {
+        Filter::betingelse(self, Operator::Eq(verdi.into()))
+    }
+
+    fn ne(self, verdi: impl Into<FilterVerdi>) -> Filter<Self> {
+        Filter::betingelse(self, Operator::Ne(verdi.into()))
+    }
+
+    fn gt(self, verdi: impl Into<FilterVerdi>) -> Filter<Self> {
+        Filter::betingelse(self, Operator::Gt(verdi.into()))
+    }
+
+    fn lt(self, verdi: impl Into<FilterVerdi>) -> Filter<Self> {
+        Filter::betingelse(self, Operator::Lt(verdi.into()))
+    }
+
+    fn in_<V: Into<FilterVerdi>>(self, verdier: impl IntoIterator<Item = V>) -> Filter<Self> {
+        Filter::betingelse(
+            self,
+            Operator::In(verdier.into_iter().map(Into::into).collect()),
+        )
+    }
+
+    fn like(self, moenster: impl Into<String>) -> Filter<Self> {
+        Filter::betingelse(self, Operator::Like(moenster.into()))
+    }
+
+    fn is_null(self, er_null: bool) -> Filter<Self> {
+        Filter::betingelse(self, Operator::IsNull(er_null))
+    }
+}
+
+/// Et sammensatt filter over feltene `F`, serialisert til `filter=`-parameteret EJB-api-et forventer.
+///
+/// Betingelser som kombineres med [`Filter::and`] på ulike felt/operatorer blir til ett objekt
+/// (`{"felt": {"eq": ..}, "annet": {"gt": ..}}`). Øvrige kombinasjoner blir `{"and": [..]}`
+/// eller `{"or": [..]}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter<F: FilterFelt> {
+    Betingelse { felt: F, operator: Operator },
+    Og(Vec<Filter<F>>),
+    Eller(Vec<Filter<F>>),
+}
+
+impl<F: FilterFelt> Filter<F> {
+    fn betingelse(felt: F, operator: Operator) -> Self {
+        Filter::Betingelse { felt, operator }
+    }
+
+    pub fn and(self, annet: Filter<F>) -> Self {
+        match self {
+            Filter::Og(mut filtre) => {
+                filtre.push(annet);
+                Filter::Og(filtre)
+            }
+            filter => Filter::Og(vec![filter, annet]),
+        }
+    }
+
+    pub fn or(self, annet: Filter<F>) -> Self {
+        match self {
+            Filter::Eller(mut filtre) => {
+                filtre.push(annet);
+                Filter::Eller(filtre)
+            }
+            filter => Filter::Eller(vec![filter, annet]),
+        }
+    }
+
+    /// Sjekker at alle operatorer og verdier passer typen til feltet de brukes på.
+    pub fn valider(&self) -> Result<()> {
+        match self {
+            Filter::Betingelse { felt, operator } => {
+                operator.valider(felt.navn(), felt.felt_type())
+            }
+            Filter::Og(filtre) | Filter::Eller(filtre) => {
+                if filtre.is_empty() {
+                    return Err(ApiError::ValidationError(
+                        "Sammensatt filter kan ikke være tomt".to_string(),
+                    ));
+                }
+                filtre.iter().try_for_each(Filter::valider)
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> Result<Value> {
+        self.valider()?;
+        Ok(self.to_json_uvalidert())
+    }
+
+    /// Lager url med `filter=` og eventuelt `limit=` for dette filteret.
+    pub fn to_url(&self, base_url: &str, limit: Option<u16>) -> Result<String> {
+        let json_string = self.to_json()?.to_string();
+        let encoded_filter = encode(&json_string);
+        let mut url = format!("{base_url}?filter={encoded_filter}");
+        if let Some(lim) = limit {
+            url.push_str(&format!("&limit={lim}"));
+        }
+        Ok(url)
+    }
+
+    fn to_json_uvalidert(&self) -> Value {
+        match self {
+            Filter::Betingelse { felt, operator } => {
+                json!({ felt.navn(): { operator.navn(): operator.to_json() } })
+            }
+            Filter::Og(filtre) => match Self::flat_og(filtre) {
+                Some(objekt) => Value::Object(objekt),
+                None => {
+                    json!({ "and": filtre.iter().map(Self::to_json_uvalidert).collect::<Vec<_>>() })
+                }
+            },
+            Filter::Eller(filtre) => {
+                json!({ "or": filtre.iter().map(Self::to_json_uvalidert).collect::<Vec<_>>() })
+            }
+        }
+    }
+
+    /// Slår sammen en og-liste av enkle betingelser til ett objekt, så lenge ingen felt/operator
+    /// gjentas.
+    fn flat_og(filtre: &[Filter<F>]) -> Option<Map<String, Value>> {
+        let mut objekt = Map::new();
+        for filter in filtre {
+            let Filter::Betingelse { felt, operator } = filter else {
+                return None;
+            };
+            let operatorer = objekt
+                .entry(felt.navn())
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()?;
+            if operatorer
+                .insert(operator.navn().to_string(), operator.to_json())
+                .is_some()
+            {
+                return None;
+            }
+        }
+        Some(objekt)
+    }
+}
+
+macro_rules! filter_felt {
+    ($(#[$meta:meta])* $navn:ident { $($variant:ident => ($felt:literal, $type:ident)),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $navn {
+            $($variant),*
+        }
+
+        impl FilterFelt for $navn {
+            fn navn(&self) -> &'static str {
+                match self {
+                    $($navn::$variant => $felt),*
+                }
+            }
+
+            fn felt_type(&self) -> FeltType {
+                match self {
+                    $($navn::$variant => FeltType::$type),*
+                }
+            }
+        }
+    };
+}
+
+filter_felt!(
+    /// Felt det kan filtreres på i `/v1/tilfeller`.
+    TilfelleFelt {
+        Idstring => ("idstring", Tekst),
+        Version => ("version", Heltall),
+        Typeid => ("typeid", Tekst),
+        Tilsynsobjektref => ("tilsynsobjektref", Tekst),
+        Virksomhetref => ("virksomhetref", Tekst),
+        Registrertdato => ("registrertdato", Dato),
+        Diagnoseid => ("diagnoseid", Tekst),
+        Mistenktsykdomid => ("mistenktsykdomid", Tekst),
+        Diagnosegrunnlagid => ("diagnosegrunnlagid", Tekst),
+        Mistenktdato => ("mistenktdato", Dato),
+        Avkreftadato => ("avkreftadato", Dato),
+        Stadfestadato => ("stadfestadato", Dato),
+        Avsluttadato => ("avsluttadato", Dato),
+        Dtype => ("dtype", Tekst),
+        Meldtvirksomhetsnavn => ("meldtvirksomhetsnavn", Tekst),
+        Meldttilsynsobjekt => ("meldttilsynsobjekt", Tekst),
+        Sykdomstilfellemapperef => ("sykdomstilfellemapperef", Tekst),
+        Artkategoriid => ("artkategoriid", Tekst),
+        Samlebehandlingref => ("samlebehandlingref", Tekst),
+        Innmeldernavn => ("innmeldernavn", Tekst),
+        Innmeldertlf => ("innmeldertlf", Tekst),
+        Doedevedmistenktdato => ("doedevedmistenktdato", Heltall),
+        Sykevedmistenktdato => ("sykevedmistenktdato", Heltall),
+        Totaltvedutbruddsdato => ("totaltvedutbruddsdato", Heltall),
+        Doedevedavsluttetdato => ("doedevedavsluttetdato", Heltall),
+        Antallpaalagtslaktet => ("antallpaalagtslaktet", Heltall),
+        Antallslaktettilkonsum => ("antallslaktettilkonsum", Heltall),
+        Gaardsnummer => ("gaardsnummer", Tekst),
+        Bruksnummer => ("bruksnummer", Tekst),
+        Merdnummer => ("merdnummer", Tekst),
+        Idpaafisk => ("idpaafisk", Tekst),
+        Kommunenummer => ("kommunenummer", Tekst),
+        Mistankegrunnlagid => ("mistankegrunnlagid", Tekst),
+        GbridentitetIdstring => ("gbridentitet_idstring", Tekst),
+        Kontaktperson => ("kontaktperson", Tekst),
+        Tlfnrkontaktperson => ("tlfnrkontaktperson", Tekst),
+        Hendelsesdato => ("hendelsesdato", Dato),
+        Hendelsestidspunkt => ("hendelsestidspunkt", Dato),
+        Ugyldig => ("ugyldig", Heltall),
+        Gbrnummerref => ("gbrnummerref", Tekst),
+        Hendelsetype => ("hendelsetype", Tekst),
+        Spesifiserthendelsetype => ("spesifiserthendelsetype", Tekst),
+        Beskrivelse => ("beskrivelse", Tekst),
+        Detaljer => ("detaljer", Tekst),
+        Strakstiltak => ("strakstiltak", Tekst),
+        Tiltaksplan => ("tiltaksplan", Tekst),
+        Mottakeligevedmistanke => ("mottakeligevedmistanke", Heltall),
+        Sykevedstadfestelse => ("sykevedstadfestelse", Heltall),
+        Doedevedstadfestelse => ("doedevedstadfestelse", Heltall),
+        Utfoertavlivetdestruert => ("utfoertavlivetdestruert", Heltall),
+        Antallvaksinert => ("antallvaksinert", Heltall),
+        Paalagtavlivetslaktet => ("paalagtavlivetslaktet", Heltall),
+        Utfoertavlivetslaktet => ("utfoertavlivetslaktet", Heltall),
+        Produsentnummer => ("produsentnummer", Tekst),
+        Produsentref => ("produsentref", Tekst),
+        Paavistvilis => ("paavistvilis", Tekst),
+        Paavistintermedia => ("paavistintermedia", Tekst),
+        Paavistglabrata => ("paavistglabrata", Tekst),
+        Paavistpilosissima => ("paavistpilosissima", Tekst),
+        Antallplanterpaavist => ("antallplanterpaavist", Heltall),
+        Sistpaavist => ("sistpaavist", Dato),
+    }
+);
+
+filter_felt!(
+    /// Felt det kan filtreres på i `/v1/begrensninger`.
+    BegrensningFelt {
+        Idstring => ("idstring", Tekst),
+        Version => ("version", Heltall),
+        Typeid => ("typeid", Tekst),
+        Handlingsloepref => ("handlingsloepref", Tekst),
+        Soeknadref => ("soeknadref", Tekst),
+        Gbrnummerref => ("gbrnummerref", Tekst),
+        Begrensningsaarsakid => ("begrensningsaarsakid", Tekst),
+        Aarsakid => ("aarsakid", Tekst),
+        Fradato => ("fradato", Dato),
+        Tildato => ("tildato", Dato),
+        Createddate => ("createddate", Dato),
+        Beskrivelse => ("beskrivelse", Tekst),
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enkel_betingelse_gir_samme_format_som_api_et() {
+        let filter = TilfelleFelt::Diagnoseid.eq("SYKDOM$FUGLEINFLUENSA");
+
+        assert_eq!(
+            filter.to_json().unwrap(),
+            json!({ "diagnoseid": { "eq": "SYKDOM$FUGLEINFLUENSA" } })
+        );
+    }
+
+    #[test]
+    fn og_slaas_sammen_til_ett_objekt() {
+        let fra = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let filter = TilfelleFelt::Kommunenummer
+            .eq("3403")
+            .and(TilfelleFelt::Registrertdato.gt(fra))
+            .and(TilfelleFelt::Registrertdato.lt(NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()));
+
+        assert_eq!(
+            filter.to_json().unwrap(),
+            json!({
+                "kommunenummer": { "eq": "3403" },
+                "registrertdato": { "gt": "2025-01-01", "lt": "2025-06-01" },
+            })
+        );
+    }
+
+    #[test]
+    fn eller_og_gjentatte_operatorer_blir_lister() {
+        let filter = BegrensningFelt::Typeid
+            .eq("A")
+            .or(BegrensningFelt::Typeid.in_(["B", "C"]))
+            .and(BegrensningFelt::Gbrnummerref.is_null(false));
+
+        assert_eq!(
+            filter.to_json().unwrap(),
+            json!({ "and": [
+                { "or": [
+                    { "typeid": { "eq": "A" } },
+                    { "typeid": { "in": ["B", "C"] } },
+                ]},
+                { "gbrnummerref": { "is_null": false } },
+            ]})
+        );
+    }
+
+    #[test]
+    fn begrensning_filtrerer_paa_aarsak_og_periode() {
+        let dato = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let filter = BegrensningFelt::Begrensningsaarsakid
+            .eq("AARSAK$SMITTE")
+            .and(BegrensningFelt::Fradato.lt(dato))
+            .and(BegrensningFelt::Tildato.gt(dato));
+
+        assert_eq!(
+            filter.to_json().unwrap(),
+            json!({
+                "begrensningsaarsakid": { "eq": "AARSAK$SMITTE" },
+                "fradato": { "lt": "2025-03-01" },
+                "tildato": { "gt": "2025-03-01" },
+            })
+        );
+    }
+
+    #[test]
+    fn avviser_verdi_med_feil_type() {
+        assert!(TilfelleFelt::Ugyldig.eq("0").to_json().is_err());
+        assert!(TilfelleFelt::Diagnoseid.gt("A").to_json().is_err());
+        assert!(TilfelleFelt::Ugyldig.like("%0%").to_json().is_err());
+        assert!(
+            BegrensningFelt::Typeid
+                .in_(Vec::<String>::new())
+                .to_json()
+                .is_err()
+        );
+        assert!(TilfelleFelt::Ugyldig.eq(0).to_json().is_ok());
+    }
+
+    #[test]
+    fn url_inneholder_kodet_filter_og_limit() {
+        let url = TilfelleFelt::Ugyldig
+            .eq(0)
+            .to_url("https://ejb/v1/tilfeller", Some(10))
+            .unwrap();
+
+        assert_eq!(
+            url,
+            "https://ejb/v1/tilfeller?filter=%7B%22ugyldig%22%3A%7B%22eq%22%3A0%7D%7D&limit=10"
+        );
+    }
+}
@@ -1,3 +1,6 @@
+pub mod aktive_begrensninger;
 pub mod ejb_client;
+pub mod filter;
 pub mod response_begrensninger;
 pub mod response_tilfeller;
+pub mod tilfelle;
@@ -2,19 +2,6 @@ use crate::ejb::response_tilfeller::date_format;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-pub const ALLOWED_FIELDS_BEGRENSNINGER: &[&str] = &[
-    "idstring",
-    "version",
-    "typeid",
-    "handlingsloepref",
-    "soeknadref",
-    "gbrnummerref",
-    "begrensningaarsakid",
-    "aarsakid",
-    "createddate",
-    "beskrivelse",
-];
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ApiResponseBegrensninger {
     pub results: Vec<Begrensning>,
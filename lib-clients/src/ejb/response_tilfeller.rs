@@ -1,70 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-pub const ALLOWED_FIELDS_TILFELLER: &[&str] = &[
-    "idstring",
-    "version",
-    "typeid",
-    "tilsynsobjektref",
-    "virksomhetref",
-    "registrertdato",
-    "diagnoseid",
-    "mistenktsykdomid",
-    "diagnosegrunnlagid",
-    "mistenktdato",
-    "avkreftadato",
-    "stadfestadato",
-    "avsluttadato",
-    "dtype",
-    "meldtvirksomhetsnavn",
-    "meldttilsynsobjekt",
-    "sykdomstilfellemapperef",
-    "artkategoriid",
-    "samlebehandlingref",
-    "innmeldernavn",
-    "innmeldertlf",
-    "doedevedmistenktdato",
-    "sykevedmistenktdato",
-    "totaltvedutbruddsdato",
-    "doedevedavsluttetdato",
-    "antallpaalagtslaktet",
-    "antallslaktettilkonsum",
-    "gaardsnummer",
-    "bruksnummer",
-    "merdnummer",
-    "idpaafisk",
-    "kommunenummer",
-    "mistankegrunnlagid",
-    "gbridentitet_idstring",
-    "kontaktperson",
-    "tlfnrkontaktperson",
-    "hendelsesdato",
-    "hendelsestidspunkt",
-    "ugyldig",
-    "gbrnummerref",
-    "hendelsetype",
-    "spesifiserthendelsetype",
-    "beskrivelse",
-    "detaljer",
-    "strakstiltak",
-    "tiltaksplan",
-    "mottakeligevedmistanke",
-    "sykevedstadfestelse",
-    "doedevedstadfestelse",
-    "utfoertavlivetdestruert",
-    "antallvaksinert",
-    "paalagtavlivetslaktet",
-    "utfoertavlivetslaktet",
-    "produsentnummer",
-    "produsentref",
-    "paavistvilis",
-    "paavistintermedia",
-    "paavistglabrata",
-    "paavistpilosissima",
-    "antallplanterpaavist",
-    "sistpaavist",
-];
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponseTilfelle {
     pub results: Vec<Sykdomstilfelle>,
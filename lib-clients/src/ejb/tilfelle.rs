@@ -0,0 +1,217 @@
+use crate::ejb::response_tilfeller::Sykdomstilfelle;
+use crate::kodeverk::response::Kodenavn;
+use chrono::{DateTime, Utc};
+use lib_schemas::typer::kommunenummer::Kommunenummer;
+use lib_schemas::typer::matrikkelnummer::Matrikkelnummer;
+use lib_schemas::typer::produsentnummer::Produsentnummer;
+use serde::Serialize;
+
+/// Hvor langt et sykdomstilfelle har kommet, utledet fra datoene på tilfellet.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Diagnosestatus {
+    /// Registrert uten at mistanke er satt.
+    Registrert,
+    Mistenkt,
+    Stadfestet,
+    Avkreftet,
+    Avsluttet,
+}
+
+/// Én hendelse i tidslinjen til et tilfelle.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TilfelleHendelse {
+    pub status: Diagnosestatus,
+    pub dato: DateTime<Utc>,
+}
+
+/// En kodeverk-id med visningsnavnet fra kodeverket, når det finnes.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Kode {
+    pub id: String,
+    pub navn: Option<String>,
+}
+
+impl Kode {
+    pub(crate) fn fra(id: &str, kodenavn: &Kodenavn) -> Self {
+        Kode {
+            id: id.to_string(),
+            navn: kodenavn.navn(id).map(str::to_string),
+        }
+    }
+}
+
+/// Typet visning av et [`Sykdomstilfelle`] fra EJB.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tilfelle {
+    pub id: String,
+    pub tilsynsobjekt_id: String,
+    pub virksomhet_id: String,
+    pub tilfelletype: Kode,
+    pub status: Diagnosestatus,
+    pub diagnose: Option<Kode>,
+    pub mistenkt_sykdom: Option<Kode>,
+    pub artkategori: Option<Kode>,
+    pub kommunenummer: Option<Kommunenummer>,
+    pub matrikkelnummer: Option<Matrikkelnummer>,
+    pub produsentnummer: Option<Produsentnummer>,
+    pub tidslinje: Vec<TilfelleHendelse>,
+    pub ugyldig: bool,
+}
+
+impl Sykdomstilfelle {
+    /// Gjeldende status. Avslutning og avkreftelse går foran stadfesting, som går foran mistanke.
+    pub fn diagnosestatus(&self) -> Diagnosestatus {
+        if self.avsluttadato.is_some() {
+            Diagnosestatus::Avsluttet
+        } else if self.avkreftadato.is_some() {
+            Diagnosestatus::Avkreftet
+        } else if self.stadfestadato.is_some() {
+            Diagnosestatus::Stadfestet
+        } else if self.mistenktdato.is_some() {
+            Diagnosestatus::Mistenkt
+        } else {
+            Diagnosestatus::Registrert
+        }
+    }
+
+    /// Tidslinjen registrert → mistenkt → stadfestet/avkreftet → avsluttet, sortert på dato.
+    /// Steg uten dato er utelatt.
+    pub fn tidslinje(&self) -> Vec<TilfelleHendelse> {
+        let mut hendelser: Vec<TilfelleHendelse> = [
+            (Diagnosestatus::Registrert, self.registrertdato),
+            (Diagnosestatus::Mistenkt, self.mistenktdato),
+            (Diagnosestatus::Stadfestet, self.stadfestadato),
+            (Diagnosestatus::Avkreftet, self.avkreftadato),
+            (Diagnosestatus::Avsluttet, self.avsluttadato),
+        ]
+        .into_iter()
+        .filter_map(|(status, dato)| dato.map(|dato| TilfelleHendelse { status, dato }))
+        .collect();
+        hendelser.sort_by_key(|h| (h.dato, h.status));
+        hendelser
+    }
+
+    pub fn kommunenummer(&self) -> Option<Kommunenummer> {
+        self.kommunenummer
+            .as_deref()
+            .and_then(|knr| Kommunenummer::new(knr).ok())
+    }
+
+    pub fn matrikkelnummer(&self) -> Option<Matrikkelnummer> {
+        Matrikkelnummer::new(
+            self.kommunenummer()?,
+            self.gaardsnummer.as_deref()?,
+            self.bruksnummer.as_deref()?,
+        )
+        .ok()
+    }
+
+    pub fn produsentnummer(&self) -> Option<Produsentnummer> {
+        self.produsentnummer
+            .as_deref()
+            .and_then(|nr| Produsentnummer::new(nr).ok())
+    }
+
+    /// Lager den typede visningen, med visningsnavn slått opp i `kodenavn`.
+    pub fn til_tilfelle(&self, kodenavn: &Kodenavn) -> Tilfelle {
+        let kode = |id: &Option<String>| id.as_deref().map(|id| Kode::fra(id, kodenavn));
+        Tilfelle {
+            id: self.idstring.clone(),
+            tilsynsobjekt_id: self.tilsynsobjektref.clone(),
+            virksomhet_id: self.virksomhetref.clone(),
+            tilfelletype: Kode::fra(&self.typeid, kodenavn),
+            status: self.diagnosestatus(),
+            diagnose: kode(&self.diagnoseid),
+            mistenkt_sykdom: kode(&self.mistenktsykdomid),
+            artkategori: kode(&self.artkategoriid),
+            kommunenummer: self.kommunenummer(),
+            matrikkelnummer: self.matrikkelnummer(),
+            produsentnummer: self.produsentnummer(),
+            tidslinje: self.tidslinje(),
+            ugyldig: self.ugyldig != 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arkiv::response::Kodeverk;
+    use serde_json::json;
+
+    fn tilfelle() -> Sykdomstilfelle {
+        serde_json::from_value(json!({
+            "idstring": "T1",
+            "version": 1,
+            "typeid": "TILFELLETYPE$DYR",
+            "tilsynsobjektref": "TO1",
+            "virksomhetref": "V1",
+            "registrertdato": "2025-03-01",
+            "diagnoseid": "SYKDOM$AI",
+            "mistenktdato": "2025-03-02 10:00:00",
+            "stadfestadato": "2025-03-05",
+            "avkreftadato": null,
+            "avsluttadato": null,
+            "hendelsesdato": null,
+            "hendelsestidspunkt": null,
+            "sistpaavist": null,
+            "dtype": "Dyresykdomstilfelle",
+            "kommunenummer": "3403",
+            "gaardsnummer": "12",
+            "bruksnummer": "5",
+            "produsentnummer": "3403123456",
+            "ugyldig": 0,
+            "mottakeligevedmistanke": 0,
+            "sykevedstadfestelse": 0,
+            "doedevedstadfestelse": 0,
+            "utfoertavlivetdestruert": 0,
+            "antallvaksinert": 0,
+            "paalagtavlivetslaktet": 0,
+            "utfoertavlivetslaktet": 0,
+            "antallplanterpaavist": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn status_og_tidslinje_utledes_fra_datoer() {
+        let mut tilfelle = tilfelle();
+        assert_eq!(tilfelle.diagnosestatus(), Diagnosestatus::Stadfestet);
+
+        let steg: Vec<_> = tilfelle.tidslinje().iter().map(|h| h.status).collect();
+        assert_eq!(
+            steg,
+            vec![
+                Diagnosestatus::Registrert,
+                Diagnosestatus::Mistenkt,
+                Diagnosestatus::Stadfestet
+            ]
+        );
+
+        tilfelle.avsluttadato = tilfelle.stadfestadato.map(|d| d + chrono::Days::new(30));
+        assert_eq!(tilfelle.diagnosestatus(), Diagnosestatus::Avsluttet);
+    }
+
+    #[test]
+    fn typede_identifikatorer_og_kodenavn() {
+        let kodenavn: Kodenavn = [Kodeverk {
+            id: "SYKDOM$AI".to_string(),
+            beskrivelse: "Aviær influensa".to_string(),
+        }]
+        .into_iter()
+        .collect();
+
+        let visning = tilfelle().til_tilfelle(&kodenavn);
+
+        assert_eq!(
+            visning.matrikkelnummer.map(|m| m.to_string()).as_deref(),
+            Some("3403-12/5")
+        );
+        assert_eq!(
+            visning.diagnose.and_then(|d| d.navn).as_deref(),
+            Some("Aviær influensa")
+        );
+        assert_eq!(visning.tilfelletype.navn, None);
+        assert!(!visning.ugyldig);
+    }
+}
@@ -12,6 +12,12 @@ pub enum ApiError {
     TokenError(String),
     #[error("Failed to parse response: {0}")]
     ParseError(String),
+    #[error("Failed to deserialize {resource} at {path}: {error_message}")]
+    DeserializeError {
+        resource: String,
+        path: String,
+        error_message: String,
+    },
     #[error("Client error in {resource}: {error_message}")]
     ClientError {
         resource: String,
@@ -18,4 +18,7 @@ pub enum GeonorgeError {
 
     #[error("API error: {0}")]
     ApiError(String),
+
+    #[error("Unsupported coordinate system: {0}")]
+    UnsupportedKoordinatsystem(String),
 }
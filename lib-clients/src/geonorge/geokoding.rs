@@ -0,0 +1,381 @@
+use crate::geonorge::geonorge_client::normalize_house_letter;
+use crate::geonorge::response::{AddressNumber, AddressResult};
+
+/// En adresse som skal geokodes. Postnummer og poststed er valgfrie, men gir bedre treff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdresseForesporsel {
+    pub adresse: String,
+    pub postnummer: Option<String>,
+    pub poststed: Option<String>,
+}
+
+impl AdresseForesporsel {
+    pub fn new(adresse: impl Into<String>) -> Self {
+        Self {
+            adresse: adresse.into(),
+            postnummer: None,
+            poststed: None,
+        }
+    }
+
+    pub fn postnummer(mut self, postnummer: impl Into<String>) -> Self {
+        self.postnummer = Some(postnummer.into());
+        self
+    }
+
+    pub fn poststed(mut self, poststed: impl Into<String>) -> Self {
+        self.poststed = Some(poststed.into());
+        self
+    }
+
+    /// Teksten som sendes til fritekstsøket.
+    pub(crate) fn sokestreng(&self) -> String {
+        [
+            Some(normalize_house_letter(self.adresse.trim())),
+            self.postnummer.clone(),
+            self.poststed.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|del| !del.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+/// Innstillinger for batch-geokoding.
+#[derive(Debug, Clone)]
+pub struct GeokodingOppsett {
+    /// Maks antall samtidige kall mot GeoNorge.
+    pub samtidighet: usize,
+    /// Antall kandidater som hentes per adresse.
+    pub maks_kandidater: u32,
+    /// Laveste score som regnes som et treff.
+    pub treffgrense: f64,
+    /// Minste avstand i score mellom beste og nest beste kandidat før treffet regnes som entydig.
+    pub tvetydighetsmargin: f64,
+}
+
+impl Default for GeokodingOppsett {
+    fn default() -> Self {
+        Self {
+            samtidighet: 8,
+            maks_kandidater: 10,
+            treffgrense: 0.8,
+            tvetydighetsmargin: 0.1,
+        }
+    }
+}
+
+/// Hvor godt en kandidat passer med forespørselen, fra 0.0 til 1.0.
+///
+/// Delscorene for postnummer og poststed er `None` når forespørselen ikke oppga dem, og de
+/// teller da ikke med i totalen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matchscore {
+    pub total: f64,
+    pub gatenavn: f64,
+    pub husnummer: f64,
+    pub bokstav: f64,
+    pub postnummer: Option<f64>,
+    pub poststed: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Kandidat {
+    pub adresse: AddressResult,
+    pub score: Matchscore,
+}
+
+impl Kandidat {
+    pub fn koordinater(&self) -> Option<(f64, f64)> {
+        self.adresse.get_koordinater()
+    }
+}
+
+/// Utfallet av geokodingen for én adresse. Kandidatene er sortert med høyest score først.
+#[derive(Debug, Clone)]
+pub enum Geokoding {
+    /// Én kandidat er klart best og over treffgrensen.
+    Treff {
+        beste: Box<Kandidat>,
+        alternativer: Vec<Kandidat>,
+    },
+    /// Flere kandidater er over treffgrensen med nesten lik score.
+    Tvetydig { kandidater: Vec<Kandidat> },
+    /// Ingen kandidater er over treffgrensen.
+    IngenTreff { kandidater: Vec<Kandidat> },
+}
+
+impl Geokoding {
+    pub fn treff(&self) -> Option<&Kandidat> {
+        match self {
+            Geokoding::Treff { beste, .. } => Some(beste),
+            Geokoding::Tvetydig { .. } | Geokoding::IngenTreff { .. } => None,
+        }
+    }
+
+    pub(crate) fn vurder(
+        foresporsel: &AdresseForesporsel,
+        adresser: Vec<AddressResult>,
+        oppsett: &GeokodingOppsett,
+    ) -> Self {
+        let mut kandidater: Vec<Kandidat> = adresser
+            .into_iter()
+            .map(|adresse| Kandidat {
+                score: score(foresporsel, &adresse),
+                adresse,
+            })
+            .collect();
+        kandidater.sort_by(|a, b| b.score.total.total_cmp(&a.score.total));
+
+        let beste = kandidater.first().map(|k| k.score.total).unwrap_or(0.0);
+        if beste < oppsett.treffgrense {
+            return Geokoding::IngenTreff { kandidater };
+        }
+        let nest_beste = kandidater.get(1).map(|k| k.score.total);
+        if nest_beste.is_some_and(|n| beste - n < oppsett.tvetydighetsmargin) {
+            return Geokoding::Tvetydig { kandidater };
+        }
+
+        let alternativer = kandidater.split_off(1);
+        Geokoding::Treff {
+            beste: Box::new(kandidater.remove(0)),
+            alternativer,
+        }
+    }
+}
+
+const VEKT_GATENAVN: f64 = 0.4;
+const VEKT_HUSNUMMER: f64 = 0.25;
+const VEKT_BOKSTAV: f64 = 0.1;
+const VEKT_POSTNUMMER: f64 = 0.15;
+const VEKT_POSTSTED: f64 = 0.1;
+
+/// En adresse delt opp i gatenavn, husnummer og bokstav.
+#[derive(Debug, PartialEq, Eq)]
+struct Adressedeler {
+    gatenavn: String,
+    husnummer: Option<u32>,
+    bokstav: String,
+}
+
+/// Deler opp "Storgata 12 b, Oslo" i gatenavn, husnummer og bokstav. Alt etter første komma
+/// ignoreres.
+fn del_opp(adresse: &str) -> Adressedeler {
+    let adresse = adresse.split(',').next().unwrap_or_default();
+    let normalisert = normalize_house_letter(adresse.trim());
+    let mut ord: Vec<&str> = normalisert.split_whitespace().collect();
+
+    let nummerdel = ord
+        .last()
+        .filter(|siste| siste.starts_with(|c: char| c.is_ascii_digit()) && ord.len() > 1)
+        .copied();
+    let (husnummer, bokstav) = match nummerdel {
+        Some(del) => {
+            ord.pop();
+            let sifre: String = del.chars().take_while(char::is_ascii_digit).collect();
+            let bokstav: String = del.chars().skip(sifre.len()).collect();
+            (sifre.parse().ok(), bokstav.to_lowercase())
+        }
+        None => (None, String::new()),
+    };
+
+    Adressedeler {
+        gatenavn: normaliser_gatenavn(&ord.join(" ")),
+        husnummer,
+        bokstav,
+    }
+}
+
+/// Små bokstaver og utskrevne forkortelser, slik at "Storgt." og "Storgata" blir like.
+fn normaliser_gatenavn(gatenavn: &str) -> String {
+    let gatenavn = gatenavn.to_lowercase();
+    let gatenavn = gatenavn.trim_end_matches('.');
+    for (forkortelse, full) in [("gt", "gata"), ("vn", "veien"), ("v", "vegen")] {
+        if let Some(stamme) = gatenavn.strip_suffix(forkortelse)
+            && (stamme.is_empty() || !stamme.ends_with(|c: char| "aeiouyæøå".contains(c)))
+        {
+            return format!("{stamme}{full}");
+        }
+    }
+    gatenavn.to_string()
+}
+
+fn kandidatdeler(adresse: &AddressResult) -> Adressedeler {
+    let mut deler = del_opp(&adresse.address_text);
+    if let Some(gatenavn) = &adresse.street_name {
+        deler.gatenavn = normaliser_gatenavn(gatenavn);
+    }
+    match &adresse.number {
+        Some(AddressNumber::Number(nummer)) => deler.husnummer = u32::try_from(*nummer).ok(),
+        Some(AddressNumber::Detailed { husnummer, bokstav }) => {
+            deler.husnummer = husnummer.and_then(|n| u32::try_from(n).ok());
+            if let Some(bokstav) = bokstav {
+                deler.bokstav = bokstav.to_lowercase();
+            }
+        }
+        None => {}
+    }
+    if let Some(bokstav) = &adresse.letter {
+        deler.bokstav = bokstav.to_lowercase();
+    }
+    deler
+}
+
+pub(crate) fn score(foresporsel: &AdresseForesporsel, adresse: &AddressResult) -> Matchscore {
+    let onsket = del_opp(&foresporsel.adresse);
+    let funnet = kandidatdeler(adresse);
+
+    let gatenavn = likhet(&onsket.gatenavn, &funnet.gatenavn);
+    let husnummer = lik(onsket.husnummer == funnet.husnummer);
+    let bokstav = lik(onsket.bokstav == funnet.bokstav);
+    let postnummer = foresporsel
+        .postnummer
+        .as_deref()
+        .map(|p| lik(Some(p.trim()) == adresse.postal_code.as_deref()));
+    let poststed = foresporsel.poststed.as_deref().map(|p| {
+        likhet(
+            &p.trim().to_lowercase(),
+            &adresse.city.as_deref().unwrap_or_default().to_lowercase(),
+        )
+    });
+
+    let mut sum = VEKT_GATENAVN * gatenavn + VEKT_HUSNUMMER * husnummer + VEKT_BOKSTAV * bokstav;
+    let mut vekter = VEKT_GATENAVN + VEKT_HUSNUMMER + VEKT_BOKSTAV;
+    if let Some(postnummer) = postnummer {
+        sum += VEKT_POSTNUMMER * postnummer;
+        vekter += VEKT_POSTNUMMER;
+    }
+    if let Some(poststed) = poststed {
+        sum += VEKT_POSTSTED * poststed;
+        vekter += VEKT_POSTSTED;
+    }
+
+    Matchscore {
+        total: sum / vekter,
+        gatenavn,
+        husnummer,
+        bokstav,
+        postnummer,
+        poststed,
+    }
+}
+
+fn lik(lik: bool) -> f64 {
+    if lik { 1.0 } else { 0.0 }
+}
+
+/// Normalisert Levenshtein-likhet, 1.0 for like strenger.
+fn likhet(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let lengste = a.len().max(b.len());
+    if lengste == 0 {
+        return 1.0;
+    }
+
+    let mut forrige: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut rad = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let bytte = forrige[j] + usize::from(ca != cb);
+            rad[j + 1] = bytte.min(forrige[j + 1] + 1).min(rad[j] + 1);
+        }
+        forrige = rad;
+    }
+    1.0 - forrige[b.len()] as f64 / lengste as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adresse(
+        gate: &str,
+        nummer: i32,
+        bokstav: &str,
+        postnummer: &str,
+        poststed: &str,
+    ) -> AddressResult {
+        let tekst = format!("{gate} {nummer}{bokstav}");
+        serde_json::from_value(serde_json::json!({
+            "adressetekst": tekst,
+            "adressenavn": gate,
+            "nummer": nummer,
+            "bokstav": bokstav,
+            "postnummer": postnummer,
+            "poststed": poststed,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn deler_opp_adresse_med_bokstav() {
+        assert_eq!(
+            del_opp("Storgt. 12 B, Oslo"),
+            Adressedeler {
+                gatenavn: "storgata".to_string(),
+                husnummer: Some(12),
+                bokstav: "b".to_string(),
+            }
+        );
+        assert_eq!(del_opp("Tårnvegen").husnummer, None);
+    }
+
+    #[test]
+    fn eksakt_treff_gir_full_score() {
+        let foresporsel = AdresseForesporsel::new("Tårnvegen 41")
+            .postnummer("2380")
+            .poststed("Brumunddal");
+        let score = score(
+            &foresporsel,
+            &adresse("Tårnvegen", 41, "", "2380", "BRUMUNDDAL"),
+        );
+        assert_eq!(score.total, 1.0);
+    }
+
+    #[test]
+    fn feil_bokstav_og_postnummer_trekker_ned() {
+        let foresporsel = AdresseForesporsel::new("Tårnvegen 41b").postnummer("2380");
+        let riktig = score(
+            &foresporsel,
+            &adresse("Tårnvegen", 41, "B", "2380", "BRUMUNDDAL"),
+        );
+        let feil = score(
+            &foresporsel,
+            &adresse("Tårnvegen", 41, "", "2381", "BRUMUNDDAL"),
+        );
+        assert_eq!(riktig.total, 1.0);
+        assert!(feil.total < 0.8, "score var {}", feil.total);
+        assert_eq!(feil.postnummer, Some(0.0));
+    }
+
+    #[test]
+    fn like_kandidater_er_tvetydige() {
+        let foresporsel = AdresseForesporsel::new("Storgata 1");
+        let vurdering = Geokoding::vurder(
+            &foresporsel,
+            vec![
+                adresse("Storgata", 1, "", "0155", "OSLO"),
+                adresse("Storgata", 1, "", "2000", "LILLESTRØM"),
+            ],
+            &GeokodingOppsett::default(),
+        );
+        assert!(
+            matches!(vurdering, Geokoding::Tvetydig { ref kandidater } if kandidater.len() == 2)
+        );
+    }
+
+    #[test]
+    fn ingen_kandidater_gir_ingen_treff() {
+        let vurdering = Geokoding::vurder(
+            &AdresseForesporsel::new("Finnes ikke 999"),
+            Vec::new(),
+            &GeokodingOppsett::default(),
+        );
+        assert!(
+            matches!(vurdering, Geokoding::IngenTreff { ref kandidater } if kandidater.is_empty())
+        );
+    }
+}
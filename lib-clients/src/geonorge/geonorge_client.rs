@@ -1,47 +1,159 @@
+use crate::geonorge::geokoding::{AdresseForesporsel, Geokoding, GeokodingOppsett};
+use crate::geonorge::matrikkel::{Kommune, KommuneResponse, MatrikkelAdresser};
 use crate::geonorge::response::{AddressResult, GeonorgeResponse, KommuneOgFylke};
-use crate::geonorge::{GeonorgeError, Koordinater, Result};
+use crate::geonorge::sone::{self, MedAvstand, Soneplassering, Soner};
+use crate::geonorge::{Epsg, GeonorgeError, Koordinater, Result};
+use futures::{StreamExt, stream};
+use lib_schemas::typer::kommunenummer::Kommunenummer;
+use lib_schemas::typer::matrikkelnummer::Matrikkelnummer;
+use reqwest::StatusCode;
 use reqwest_middleware::reqwest::Client;
-use reqwest_middleware::{ClientBuilder as MiddlewareClientBuilder, ClientWithMiddleware};
+use reqwest_middleware::{
+    ClientBuilder as MiddlewareClientBuilder, ClientWithMiddleware, RequestBuilder,
+};
 use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::time::Duration;
 use tracing;
 
 #[derive(Clone)]
 pub struct GeoNorgeClient {
     client: ClientWithMiddleware,
+    adresser_url: String,
+    kommuneinfo_url: String,
 }
 
 const ADRESSER_URL: &str = "https://ws.geonorge.no/adresser/v1";
 const PUNKTSOK_URL: &str = "https://api.kartverket.no/kommuneinfo/v1";
+/// Største radius vi søker med i ett kall mot punktsøket. Større områder deles opp i fliser.
+const MAKS_SOKERADIUS_M: f64 = 2_500.0;
+const PUNKTSOK_TREFF_PER_SIDE: usize = 1000;
+const SAMTIDIGE_FLISER: usize = 4;
+const USER_AGENT: &str = concat!("lib-clients/", env!("CARGO_PKG_VERSION"));
+
+/// Builder for [`GeoNorgeClient`]. Standardverdiene peker mot de offentlige api-ene.
+pub struct GeoNorgeClientBuilder {
+    adresser_url: String,
+    kommuneinfo_url: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    max_retries: u32,
+    user_agent: String,
+}
 
-impl GeoNorgeClient {
+impl Default for GeoNorgeClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeoNorgeClientBuilder {
     pub fn new() -> Self {
-        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
-        let client = MiddlewareClientBuilder::new(Client::new())
-            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-            .build();
+        Self {
+            adresser_url: ADRESSER_URL.to_string(),
+            kommuneinfo_url: PUNKTSOK_URL.to_string(),
+            timeout: None,
+            connect_timeout: None,
+            max_retries: 3,
+            user_agent: USER_AGENT.to_string(),
+        }
+    }
 
-        Self { client }
+    /// Base-url til adresse-api-et, for eksempel `https://ws.geonorge.no/adresser/v1`.
+    pub fn adresser_url(mut self, url: impl Into<String>) -> Self {
+        self.adresser_url = url.into().trim_end_matches('/').to_string();
+        self
     }
 
-    #[tracing::instrument(
-        name = "Henter kommune og fylke basert på koordinater fra GeoNorge.",
-        skip(self, koordinater)
-    )]
-    async fn search_punkt(&self, koordinater: &Koordinater) -> Result<KommuneOgFylke> {
-        let url = format!(
-            "{}/punkt?nord={}&ost={}&koordsys=4258",
-            PUNKTSOK_URL, koordinater.latitude, koordinater.longitude
-        );
+    /// Base-url til kommuneinfo-api-et, for eksempel `https://api.kartverket.no/kommuneinfo/v1`.
+    pub fn kommuneinfo_url(mut self, url: impl Into<String>) -> Self {
+        self.kommuneinfo_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-        let response = self.client.get(&url).send().await.map_err(|e| {
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Antall nye forsøk med eksponentiell backoff ved transiente feil. `0` skrur av retry.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn build(self) -> Result<GeoNorgeClient> {
+        let mut http_client = Client::builder().user_agent(self.user_agent);
+        if let Some(timeout) = self.timeout {
+            http_client = http_client.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
+        let http_client = http_client
+            .build()
+            .map_err(|e| GeonorgeError::RequestError(e.to_string()))?;
+
+        let mut client = MiddlewareClientBuilder::new(http_client);
+        if self.max_retries > 0 {
+            let retry_policy =
+                ExponentialBackoff::builder().build_with_max_retries(self.max_retries);
+            client = client.with(RetryTransientMiddleware::new_with_policy(retry_policy));
+        }
+
+        Ok(GeoNorgeClient {
+            client: client.build(),
+            adresser_url: self.adresser_url,
+            kommuneinfo_url: self.kommuneinfo_url,
+        })
+    }
+}
+
+impl GeoNorgeClient {
+    pub fn new() -> Self {
+        Self::builder()
+            .build()
+            .expect("Failed to build GeoNorgeClient with default configuration")
+    }
+
+    pub fn builder() -> GeoNorgeClientBuilder {
+        GeoNorgeClientBuilder::new()
+    }
+
+    /// Sender requesten og deserialiserer svaret. 404 gir [`GeonorgeError::NoResults`].
+    async fn hent_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        resource: &str,
+    ) -> Result<T> {
+        let response = request.send().await.map_err(|e| {
             tracing::error!("Klarte ikke sende request til GeoNorge");
             GeonorgeError::RequestError(e.to_string())
         })?;
 
-        if !response.status().is_success() {
-            let status = response.status();
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Err(GeonorgeError::NoResults(format!("Fant ikke {resource}")));
+        }
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            tracing::error!("Geonorge API returned error {}: {}", status, error_text);
+            tracing::error!(
+                "Geonorge API returned error {} for {}: {}",
+                status,
+                resource,
+                error_text
+            );
             return Err(GeonorgeError::ApiError(format!(
                 "API returned status {}: {}",
                 status, error_text
@@ -53,13 +165,25 @@ impl GeoNorgeClient {
             GeonorgeError::RequestError(e.to_string())
         })?;
 
-        let geonorge_response: KommuneOgFylke =
-            serde_json::from_str(&response_text).map_err(|e| {
-                tracing::error!("Failed to parse Geonorge response: {}", e);
-                GeonorgeError::ParseError(e.to_string())
-            })?;
+        serde_json::from_str(&response_text).map_err(|e| {
+            tracing::error!("Failed to parse Geonorge response for {}: {}", resource, e);
+            GeonorgeError::ParseError(e.to_string())
+        })
+    }
+
+    #[tracing::instrument(
+        name = "Henter kommune og fylke basert på koordinater fra GeoNorge.",
+        skip(self, koordinater)
+    )]
+    async fn search_punkt(&self, koordinater: &Koordinater) -> Result<KommuneOgFylke> {
+        let koordinater = koordinater.til(Epsg::Euref89);
+        let url = format!(
+            "{}/punkt?nord={}&ost={}&koordsys=4258",
+            self.kommuneinfo_url, koordinater.latitude, koordinater.longitude
+        );
 
-        Ok(geonorge_response)
+        self.hent_json(self.client.get(&url), "kommune og fylke")
+            .await
     }
 
     async fn get_address_object(
@@ -74,47 +198,22 @@ impl GeoNorgeClient {
             ));
         }
 
-        let url = format!("{}/sok", ADRESSER_URL);
+        let url = format!("{}/sok", self.adresser_url);
         let normalized_address = normalize_house_letter(adresse);
 
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("fuzzy", "false"),
-                ("adressetekst", &normalized_address),
-                ("postnummer", postnummer),
-                ("poststed", poststed),
-                ("treffPerSide", "1"),
-                ("side", "0"),
-            ])
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Klarte ikke sende request til GeoNorge");
-                GeonorgeError::RequestError(e.to_string())
-            })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            tracing::error!("Geonorge API returned error {}: {}", status, error_text);
-            return Err(GeonorgeError::ApiError(format!(
-                "API returned status {}: {}",
-                status, error_text
-            )));
-        }
-
-        let response_text = response.text().await.map_err(|e| {
-            tracing::error!("Failed to read response body: {}", e);
-            GeonorgeError::RequestError(e.to_string())
-        })?;
-
-        let geonorge_response: GeonorgeResponse =
-            serde_json::from_str(&response_text).map_err(|e| {
-                tracing::error!("Failed to parse Geonorge response: {}", e);
-                GeonorgeError::ParseError(e.to_string())
-            })?;
+        let geonorge_response: GeonorgeResponse = self
+            .hent_json(
+                self.client.get(&url).query(&[
+                    ("fuzzy", "false"),
+                    ("adressetekst", &normalized_address),
+                    ("postnummer", postnummer),
+                    ("poststed", poststed),
+                    ("treffPerSide", "1"),
+                    ("side", "0"),
+                ]),
+                "adresse",
+            )
+            .await?;
 
         match geonorge_response.addresses.into_iter().next() {
             Some(address) => Ok(address),
@@ -131,43 +230,18 @@ impl GeoNorgeClient {
 
         let normalized_address = normalize_house_letter(address);
 
-        let url = format!("{}/sok", ADRESSER_URL);
-
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("sok", normalized_address.as_str()),
-                ("treffPerSide", "10"),
-                ("side", "0"),
-            ])
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Klarte ikke sende request til GeoNorge");
-                GeonorgeError::RequestError(e.to_string())
-            })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            tracing::error!("Geonorge API returned error {}: {}", status, error_text);
-            return Err(GeonorgeError::ApiError(format!(
-                "API returned status {}: {}",
-                status, error_text
-            )));
-        }
-
-        let response_text = response.text().await.map_err(|e| {
-            tracing::error!("Failed to read response body: {}", e);
-            GeonorgeError::RequestError(e.to_string())
-        })?;
-
-        let geonorge_response: GeonorgeResponse =
-            serde_json::from_str(&response_text).map_err(|e| {
-                tracing::error!("Failed to parse Geonorge response: {}", e);
-                GeonorgeError::ParseError(e.to_string())
-            })?;
+        let url = format!("{}/sok", self.adresser_url);
+
+        let geonorge_response: GeonorgeResponse = self
+            .hent_json(
+                self.client.get(&url).query(&[
+                    ("sok", normalized_address.as_str()),
+                    ("treffPerSide", "10"),
+                    ("side", "0"),
+                ]),
+                "adresser",
+            )
+            .await?;
 
         if geonorge_response.addresses.is_empty() {
             return Err(GeonorgeError::NoResults(address.to_string()));
@@ -184,43 +258,19 @@ impl GeoNorgeClient {
         &self,
         koordinater: &Koordinater,
     ) -> Result<Option<AddressResult>> {
-        let url = format!("{}/punktsok", ADRESSER_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("lat", koordinater.latitude.to_string()),
-                ("lon", koordinater.longitude.to_string()),
-                ("radius", "100".to_string()), // meter
-                ("treffPerSide", "1".to_string()),
-            ])
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Klarte ikke sende request til GeoNorge");
-                GeonorgeError::RequestError(e.to_string())
-            })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            tracing::error!("Geonorge API returned error {}: {}", status, error_text);
-            return Err(GeonorgeError::ApiError(format!(
-                "API returned status {}: {}",
-                status, error_text
-            )));
-        }
-
-        let response_text = response.text().await.map_err(|e| {
-            tracing::error!("Failed to read response body: {}", e);
-            GeonorgeError::RequestError(e.to_string())
-        })?;
-
-        let geonorge_response: GeonorgeResponse =
-            serde_json::from_str(&response_text).map_err(|e| {
-                tracing::error!("Failed to parse Geonorge response: {}", e);
-                GeonorgeError::ParseError(e.to_string())
-            })?;
+        let koordinater = koordinater.til(Epsg::Euref89);
+        let url = format!("{}/punktsok", self.adresser_url);
+        let geonorge_response: GeonorgeResponse = self
+            .hent_json(
+                self.client.get(&url).query(&[
+                    ("lat", koordinater.latitude.to_string()),
+                    ("lon", koordinater.longitude.to_string()),
+                    ("radius", "100".to_string()), // meter
+                    ("treffPerSide", "1".to_string()),
+                ]),
+                "adresse på koordinat",
+            )
+            .await?;
 
         let address = geonorge_response.addresses.into_iter().next();
 
@@ -232,6 +282,211 @@ impl GeoNorgeClient {
         }
     }
 
+    #[tracing::instrument(
+        name = "Henter adressekandidater fra GeoNorge.",
+        skip(self, foresporsel, oppsett)
+    )]
+    async fn search_candidates(
+        &self,
+        foresporsel: &AdresseForesporsel,
+        oppsett: &GeokodingOppsett,
+    ) -> Result<Vec<AddressResult>> {
+        let sok = foresporsel.sokestreng();
+        if sok.trim().is_empty() {
+            return Err(GeonorgeError::InvalidAddress(
+                "Address cannot be empty".to_string(),
+            ));
+        }
+
+        let url = format!("{}/sok", self.adresser_url);
+        let geonorge_response: GeonorgeResponse = self
+            .hent_json(
+                self.client.get(&url).query(&[
+                    ("sok", sok),
+                    ("fuzzy", "true".to_string()),
+                    ("treffPerSide", oppsett.maks_kandidater.to_string()),
+                    ("side", "0".to_string()),
+                ]),
+                "adressekandidater",
+            )
+            .await?;
+
+        Ok(geonorge_response.addresses)
+    }
+
+    /// Geokoder én adresse med fuzzy-søk og scorer kandidatene mot forespørselen.
+    pub async fn geokod_adresse(
+        &self,
+        foresporsel: &AdresseForesporsel,
+        oppsett: &GeokodingOppsett,
+    ) -> Result<Geokoding> {
+        let kandidater = self.search_candidates(foresporsel, oppsett).await?;
+        Ok(Geokoding::vurder(foresporsel, kandidater, oppsett))
+    }
+
+    /// Geokoder mange adresser med begrenset samtidighet. Resultatene kommer i samme rekkefølge
+    /// som forespørslene, og feil for én adresse stopper ikke resten.
+    pub async fn geokod_adresser(
+        &self,
+        foresporsler: &[AdresseForesporsel],
+        oppsett: &GeokodingOppsett,
+    ) -> Vec<(AdresseForesporsel, Result<Geokoding>)> {
+        stream::iter(foresporsler)
+            .map(|foresporsel| async move {
+                let resultat = self.geokod_adresse(foresporsel, oppsett).await;
+                if let Err(e) = &resultat {
+                    tracing::warn!("Klarte ikke geokode {:?}: {}", foresporsel.adresse, e);
+                }
+                (foresporsel.clone(), resultat)
+            })
+            .buffered(oppsett.samtidighet.max(1))
+            .collect()
+            .await
+    }
+
+    #[tracing::instrument(
+        name = "Henter adresser på matrikkelenhet fra GeoNorge.",
+        skip(self),
+        fields(matrikkelnummer = %matrikkelnummer)
+    )]
+    pub async fn get_adresser_fra_matrikkel(
+        &self,
+        matrikkelnummer: &Matrikkelnummer,
+    ) -> Result<MatrikkelAdresser> {
+        let url = format!("{}/sok", self.adresser_url);
+        let geonorge_response: GeonorgeResponse = self
+            .hent_json(
+                self.client.get(&url).query(&[
+                    (
+                        "kommunenummer",
+                        matrikkelnummer.kommunenummer.as_str().to_string(),
+                    ),
+                    ("gardsnummer", matrikkelnummer.gaardsnummer.to_string()),
+                    ("bruksnummer", matrikkelnummer.bruksnummer.to_string()),
+                    ("treffPerSide", "1000".to_string()),
+                    ("side", "0".to_string()),
+                ]),
+                "adresser på matrikkelenhet",
+            )
+            .await?;
+
+        Ok(MatrikkelAdresser::new(
+            matrikkelnummer.clone(),
+            geonorge_response.addresses,
+        ))
+    }
+
+    /// Henter navn, fylke og avgrensningsboks for en kommune.
+    #[tracing::instrument(
+        name = "Henter kommune fra GeoNorge.",
+        skip(self),
+        fields(kommunenummer = %kommunenummer)
+    )]
+    pub async fn get_kommune(&self, kommunenummer: &Kommunenummer) -> Result<Kommune> {
+        let url = format!("{}/kommuner/{}", self.kommuneinfo_url, kommunenummer);
+        let kommune: KommuneResponse = self
+            .hent_json(self.client.get(&url), &format!("kommune {kommunenummer}"))
+            .await?;
+
+        Kommune::try_from(kommune)
+    }
+
+    async fn search_punktsok_side(
+        &self,
+        senter: &Koordinater,
+        radius_meter: f64,
+        side: usize,
+    ) -> Result<Vec<AddressResult>> {
+        let url = format!("{}/punktsok", self.adresser_url);
+        let geonorge_response: GeonorgeResponse = self
+            .hent_json(
+                self.client.get(&url).query(&[
+                    ("lat", senter.latitude.to_string()),
+                    ("lon", senter.longitude.to_string()),
+                    ("radius", radius_meter.ceil().to_string()),
+                    ("treffPerSide", PUNKTSOK_TREFF_PER_SIDE.to_string()),
+                    ("side", side.to_string()),
+                ]),
+                "adresser i punktsøk",
+            )
+            .await?;
+
+        Ok(geonorge_response.addresses)
+    }
+
+    async fn search_punktsok_alle_sider(
+        &self,
+        senter: &Koordinater,
+        radius_meter: f64,
+    ) -> Result<Vec<AddressResult>> {
+        let mut adresser = Vec::new();
+        for side in 0.. {
+            let treff = self
+                .search_punktsok_side(senter, radius_meter, side)
+                .await?;
+            let siste_side = treff.len() < PUNKTSOK_TREFF_PER_SIDE;
+            adresser.extend(treff);
+            if siste_side {
+                break;
+            }
+        }
+        Ok(adresser)
+    }
+
+    /// Henter alle adresser innenfor `radius_meter` fra `senter`, med avstand, nærmeste først.
+    ///
+    /// Store radiuser deles opp i flere mindre søk som slås sammen og dedupliseres.
+    #[tracing::instrument(
+        name = "Henter adresser innenfor radius fra GeoNorge.",
+        skip(self, senter)
+    )]
+    pub async fn get_adresser_innenfor_radius(
+        &self,
+        senter: &Koordinater,
+        radius_meter: f64,
+    ) -> Result<Vec<MedAvstand<AddressResult>>> {
+        let fliser = sone::fliser(senter, radius_meter, MAKS_SOKERADIUS_M);
+        let fliseradius = radius_meter.min(MAKS_SOKERADIUS_M);
+        let sider: Vec<Vec<AddressResult>> = stream::iter(&fliser)
+            .map(|flis| self.search_punktsok_alle_sider(flis, fliseradius))
+            .buffer_unordered(SAMTIDIGE_FLISER)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+
+        let mut sett = HashSet::new();
+        let adresser = sider
+            .into_iter()
+            .flatten()
+            .filter_map(|adresse| adresse.koordinater.map(|k| (adresse, k)))
+            .filter(|(adresse, _)| {
+                sett.insert((
+                    adresse.municipality_number.clone(),
+                    adresse.address_text.clone(),
+                ))
+            });
+
+        let mut adresser = sone::med_avstand(senter, adresser);
+        adresser.retain(|adresse| adresse.avstand_meter <= radius_meter);
+        Ok(adresser)
+    }
+
+    /// Henter alle adresser i beskyttelses- og overvåkingssonen rundt et utbrudd.
+    pub async fn get_adresser_i_soner(
+        &self,
+        utbrudd: &Koordinater,
+        soner: &Soner,
+    ) -> Result<Vec<Soneplassering<AddressResult>>> {
+        let adresser = self
+            .get_adresser_innenfor_radius(utbrudd, soner.overvaaking_meter)
+            .await?;
+        Ok(adresser
+            .into_iter()
+            .map(|adresse| adresse.i_sone(soner))
+            .collect())
+    }
+
     pub async fn get_koordinater(&self, address: &str) -> Result<Option<(f64, f64)>> {
         let results = self.search_address(address).await?;
         Ok(results.first().and_then(|r| r.get_koordinater()))
@@ -249,6 +504,7 @@ impl GeoNorgeClient {
         Ok(results.get_koordinater())
     }
 
+    /// Koordinatene kan være i hvilket som helst støttet system, de normaliseres til EUREF89.
     pub async fn get_addresse_fra_koordinater(
         &self,
         koordinater: &Koordinater,
@@ -256,6 +512,7 @@ impl GeoNorgeClient {
         self.search_address_from_coordinate(koordinater).await
     }
 
+    /// Koordinatene kan være i hvilket som helst støttet system, de normaliseres til EUREF89.
     pub async fn get_kommune_og_fylke_from_koordinat(
         &self,
         koordinater: &Koordinater,
@@ -270,7 +527,7 @@ impl Default for GeoNorgeClient {
     }
 }
 
-fn normalize_house_letter(s: &str) -> String {
+pub(crate) fn normalize_house_letter(s: &str) -> String {
     let chars: Vec<char> = s.chars().collect();
     let mut out = String::with_capacity(s.len());
     let mut i = 0;
@@ -313,24 +570,3 @@ fn normalize_house_letter(s: &str) -> String {
 
     out
 }
-
-#[cfg(test)]
-mod tests {
-    use crate::geonorge::GeoNorgeClient;
-
-    #[tokio::test]
-    async fn test_get_address_object() {
-        let adresse = "Tårnvegen 41";
-        let postnummer = "2380";
-        let poststed = "Brumunddal";
-
-        let geonorge_client = GeoNorgeClient::new();
-        let adresse_obj = geonorge_client
-            .get_address_object(adresse, postnummer, poststed)
-            .await
-            .unwrap();
-        let koordinater = adresse_obj.get_koordinater();
-
-        assert_eq!(koordinater, Some((60.87750554180073, 10.92918075347748)))
-    }
-}
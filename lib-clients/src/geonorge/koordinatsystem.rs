@@ -0,0 +1,264 @@
+use crate::geonorge::GeonorgeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Koordinatsystemene vi mottar tilsynsdata i.
+///
+/// EUREF89 og WGS84 behandles som like. Forskjellen er under en meter i Norge, som er godt
+/// innenfor nøyaktigheten til adresse- og punktoppslag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Epsg {
+    /// EPSG:4258, EUREF89 geografisk (lat/lon). Det GeoNorge-api-ene bruker.
+    #[default]
+    Euref89,
+    /// EPSG:4326, WGS84 geografisk (lat/lon). Det GPS og mobiler gir.
+    Wgs84,
+    /// EPSG:25832, EUREF89 UTM sone 32.
+    Utm32,
+    /// EPSG:25833, EUREF89 UTM sone 33.
+    Utm33,
+    /// EPSG:25835, EUREF89 UTM sone 35.
+    Utm35,
+}
+
+impl Epsg {
+    pub fn kode(&self) -> u32 {
+        match self {
+            Epsg::Euref89 => 4258,
+            Epsg::Wgs84 => 4326,
+            Epsg::Utm32 => 25832,
+            Epsg::Utm33 => 25833,
+            Epsg::Utm35 => 25835,
+        }
+    }
+
+    pub fn er_geografisk(&self) -> bool {
+        matches!(self, Epsg::Euref89 | Epsg::Wgs84)
+    }
+
+    /// Sentralmeridianen i grader for UTM-sonene.
+    fn sentralmeridian(&self) -> Option<f64> {
+        match self {
+            Epsg::Utm32 => Some(9.0),
+            Epsg::Utm33 => Some(15.0),
+            Epsg::Utm35 => Some(27.0),
+            Epsg::Euref89 | Epsg::Wgs84 => None,
+        }
+    }
+}
+
+impl TryFrom<u32> for Epsg {
+    type Error = GeonorgeError;
+
+    fn try_from(kode: u32) -> Result<Self, Self::Error> {
+        match kode {
+            4258 => Ok(Epsg::Euref89),
+            4326 => Ok(Epsg::Wgs84),
+            25832 => Ok(Epsg::Utm32),
+            25833 => Ok(Epsg::Utm33),
+            25835 => Ok(Epsg::Utm35),
+            other => Err(GeonorgeError::UnsupportedKoordinatsystem(other.to_string())),
+        }
+    }
+}
+
+impl FromStr for Epsg {
+    type Err = GeonorgeError;
+
+    /// Godtar både `4258` og `EPSG:4258`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let kode = s.trim();
+        let kode = kode
+            .strip_prefix("EPSG:")
+            .or_else(|| kode.strip_prefix("epsg:"))
+            .unwrap_or(kode);
+        kode.parse::<u32>()
+            .map_err(|_| GeonorgeError::UnsupportedKoordinatsystem(s.to_string()))
+            .and_then(Epsg::try_from)
+    }
+}
+
+impl fmt::Display for Epsg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EPSG:{}", self.kode())
+    }
+}
+
+impl Serialize for Epsg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Epsg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Kode(u32),
+            Tekst(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Kode(kode) => Epsg::try_from(kode),
+            Raw::Tekst(tekst) => tekst.parse(),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+// Transversal Mercator på GRS80-ellipsoiden med Krüger-rekkene til tredje orden, som gir
+// millimeternøyaktighet innenfor en UTM-sone.
+const A: f64 = 6_378_137.0;
+const F: f64 = 1.0 / 298.257_222_101;
+const K0: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.0;
+
+struct Kruger {
+    a_hat: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+    n: f64,
+}
+
+fn kruger() -> Kruger {
+    let n = F / (2.0 - F);
+    let (n2, n3) = (n * n, n * n * n);
+    Kruger {
+        a_hat: A / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+        alpha: [
+            n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0,
+            13.0 * n2 / 48.0 - 3.0 * n3 / 5.0,
+            61.0 * n3 / 240.0,
+        ],
+        beta: [
+            n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0,
+            n2 / 48.0 + n3 / 15.0,
+            17.0 * n3 / 480.0,
+        ],
+        delta: [
+            2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3,
+            7.0 * n2 / 3.0 - 8.0 * n3 / 5.0,
+            56.0 * n3 / 15.0,
+        ],
+        n,
+    }
+}
+
+/// Geografisk (grader) til UTM (nord, øst) i meter.
+pub(crate) fn geografisk_til_utm(lat: f64, lon: f64, sentralmeridian: f64) -> (f64, f64) {
+    let k = kruger();
+    let phi = lat.to_radians();
+    let dlam = (lon - sentralmeridian).to_radians();
+    let c = 2.0 * k.n.sqrt() / (1.0 + k.n);
+
+    let t = (phi.sin().atanh() - c * (c * phi.sin()).atanh()).sinh();
+    let xi_p = (t / dlam.cos()).atan();
+    let eta_p = (dlam.sin() / (1.0 + t * t).sqrt()).atanh();
+
+    let (mut xi, mut eta) = (xi_p, eta_p);
+    for (j, alpha) in k.alpha.iter().enumerate() {
+        let m = 2.0 * (j as f64 + 1.0);
+        xi += alpha * (m * xi_p).sin() * (m * eta_p).cosh();
+        eta += alpha * (m * xi_p).cos() * (m * eta_p).sinh();
+    }
+
+    (K0 * k.a_hat * xi, FALSE_EASTING + K0 * k.a_hat * eta)
+}
+
+/// UTM (nord, øst) i meter til geografisk (lat, lon) i grader.
+pub(crate) fn utm_til_geografisk(nord: f64, ost: f64, sentralmeridian: f64) -> (f64, f64) {
+    let k = kruger();
+    let xi = nord / (K0 * k.a_hat);
+    let eta = (ost - FALSE_EASTING) / (K0 * k.a_hat);
+
+    let (mut xi_p, mut eta_p) = (xi, eta);
+    for (j, beta) in k.beta.iter().enumerate() {
+        let m = 2.0 * (j as f64 + 1.0);
+        xi_p -= beta * (m * xi).sin() * (m * eta).cosh();
+        eta_p -= beta * (m * xi).cos() * (m * eta).sinh();
+    }
+
+    let chi = (xi_p.sin() / eta_p.cosh()).asin();
+    let mut phi = chi;
+    for (j, delta) in k.delta.iter().enumerate() {
+        phi += delta * (2.0 * (j as f64 + 1.0) * chi).sin();
+    }
+    let lam = (eta_p.sinh() / xi_p.cos()).atan();
+
+    (phi.to_degrees(), sentralmeridian + lam.to_degrees())
+}
+
+/// Transformerer et punkt (første akse, andre akse) fra `fra` til `til`.
+///
+/// For geografiske system er aksene (lat, lon), for UTM (nord, øst).
+pub(crate) fn transformer(punkt: (f64, f64), fra: Epsg, til: Epsg) -> (f64, f64) {
+    if fra == til || (fra.er_geografisk() && til.er_geografisk()) {
+        return punkt;
+    }
+    let (lat, lon) = match fra.sentralmeridian() {
+        Some(cm) => utm_til_geografisk(punkt.0, punkt.1, cm),
+        None => punkt,
+    };
+    match til.sentralmeridian() {
+        Some(cm) => geografisk_til_utm(lat, lon, cm),
+        None => (lat, lon),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naer(a: (f64, f64), b: (f64, f64), toleranse: f64) -> bool {
+        (a.0 - b.0).abs() < toleranse && (a.1 - b.1).abs() < toleranse
+    }
+
+    #[test]
+    fn sentralmeridianen_gir_false_easting() {
+        let (nord, ost) = geografisk_til_utm(60.0, 9.0, 9.0);
+        assert!((ost - 500_000.0).abs() < 1e-6);
+        assert!((nord - 6_651_411.19).abs() < 0.01, "nord var {nord}");
+    }
+
+    #[test]
+    fn kjente_punkter_i_sone_32_33_og_35() {
+        // Referanseverdier beregnet uavhengig med Snyder sine rekker.
+        let brumunddal = geografisk_til_utm(60.87750554180073, 10.92918075347748, 9.0);
+        assert!(naer(brumunddal, (6_750_684.00, 604_732.50), 0.05));
+
+        let brumunddal_33 = geografisk_til_utm(60.87750554180073, 10.92918075347748, 15.0);
+        assert!(naer(brumunddal_33, (6_756_003.79, 279_076.91), 0.5));
+
+        let tromso = geografisk_til_utm(69.6492, 18.9553, 27.0);
+        assert!(naer(tromso, (7_747_295.77, 188_546.73), 0.5));
+    }
+
+    #[test]
+    fn rundtur_mellom_alle_system() {
+        let start = (60.87750554180073, 10.92918075347748);
+        let system = [
+            Epsg::Euref89,
+            Epsg::Wgs84,
+            Epsg::Utm32,
+            Epsg::Utm33,
+            Epsg::Utm35,
+        ];
+        for fra in system {
+            for til in system {
+                let i_fra = transformer(start, Epsg::Euref89, fra);
+                let tilbake = transformer(transformer(i_fra, fra, til), til, Epsg::Euref89);
+                assert!(naer(tilbake, start, 1e-7), "{fra} -> {til}: {tilbake:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn epsg_parses_fra_tekst_og_tall() {
+        assert_eq!("EPSG:25833".parse::<Epsg>().unwrap(), Epsg::Utm33);
+        assert_eq!("4326".parse::<Epsg>().unwrap(), Epsg::Wgs84);
+        assert!("EPSG:3857".parse::<Epsg>().is_err());
+        assert_eq!(serde_json::from_str::<Epsg>("25835").unwrap(), Epsg::Utm35);
+    }
+}
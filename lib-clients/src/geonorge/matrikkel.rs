@@ -0,0 +1,142 @@
+use crate::geonorge::{AddressResult, GeonorgeError, Koordinater, Result};
+use lib_schemas::typer::matrikkelnummer::Matrikkelnummer;
+use serde::{Deserialize, Serialize};
+
+/// Adressene som ligger på en matrikkelenhet, med et representativt punkt for kartvisning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrikkelAdresser {
+    pub matrikkelnummer: Matrikkelnummer,
+    pub adresser: Vec<AddressResult>,
+    /// Midtpunktet av adressepunktene, `None` når enheten ikke har adresser.
+    pub representasjonspunkt: Option<Koordinater>,
+}
+
+impl MatrikkelAdresser {
+    pub(crate) fn new(matrikkelnummer: Matrikkelnummer, adresser: Vec<AddressResult>) -> Self {
+        let punkter: Vec<Koordinater> = adresser.iter().filter_map(|a| a.koordinater).collect();
+        let representasjonspunkt = (!punkter.is_empty()).then(|| {
+            let antall = punkter.len() as f64;
+            Koordinater::new(
+                punkter.iter().map(|p| p.latitude).sum::<f64>() / antall,
+                punkter.iter().map(|p| p.longitude).sum::<f64>() / antall,
+            )
+        });
+        Self {
+            matrikkelnummer,
+            adresser,
+            representasjonspunkt,
+        }
+    }
+}
+
+/// Minste rektangel som dekker kommunen, i EUREF89.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Avgrensningsboks {
+    pub sorvest: Koordinater,
+    pub nordost: Koordinater,
+}
+
+impl Avgrensningsboks {
+    pub fn inneholder(&self, punkt: &Koordinater) -> bool {
+        let punkt = punkt.til(self.sorvest.epsg);
+        (self.sorvest.latitude..=self.nordost.latitude).contains(&punkt.latitude)
+            && (self.sorvest.longitude..=self.nordost.longitude).contains(&punkt.longitude)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kommune {
+    pub kommunenavn: String,
+    pub kommunenummer: String,
+    pub fylkesnavn: String,
+    pub fylkesnummer: String,
+    pub avgrensningsboks: Option<Avgrensningsboks>,
+}
+
+/// Svaret fra `kommuneinfo/v1/kommuner/{kommunenummer}`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KommuneResponse {
+    kommunenavn_norsk: Option<String>,
+    kommunenavn: String,
+    kommunenummer: String,
+    fylkesnavn: String,
+    fylkesnummer: String,
+    avgrensningsboks: Option<GeoJsonPolygon>,
+}
+
+/// GeoJSON-polygon med koordinater som `[lon, lat]`.
+#[derive(Debug, Deserialize)]
+struct GeoJsonPolygon {
+    coordinates: Vec<Vec<[f64; 2]>>,
+}
+
+impl TryFrom<KommuneResponse> for Kommune {
+    type Error = GeonorgeError;
+
+    fn try_from(response: KommuneResponse) -> Result<Self> {
+        let avgrensningsboks = response
+            .avgrensningsboks
+            .map(|polygon| {
+                let punkter: Vec<[f64; 2]> = polygon.coordinates.into_iter().flatten().collect();
+                if punkter.is_empty() {
+                    return Err(GeonorgeError::ParseError(
+                        "avgrensningsboks uten koordinater".to_string(),
+                    ));
+                }
+                let (min, max) = punkter.iter().fold(
+                    ([f64::MAX, f64::MAX], [f64::MIN, f64::MIN]),
+                    |(min, max), [lon, lat]| {
+                        (
+                            [min[0].min(*lon), min[1].min(*lat)],
+                            [max[0].max(*lon), max[1].max(*lat)],
+                        )
+                    },
+                );
+                Ok(Avgrensningsboks {
+                    sorvest: Koordinater::new(min[1], min[0]),
+                    nordost: Koordinater::new(max[1], max[0]),
+                })
+            })
+            .transpose()?;
+
+        Ok(Kommune {
+            kommunenavn: response.kommunenavn_norsk.unwrap_or(response.kommunenavn),
+            kommunenummer: response.kommunenummer,
+            fylkesnavn: response.fylkesnavn,
+            fylkesnummer: response.fylkesnummer,
+            avgrensningsboks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avgrensningsboks_fra_geojson() {
+        let response: KommuneResponse = serde_json::from_value(serde_json::json!({
+            "kommunenavn": "Ringsaker",
+            "kommunenavnNorsk": "Ringsaker",
+            "kommunenummer": "3411",
+            "fylkesnavn": "Innlandet",
+            "fylkesnummer": "34",
+            "avgrensningsboks": {
+                "type": "Polygon",
+                "coordinates": [[
+                    [10.32, 60.63], [11.27, 60.63], [11.27, 61.26], [10.32, 61.26], [10.32, 60.63]
+                ]]
+            }
+        }))
+        .unwrap();
+
+        let kommune = Kommune::try_from(response).unwrap();
+        let boks = kommune.avgrensningsboks.unwrap();
+
+        assert_eq!(boks.sorvest, Koordinater::new(60.63, 10.32));
+        assert_eq!(boks.nordost, Koordinater::new(61.26, 11.27));
+        assert!(boks.inneholder(&Koordinater::new(60.8775, 10.9291)));
+        assert!(!boks.inneholder(&Koordinater::new(59.9111, 10.7505)));
+    }
+}
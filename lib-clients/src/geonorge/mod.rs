@@ -1,7 +1,15 @@
 pub mod error;
+pub mod geokoding;
 pub mod geonorge_client;
+pub mod koordinatsystem;
+pub mod matrikkel;
 pub mod response;
+pub mod sone;
 
 pub use error::{GeonorgeError, Result};
-pub use geonorge_client::GeoNorgeClient;
+pub use geokoding::{AdresseForesporsel, Geokoding, GeokodingOppsett, Kandidat, Matchscore};
+pub use geonorge_client::{GeoNorgeClient, GeoNorgeClientBuilder};
+pub use koordinatsystem::Epsg;
+pub use matrikkel::{Avgrensningsboks, Kommune, MatrikkelAdresser};
 pub use response::{AddressResult, GeonorgeResponse, Koordinater};
+pub use sone::{MedAvstand, Sone, Soneplassering, Soner};
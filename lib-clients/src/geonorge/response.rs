@@ -1,3 +1,4 @@
+use crate::geonorge::koordinatsystem::{Epsg, transformer};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +20,9 @@ pub struct AddressResult {
     #[serde(rename = "nummer")]
     pub number: Option<AddressNumber>,
 
+    #[serde(rename = "bokstav")]
+    pub letter: Option<String>,
+
     #[serde(rename = "postnummer")]
     pub postal_code: Option<String>,
 
@@ -47,13 +51,63 @@ pub enum AddressNumber {
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Et punkt med koordinatsystemet det er oppgitt i.
+///
+/// For geografiske system (EUREF89/WGS84) er `latitude`/`longitude` grader. For UTM-sonene
+/// holder de nord og øst i meter, se [`Koordinater::nord`] og [`Koordinater::ost`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Koordinater {
     #[serde(rename = "lat")]
     pub latitude: f64,
 
     #[serde(rename = "lon")]
     pub longitude: f64,
+
+    #[serde(default)]
+    pub epsg: Epsg,
+}
+
+impl Koordinater {
+    /// Geografiske koordinater i EUREF89 (EPSG:4258).
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self::geografisk(Epsg::Euref89, latitude, longitude)
+    }
+
+    /// Geografiske koordinater i oppgitt system, typisk [`Epsg::Wgs84`] fra GPS.
+    pub fn geografisk(epsg: Epsg, latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+            epsg,
+        }
+    }
+
+    /// UTM-koordinater (nord, øst) i meter.
+    pub fn utm(epsg: Epsg, nord: f64, ost: f64) -> Self {
+        Self {
+            latitude: nord,
+            longitude: ost,
+            epsg,
+        }
+    }
+
+    pub fn nord(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn ost(&self) -> f64 {
+        self.longitude
+    }
+
+    /// Transformerer punktet til et annet koordinatsystem.
+    pub fn til(&self, epsg: Epsg) -> Koordinater {
+        let (forste, andre) = transformer((self.latitude, self.longitude), self.epsg, epsg);
+        Self {
+            latitude: forste,
+            longitude: andre,
+            epsg,
+        }
+    }
 }
 
 impl AddressResult {
@@ -0,0 +1,262 @@
+use crate::geonorge::{Epsg, Koordinater};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::f64::consts::PI;
+
+/// Middelradius for jorda i meter (IUGG).
+const JORDRADIUS_M: f64 = 6_371_008.8;
+
+/// Storsirkelavstand i meter mellom to punkt, uansett koordinatsystem.
+pub fn avstand_meter(a: &Koordinater, b: &Koordinater) -> f64 {
+    let a = a.til(Epsg::Euref89);
+    let b = b.til(Epsg::Euref89);
+    let (lat_a, lat_b) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let dlat = lat_b - lat_a;
+    let dlon = (b.longitude - a.longitude).to_radians();
+
+    let h = (dlat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * JORDRADIUS_M * h.sqrt().asin()
+}
+
+/// Punktet `ost` meter mot øst og `nord` meter mot nord fra `senter`. Nøyaktig nok for
+/// avstander på noen titalls kilometer.
+pub(crate) fn forskyv(senter: &Koordinater, ost: f64, nord: f64) -> Koordinater {
+    let senter = senter.til(Epsg::Euref89);
+    let dlat = (nord / JORDRADIUS_M).to_degrees();
+    let dlon = (ost / (JORDRADIUS_M * senter.latitude.to_radians().cos())).to_degrees();
+    Koordinater::new(senter.latitude + dlat, senter.longitude + dlon)
+}
+
+/// Sentrene for søk med radius `fliseradius` som til sammen dekker en sirkel med `radius`.
+///
+/// Fliseradiusen er den største radiusen vi søker med mot api-et. Flisene ligger i et kvadratisk
+/// rutenett der hver rute er innskrevet i søkesirkelen sin, så hele området blir dekket.
+pub(crate) fn fliser(senter: &Koordinater, radius: f64, fliseradius: f64) -> Vec<Koordinater> {
+    if radius <= fliseradius {
+        return vec![senter.til(Epsg::Euref89)];
+    }
+    let steg = fliseradius * 2f64.sqrt();
+    let antall = (radius / steg).ceil() as i64;
+    let mut fliser = Vec::new();
+    for i in -antall..=antall {
+        for j in -antall..=antall {
+            let (ost, nord) = (i as f64 * steg, j as f64 * steg);
+            if (ost * ost + nord * nord).sqrt() <= radius + fliseradius {
+                fliser.push(forskyv(senter, ost, nord));
+            }
+        }
+    }
+    fliser
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sone {
+    Beskyttelsessone,
+    Overvaakingssone,
+    Utenfor,
+}
+
+/// Radiusene for sonene rundt et utbrudd, i meter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Soner {
+    pub beskyttelse_meter: f64,
+    pub overvaaking_meter: f64,
+}
+
+impl Default for Soner {
+    /// 3 km beskyttelsessone og 10 km overvåkingssone.
+    fn default() -> Self {
+        Self {
+            beskyttelse_meter: 3_000.0,
+            overvaaking_meter: 10_000.0,
+        }
+    }
+}
+
+impl Soner {
+    pub fn sone(&self, avstand_meter: f64) -> Sone {
+        if avstand_meter <= self.beskyttelse_meter {
+            Sone::Beskyttelsessone
+        } else if avstand_meter <= self.overvaaking_meter {
+            Sone::Overvaakingssone
+        } else {
+            Sone::Utenfor
+        }
+    }
+}
+
+/// Et objekt (adresse, tilsynsobjekt, ...) med avstanden fra et senterpunkt.
+#[derive(Debug, Clone, Serialize)]
+pub struct MedAvstand<T> {
+    pub objekt: T,
+    pub koordinater: Koordinater,
+    pub avstand_meter: f64,
+}
+
+impl<T> MedAvstand<T> {
+    /// Plasserer objektet i en sone, med senterpunktet som utbrudd.
+    pub fn i_sone(self, soner: &Soner) -> Soneplassering<T> {
+        Soneplassering {
+            sone: soner.sone(self.avstand_meter),
+            objekt: self.objekt,
+            koordinater: self.koordinater,
+            avstand_meter: self.avstand_meter,
+        }
+    }
+}
+
+/// Et objekt (adresse, tilsynsobjekt, ...) plassert i en sone rundt et utbrudd.
+#[derive(Debug, Clone, Serialize)]
+pub struct Soneplassering<T> {
+    pub objekt: T,
+    pub koordinater: Koordinater,
+    pub avstand_meter: f64,
+    pub sone: Sone,
+}
+
+/// Regner ut avstanden fra `senter` til objektene, sortert med nærmeste først.
+pub fn med_avstand<T>(
+    senter: &Koordinater,
+    objekter: impl IntoIterator<Item = (T, Koordinater)>,
+) -> Vec<MedAvstand<T>> {
+    let mut med_avstand: Vec<MedAvstand<T>> = objekter
+        .into_iter()
+        .map(|(objekt, koordinater)| MedAvstand {
+            avstand_meter: avstand_meter(senter, &koordinater),
+            objekt,
+            koordinater,
+        })
+        .collect();
+    med_avstand.sort_by(|a, b| a.avstand_meter.total_cmp(&b.avstand_meter));
+    med_avstand
+}
+
+/// Plasserer objektene i soner rundt `utbrudd`, sortert med nærmeste først.
+pub fn klassifiser<T>(
+    utbrudd: &Koordinater,
+    objekter: impl IntoIterator<Item = (T, Koordinater)>,
+    soner: &Soner,
+) -> Vec<Soneplassering<T>> {
+    med_avstand(utbrudd, objekter)
+        .into_iter()
+        .map(|objekt| objekt.i_sone(soner))
+        .collect()
+}
+
+/// Antall hjørner i polygonene som tegner sonene.
+const SIRKELPUNKTER: usize = 64;
+
+fn sirkel(senter: &Koordinater, radius: f64) -> Vec<[f64; 2]> {
+    (0..=SIRKELPUNKTER)
+        .map(|i| {
+            let vinkel = 2.0 * PI * (i % SIRKELPUNKTER) as f64 / SIRKELPUNKTER as f64;
+            let punkt = forskyv(senter, radius * vinkel.cos(), radius * vinkel.sin());
+            [punkt.longitude, punkt.latitude]
+        })
+        .collect()
+}
+
+/// GeoJSON `FeatureCollection` med utbruddet, sonene som polygoner og hvert objekt som punkt.
+///
+/// Koordinatene er `[lon, lat]` i EUREF89, som kartbibliotek behandler som WGS84.
+pub fn til_geojson<T: Serialize>(
+    utbrudd: &Koordinater,
+    soner: &Soner,
+    plasseringer: &[Soneplassering<T>],
+) -> Value {
+    let senter = utbrudd.til(Epsg::Euref89);
+    let sonepolygon = |sone: Sone, radius: f64| {
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Polygon", "coordinates": [sirkel(&senter, radius)] },
+            "properties": { "type": "sone", "sone": sone, "radius_meter": radius },
+        })
+    };
+    let mut features = vec![
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [senter.longitude, senter.latitude] },
+            "properties": { "type": "utbrudd" },
+        }),
+        sonepolygon(Sone::Beskyttelsessone, soner.beskyttelse_meter),
+        sonepolygon(Sone::Overvaakingssone, soner.overvaaking_meter),
+    ];
+
+    features.extend(plasseringer.iter().map(|plassering| {
+        let punkt = plassering.koordinater.til(Epsg::Euref89);
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [punkt.longitude, punkt.latitude] },
+            "properties": {
+                "type": "objekt",
+                "sone": plassering.sone,
+                "avstand_meter": plassering.avstand_meter,
+                "objekt": plassering.objekt,
+            },
+        })
+    }));
+
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avstand_oslo_til_brumunddal() {
+        let oslo = Koordinater::new(59.91115371698211, 10.750519275165197);
+        let brumunddal = Koordinater::new(60.87750554180073, 10.92918075347748);
+        let avstand = avstand_meter(&oslo, &brumunddal);
+        assert!((avstand - 107_900.74).abs() < 0.01, "avstand var {avstand}");
+        let utm = brumunddal.til(Epsg::Utm33);
+        assert!((avstand_meter(&oslo, &utm) - avstand).abs() < 0.01);
+    }
+
+    #[test]
+    fn fliser_dekker_hele_sirkelen() {
+        let senter = Koordinater::new(60.8775, 10.9291);
+        let fliser = fliser(&senter, 10_000.0, 2_500.0);
+        assert!(fliser.len() > 1);
+
+        for i in 0..36 {
+            let vinkel = 2.0 * PI * i as f64 / 36.0;
+            for avstand in [0.0, 5_000.0, 9_999.0] {
+                let punkt = forskyv(&senter, avstand * vinkel.cos(), avstand * vinkel.sin());
+                assert!(
+                    fliser.iter().any(|f| avstand_meter(f, &punkt) <= 2_500.0),
+                    "punkt {punkt:?} er ikke dekket"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn klassifiserer_objekter_i_soner() {
+        let utbrudd = Koordinater::new(60.8775, 10.9291);
+        let objekter = vec![
+            ("langt unna", forskyv(&utbrudd, 0.0, 12_000.0)),
+            ("naer", forskyv(&utbrudd, 1_000.0, 0.0)),
+            ("middels", forskyv(&utbrudd, 0.0, -5_000.0)),
+        ];
+
+        let plasseringer = klassifiser(&utbrudd, objekter, &Soner::default());
+
+        let soner: Vec<_> = plasseringer.iter().map(|p| (p.objekt, p.sone)).collect();
+        assert_eq!(
+            soner,
+            vec![
+                ("naer", Sone::Beskyttelsessone),
+                ("middels", Sone::Overvaakingssone),
+                ("langt unna", Sone::Utenfor),
+            ]
+        );
+
+        let geojson = til_geojson(&utbrudd, &Soner::default(), &plasseringer);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 6);
+        assert_eq!(features[3]["properties"]["sone"], "beskyttelsessone");
+        assert_eq!(features[3]["properties"]["objekt"], "naer");
+    }
+}
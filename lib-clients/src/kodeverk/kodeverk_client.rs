@@ -1,6 +1,6 @@
 use crate::arkiv::response::Kodeverk;
 use crate::client::ApiClient;
-use crate::kodeverk::response::{Code, KodeverkError, KodeverkResponse, KodeverkResult};
+use crate::kodeverk::response::{Code, Kodenavn, KodeverkError, KodeverkResponse, KodeverkResult};
 use reqwest_middleware::reqwest::header::{ACCEPT, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use tracing::debug;
@@ -89,6 +89,24 @@ impl KodeverkClient {
         Ok(related_code_list_string)
     }
     pub async fn get_code(&self, code_type: &str, params: &CodeParams) -> KodeverkResult<Kodeverk> {
+        Ok(self.get_code_tree(code_type, params).await?.to_kodeverk())
+    }
+
+    /// Henter visningsnavn for alle koder (også inaktive) under de gitte kodetypene.
+    pub async fn hent_kodenavn(&self, code_types: &[&str]) -> KodeverkResult<Kodenavn> {
+        let params = CodeParams {
+            root_code: None,
+            filter: None,
+            include_inactive: Some(true),
+        };
+        let mut kodenavn = Kodenavn::default();
+        for code_type in code_types {
+            kodenavn.legg_til(&self.get_code_tree(code_type, &params).await?);
+        }
+        Ok(kodenavn)
+    }
+
+    async fn get_code_tree(&self, code_type: &str, params: &CodeParams) -> KodeverkResult<Code> {
         let mut url = format!(
             "{}/kodeverk/code/{}",
             self.api_client.get_base_url(),
@@ -133,7 +151,7 @@ impl KodeverkClient {
         let kodeverk_response: Code = serde_json::from_str(&response_text)
             .map_err(|e| KodeverkError::Parse(e.to_string()))?;
 
-        Ok(kodeverk_response.to_kodeverk())
+        Ok(kodeverk_response)
     }
 }
 
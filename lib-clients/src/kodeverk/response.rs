@@ -69,6 +69,50 @@ impl Code {
             beskrivelse: beskrivelse.unwrap_or_default(),
         }
     }
+
+    /// Koden selv og alle underliggende koder, dybde først.
+    pub fn alle_koder(&self) -> Vec<&Code> {
+        let mut koder = vec![self];
+        for child in self.children.iter().flatten() {
+            koder.extend(child.alle_koder());
+        }
+        koder
+    }
+}
+
+/// Oppslag fra kodeverk-id (`kodetype$kode`) til visningsnavn.
+#[derive(Debug, Clone, Default)]
+pub struct Kodenavn(HashMap<String, String>);
+
+impl Kodenavn {
+    /// Visningsnavnet for en kode. Godtar både `kodetype$kode` og bare `kode` når den er entydig.
+    pub fn navn(&self, id: &str) -> Option<&str> {
+        if let Some(navn) = self.0.get(id) {
+            return Some(navn);
+        }
+        let mut treff = self
+            .0
+            .iter()
+            .filter(|(k, _)| k.split('$').next_back() == Some(id));
+        match (treff.next(), treff.next()) {
+            (Some((_, navn)), None) => Some(navn),
+            _ => None,
+        }
+    }
+
+    /// Legger til koden og alle underliggende koder.
+    pub fn legg_til(&mut self, code: &Code) {
+        for kode in code.alle_koder() {
+            let kodeverk = kode.to_kodeverk();
+            self.0.insert(kodeverk.id, kodeverk.beskrivelse);
+        }
+    }
+}
+
+impl FromIterator<Kodeverk> for Kodenavn {
+    fn from_iter<I: IntoIterator<Item = Kodeverk>>(iter: I) -> Self {
+        Kodenavn(iter.into_iter().map(|k| (k.id, k.beskrivelse)).collect())
+    }
 }
 
 #[derive(Debug, Error)]
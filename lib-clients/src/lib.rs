@@ -1,34 +1,54 @@
+#[cfg(feature = "ansatt_profil")]
+pub mod ansatt_profil;
 pub mod arkiv;
 pub mod auth;
 pub mod bilde;
 pub mod client;
 pub mod config;
+#[cfg(feature = "dokument_generator")]
 pub mod document_generator;
+#[cfg(feature = "ejb")]
 pub mod ejb;
 pub mod error;
 pub mod geonorge;
 pub mod kodeverk;
 pub mod orgenhet;
+#[cfg(any(feature = "arkiv", feature = "dokument_generator", feature = "skuffen"))]
+pub mod pdf;
+#[cfg(feature = "skuffen")]
+pub mod skuffen;
+#[cfg(feature = "tilsynshistorikk")]
+pub mod tilsynshistorikk;
 pub mod tilsynskvittering;
 pub mod virksomhet;
 
 #[cfg(feature = "kodeverk")]
 pub use kodeverk::{
     kodeverk_client::CodeParams, kodeverk_client::KodeverkClient, response::Code,
-    response::KodeverkResponse, response::RelatedCode,
+    response::Kodenavn, response::KodeverkResponse, response::RelatedCode,
 };
 
 #[cfg(feature = "dokument_generator")]
 pub use document_generator::{
-    document_generator_client::DokumentGeneratorClient, response::Avsender,
-    response::InterntDokument, response::InterntDokumentBuilder, response::VedleggDokument,
+    document_generator_client::DokumentGeneratorClient, format::Dokumentformat,
+    format::GenerertDokument, format::Locale, format::Renderingsvalg, response::Avsender,
+    response::InterntDokument, response::InterntDokumentBuilder, response::InterntDokumentFeil,
+    response::VedleggDokument,
 };
 
 #[cfg(feature = "orgenhet")]
 pub use orgenhet::{orgenhet_client::OrgEnhetClient, response::*};
 
+#[cfg(feature = "ansatt_profil")]
+pub use ansatt_profil::{AnsattProfil, AnsattProfilClient, KildeFeil};
+
 #[cfg(feature = "bilde")]
-pub use bilde::{bilde_client::BildeClient, response::ImageMetaData};
+pub use bilde::{
+    bilde_client::BildeClient,
+    exif::{BildeExif, Bildeformat, ForberedtBilde},
+    request::{BildeOppdatering, Bildeposisjon, Bildetilknytning, NyttBilde},
+    response::{Bilde, BildeSvar, Bildestorrelse, Bildestrom, FotoApp, ImageMetaData},
+};
 
 #[cfg(feature = "arkiv")]
 pub use arkiv::{
@@ -38,21 +58,43 @@ pub use arkiv::{
 
 #[cfg(feature = "virksomhet")]
 pub use virksomhet::{
-    response::Underenhet, response::Virksomhet, virksomhet_client::VirksomhetClient,
+    response::Enhetstre, response::Underenhet, response::Virksomhet, response::VirksomhetFilter,
+    response::VirksomhetOppslag, sok::VirksomhetSide, sok::VirksomhetSok,
+    virksomhet_client::VirksomhetClient,
 };
 
 #[cfg(feature = "tilsynskvittering")]
 pub use tilsynskvittering::{
-    response::TilsynsobjektKvittering, tilsynskvittering_client::TilsynskvitteringClient,
+    request::NyTilsynskvittering, request::NyttKontrollpunkt, response::Arkiveringsfeil,
+    response::Kontrollpunkt, response::KvitteringBilde, response::Kvitteringsstatus,
+    response::Noarksakreferanse, response::TidligereTilsynskvitteringInfo,
+    response::Tilsynskvittering, response::TilsynsobjektKvittering, response::Vurdering,
+    tilsynskvittering_client::TilsynskvitteringClient,
 };
 
+#[cfg(feature = "tilsynshistorikk")]
+pub use tilsynshistorikk::{
+    Historikkhendelse, Tidslinje, Tidslinjefeil, Tidslinjeinnslag, TilsynshistorikkClient,
+};
+
+#[cfg(feature = "skuffen")]
+pub use skuffen::{Forhandsvisning, SkuffenForhandsviser};
+
 #[cfg(feature = "ejb")]
 pub use ejb::{
-    ejb_client::EjbClient, response_begrensninger::Begrensning, response_tilfeller::Sykdomstilfelle,
+    aktive_begrensninger::{AktivBegrensning, AktiveBegrensninger},
+    ejb_client::EjbClient,
+    filter::{BegrensningFelt, Filter, FilterFelt, TilfelleFelt},
+    response_begrensninger::Begrensning,
+    response_tilfeller::Sykdomstilfelle,
+    tilfelle::{Diagnosestatus, Tilfelle, TilfelleHendelse},
 };
 
 #[cfg(feature = "geonorge")]
 pub use geonorge::{
-    geonorge_client::GeoNorgeClient, response::AddressResult, response::GeonorgeResponse,
-    response::Koordinater,
+    geokoding::AdresseForesporsel, geokoding::Geokoding, geokoding::GeokodingOppsett,
+    geokoding::Kandidat, geokoding::Matchscore, geonorge_client::GeoNorgeClient,
+    geonorge_client::GeoNorgeClientBuilder, koordinatsystem::Epsg, matrikkel::Avgrensningsboks,
+    matrikkel::Kommune, matrikkel::MatrikkelAdresser, response::AddressResult,
+    response::GeonorgeResponse, response::Koordinater,
 };
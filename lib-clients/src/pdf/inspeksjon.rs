@@ -0,0 +1,304 @@
+use crate::error::ApiError;
+use lopdf::Document;
+use serde::Serialize;
+use std::fmt;
+use thiserror::Error;
+
+/// PDF-headeren kan ifølge spesifikasjonen stå innenfor de første 1024 bytene.
+const HEADER_SOKEVINDU: usize = 1024;
+/// Øvre grense for dekomprimerte strømmer, så en liten fil ikke kan blåses opp i minnet.
+const MAKS_DEKOMPRIMERT: usize = 64 * 1024 * 1024;
+
+/// Det vi vet om en PDF etter å ha lest den lokalt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PdfInfo {
+    /// PDF-versjonen fra headeren, for eksempel `1.7`.
+    pub versjon: String,
+    pub antall_sider: u32,
+    /// PDF/A-merkingen i XMP-metadataene, når dokumentet har en.
+    pub pdfa: Option<PdfAMerking>,
+}
+
+/// PDF/A-merkingen (`pdfaid:part` og `pdfaid:conformance`) fra XMP-metadataene.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PdfAMerking {
+    pub del: u8,
+    /// Konformitetsnivået i små bokstaver, `a`, `b` eller `u`. PDF/A-4 har ikke nivå.
+    pub konformitet: Option<String>,
+    /// Om katalogen har en `OutputIntent` med `/S /GTS_PDFA1`, som PDF/A krever.
+    pub output_intent: bool,
+}
+
+impl fmt::Display for PdfAMerking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PDF/A-{}{}",
+            self.del,
+            self.konformitet.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PdfFeil {
+    #[error("Innholdet er HTML, ikke PDF: {utdrag:?}")]
+    Html { utdrag: String },
+    #[error("Innholdet er ikke en PDF, starter med {starter_med:?}")]
+    IkkePdf { starter_med: String },
+    #[error("Ugyldig PDF: {0}")]
+    Ugyldig(String),
+    #[error("PDF-en har ingen sider")]
+    IngenSider,
+    #[error("PDF-en er ikke merket som PDF/A")]
+    IkkePdfA,
+    #[error("PDF-en er merket {0}, men mangler OutputIntent for PDF/A")]
+    ManglerOutputIntent(PdfAMerking),
+}
+
+impl From<PdfFeil> for ApiError {
+    fn from(feil: PdfFeil) -> Self {
+        ApiError::ValidationError(feil.to_string())
+    }
+}
+
+/// Sjekker at `innhold` er en PDF med minst én side, og leser versjon, sidetall og PDF/A-merking.
+pub fn inspiser(innhold: &[u8]) -> Result<PdfInfo, PdfFeil> {
+    let dokument = last(innhold)?;
+    let antall_sider = dokument.get_pages().len() as u32;
+    if antall_sider == 0 {
+        return Err(PdfFeil::IngenSider);
+    }
+    Ok(PdfInfo {
+        versjon: dokument.version.clone(),
+        antall_sider,
+        pdfa: pdfa_merking(&dokument),
+    })
+}
+
+/// Som [`inspiser`], men krever i tillegg at dokumentet er merket som PDF/A med OutputIntent.
+pub fn verifiser_pdfa(innhold: &[u8]) -> Result<PdfInfo, PdfFeil> {
+    let info = inspiser(innhold)?;
+    match &info.pdfa {
+        None => Err(PdfFeil::IkkePdfA),
+        Some(merking) if !merking.output_intent => {
+            Err(PdfFeil::ManglerOutputIntent(merking.clone()))
+        }
+        Some(_) => Ok(info),
+    }
+}
+
+/// Teksten på alle sidene, i siderekkefølge. Ment for assertions i tester, ikke for layout.
+pub fn trekk_ut_tekst(innhold: &[u8]) -> Result<String, PdfFeil> {
+    let dokument = last(innhold)?;
+    let sider: Vec<u32> = dokument.get_pages().keys().copied().collect();
+    dokument
+        .extract_text_with_limit(&sider, MAKS_DEKOMPRIMERT)
+        .map_err(|e| PdfFeil::Ugyldig(e.to_string()))
+}
+
+fn last(innhold: &[u8]) -> Result<Document, PdfFeil> {
+    sjekk_header(innhold)?;
+    Document::load_mem(innhold).map_err(|e| PdfFeil::Ugyldig(e.to_string()))
+}
+
+/// Sjekker bare at `innhold` har en PDF-header, og skiller ut HTML-feilsider. Resten av filen
+/// leses ikke, så en avkuttet PDF slipper gjennom. Bruk [`inspiser`] for det.
+pub fn sjekk_header(innhold: &[u8]) -> Result<(), PdfFeil> {
+    let vindu = &innhold[..innhold.len().min(HEADER_SOKEVINDU)];
+    if vindu.windows(5).any(|w| w == b"%PDF-") {
+        return Ok(());
+    }
+
+    let utdrag: String = String::from_utf8_lossy(&innhold[..innhold.len().min(80)])
+        .trim_start_matches('\u{feff}')
+        .trim()
+        .to_string();
+    if utdrag.starts_with('<') {
+        Err(PdfFeil::Html { utdrag })
+    } else {
+        Err(PdfFeil::IkkePdf {
+            starter_med: utdrag,
+        })
+    }
+}
+
+fn pdfa_merking(dokument: &Document) -> Option<PdfAMerking> {
+    let katalog = dokument.catalog().ok()?;
+    let metadata = katalog.get(b"Metadata").ok()?;
+    let (_, metadata) = dokument.dereference(metadata).ok()?;
+    let xmp = metadata
+        .as_stream()
+        .ok()?
+        .get_plain_content_with_limit(MAKS_DEKOMPRIMERT)
+        .ok()?;
+    let xmp = String::from_utf8_lossy(&xmp);
+
+    let del = xmp_verdi(&xmp, "pdfaid:part")?.parse().ok()?;
+    let konformitet = xmp_verdi(&xmp, "pdfaid:conformance").map(|k| k.to_ascii_lowercase());
+    Some(PdfAMerking {
+        del,
+        konformitet,
+        output_intent: har_pdfa_output_intent(dokument),
+    })
+}
+
+/// XMP tillater både `pdfaid:part="2"` som attributt og `<pdfaid:part>2</pdfaid:part>` som element.
+fn xmp_verdi<'a>(xmp: &'a str, navn: &str) -> Option<&'a str> {
+    let attributt = format!("{navn}=");
+    if let Some(start) = xmp.find(&attributt) {
+        let rest = &xmp[start + attributt.len()..];
+        let sitat = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let rest = &rest[1..];
+        return rest.find(sitat).map(|slutt| rest[..slutt].trim());
+    }
+    let element = format!("<{navn}>");
+    let start = xmp.find(&element)? + element.len();
+    let rest = &xmp[start..];
+    rest.find('<').map(|slutt| rest[..slutt].trim())
+}
+
+fn har_pdfa_output_intent(dokument: &Document) -> bool {
+    let Ok(katalog) = dokument.catalog() else {
+        return false;
+    };
+    let Some(intents) = katalog
+        .get(b"OutputIntents")
+        .ok()
+        .and_then(|o| dokument.dereference(o).ok())
+        .and_then(|(_, o)| o.as_array().ok())
+    else {
+        return false;
+    };
+    intents.iter().any(|intent| {
+        dokument
+            .dereference(intent)
+            .ok()
+            .and_then(|(_, o)| o.as_dict().ok())
+            .and_then(|d| d.get(b"S").ok())
+            .and_then(|s| s.as_name().ok())
+            == Some(b"GTS_PDFA1".as_slice())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{Object, Stream, dictionary};
+
+    /// En enkel PDF med én side per tekst, eventuelt med PDF/A-2b-merking.
+    fn lag_pdf(sider: &[&str], pdfa: bool) -> Vec<u8> {
+        let mut dokument = Document::with_version("1.7");
+        let pages_id = dokument.new_object_id();
+        let font_id = dokument.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let resources_id = dokument.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+
+        let mut kids: Vec<Object> = Vec::new();
+        for tekst in sider {
+            let innhold = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![72.into(), 720.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*tekst)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id =
+                dokument.add_object(Stream::new(dictionary! {}, innhold.encode().unwrap()));
+            let page_id = dokument.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            });
+            kids.push(page_id.into());
+        }
+        dokument.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => kids.len() as i64,
+                "Kids" => kids,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+
+        let mut katalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+        if pdfa {
+            let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/" pdfaid:part="2" pdfaid:conformance="B"/></rdf:RDF></x:xmpmeta>"#;
+            let metadata_id = dokument.add_object(Stream::new(
+                dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+                xmp.as_bytes().to_vec(),
+            ));
+            katalog.set("Metadata", metadata_id);
+            katalog.set(
+                "OutputIntents",
+                vec![Object::Dictionary(dictionary! {
+                    "Type" => "OutputIntent",
+                    "S" => "GTS_PDFA1",
+                    "OutputConditionIdentifier" => Object::string_literal("sRGB"),
+                })],
+            );
+        }
+        let katalog_id = dokument.add_object(katalog);
+        dokument.trailer.set("Root", katalog_id);
+
+        let mut innhold = Vec::new();
+        dokument.save_to(&mut innhold).unwrap();
+        innhold
+    }
+
+    #[test]
+    fn leser_sidetall_og_tekst() {
+        let pdf = lag_pdf(&["Tilsynsrapport", "Side to"], false);
+
+        let info = inspiser(&pdf).unwrap();
+        assert_eq!(info.versjon, "1.7");
+        assert_eq!(info.antall_sider, 2);
+        assert_eq!(info.pdfa, None);
+        assert_eq!(verifiser_pdfa(&pdf), Err(PdfFeil::IkkePdfA));
+
+        let tekst = trekk_ut_tekst(&pdf).unwrap();
+        assert!(tekst.contains("Tilsynsrapport"), "{tekst}");
+        assert!(tekst.contains("Side to"), "{tekst}");
+    }
+
+    #[test]
+    fn finner_pdfa_merking() {
+        let info = verifiser_pdfa(&lag_pdf(&["Vedtak"], true)).unwrap();
+        let merking = info.pdfa.unwrap();
+        assert_eq!(merking.to_string(), "PDF/A-2b");
+        assert!(merking.output_intent);
+    }
+
+    #[test]
+    fn avviser_html_feilside() {
+        let html = b"\xef\xbb\xbf<!DOCTYPE html><html><body>502 Bad Gateway</body></html>";
+        assert!(matches!(inspiser(html), Err(PdfFeil::Html { .. })));
+        assert!(matches!(
+            inspiser(b"{\"feil\":\"x\"}"),
+            Err(PdfFeil::IkkePdf { .. })
+        ));
+        assert!(matches!(
+            inspiser(b"%PDF-1.7\n%%EOF"),
+            Err(PdfFeil::Ugyldig(_))
+        ));
+        assert!(matches!(sjekk_header(html), Err(PdfFeil::Html { .. })));
+        assert_eq!(sjekk_header(b"%PDF-1.7\n%%EOF"), Ok(()));
+    }
+
+    #[test]
+    fn xmp_som_element() {
+        let xmp = "<pdfaid:part>3</pdfaid:part><pdfaid:conformance>U</pdfaid:conformance>";
+        assert_eq!(xmp_verdi(xmp, "pdfaid:part"), Some("3"));
+        assert_eq!(xmp_verdi(xmp, "pdfaid:conformance"), Some("U"));
+    }
+}
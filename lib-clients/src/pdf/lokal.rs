@@ -0,0 +1,279 @@
+use crate::pdf::inspeksjon::PdfFeil;
+use lopdf::content::{Content, Operation};
+use lopdf::{Document, Object, Stream, StringFormat, dictionary};
+
+/// A4 i punkter.
+const SIDEBREDDE: i64 = 595;
+const SIDEHOYDE: i64 = 842;
+const MARG: i64 = 56;
+const SKRIFTSTORRELSE: i64 = 11;
+const LINJEAVSTAND: i64 = 14;
+/// Helvetica er i snitt rundt et halvt em bred, så 11 pt gir omtrent 85 tegn mellom margene.
+const TEGN_PER_LINJE: usize = 85;
+const LINJER_PER_SIDE: usize = ((SIDEHOYDE - 2 * MARG) / LINJEAVSTAND) as usize;
+
+/// Elementer som alltid gir linjeskift.
+const BLOKKELEMENTER: &[&str] = &[
+    "address", "article", "br", "div", "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header",
+    "hr", "li", "ol", "p", "section", "table", "tr", "ul",
+];
+/// Elementer der innholdet ikke er synlig tekst.
+const USYNLIGE_ELEMENTER: &[&str] = &["head", "script", "style", "template", "title"];
+
+/// Enkel PDF av teksten i `html`, uten dokumentgeneratoren.
+///
+/// Dette er ingen HTML-motor: tagger fjernes, blokkelementer gir linjeskift og teksten settes
+/// i Helvetica med linjebryting og sideskift. Det holder til å forhåndsvise innholdet, ikke
+/// layouten.
+pub fn html_til_pdf(html: &str) -> Result<Vec<u8>, PdfFeil> {
+    let linjer: Vec<String> = html_til_tekst(html)
+        .lines()
+        .flat_map(|linje| bryt_linje(linje, TEGN_PER_LINJE))
+        .collect();
+
+    let mut dokument = Document::with_version("1.7");
+    let pages_id = dokument.new_object_id();
+    let font_id = dokument.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let resources_id = dokument.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+
+    let mut kids: Vec<Object> = Vec::new();
+    let sider: Vec<&[String]> = if linjer.is_empty() {
+        vec![&[]]
+    } else {
+        linjer.chunks(LINJER_PER_SIDE).collect()
+    };
+    for side in sider {
+        let mut operations = vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), SKRIFTSTORRELSE.into()]),
+            Operation::new("TL", vec![LINJEAVSTAND.into()]),
+            Operation::new(
+                "Td",
+                vec![MARG.into(), (SIDEHOYDE - MARG - SKRIFTSTORRELSE).into()],
+            ),
+        ];
+        for linje in side {
+            operations.push(Operation::new(
+                "Tj",
+                vec![Object::String(win_ansi(linje), StringFormat::Literal)],
+            ));
+            operations.push(Operation::new("T*", vec![]));
+        }
+        operations.push(Operation::new("ET", vec![]));
+
+        let innhold = Content { operations }
+            .encode()
+            .map_err(|e| PdfFeil::Ugyldig(e.to_string()))?;
+        let content_id = dokument.add_object(Stream::new(dictionary! {}, innhold));
+        let page_id = dokument.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        kids.push(page_id.into());
+    }
+
+    dokument.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), SIDEBREDDE.into(), SIDEHOYDE.into()],
+        }),
+    );
+    let katalog_id = dokument.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    dokument.trailer.set("Root", katalog_id);
+
+    let mut pdf = Vec::new();
+    dokument
+        .save_to(&mut pdf)
+        .map_err(|e| PdfFeil::Ugyldig(e.to_string()))?;
+    Ok(pdf)
+}
+
+/// Den synlige teksten i `html`, med ett avsnitt per linje.
+fn html_til_tekst(html: &str) -> String {
+    let mut tekst = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        legg_til_tekst(&mut tekst, &rest[..start]);
+        let Some(slutt) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tagg = &rest[start + 1..start + slutt];
+        rest = &rest[start + slutt + 1..];
+
+        let lukker = tagg.starts_with('/');
+        let navn: String = tagg
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        if !lukker && USYNLIGE_ELEMENTER.contains(&navn.as_str()) {
+            let lukking = format!("</{navn}");
+            rest = match rest.to_ascii_lowercase().find(&lukking) {
+                Some(i) => rest[i..].find('>').map_or("", |j| &rest[i + j + 1..]),
+                None => "",
+            };
+        } else if BLOKKELEMENTER.contains(&navn.as_str()) {
+            nytt_avsnitt(&mut tekst);
+            if navn == "li" && !lukker {
+                tekst.push_str("- ");
+            }
+        }
+    }
+    legg_til_tekst(&mut tekst, rest);
+    tekst.trim_end().to_string()
+}
+
+fn nytt_avsnitt(tekst: &mut String) {
+    let trimmet = tekst.trim_end_matches(' ').len();
+    tekst.truncate(trimmet);
+    if !tekst.is_empty() && !tekst.ends_with('\n') {
+        tekst.push('\n');
+    }
+}
+
+/// Legger til `html`-tekst med entiteter dekodet og mellomrom slått sammen, som i en nettleser.
+fn legg_til_tekst(tekst: &mut String, html: &str) {
+    for c in dekod_entiteter(html).chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !tekst.is_empty() && !tekst.ends_with([' ', '\n']) {
+                tekst.push(' ');
+            }
+        } else {
+            tekst.push(if c == '\u{a0}' { ' ' } else { c });
+        }
+    }
+}
+
+fn dekod_entiteter(html: &str) -> String {
+    let mut tekst = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('&') {
+        tekst.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entitet = rest
+            .find(';')
+            .filter(|slutt| *slutt <= 10)
+            .and_then(|slutt| dekod_entitet(&rest[1..slutt]).map(|c| (c, slutt)));
+        match entitet {
+            Some((c, slutt)) => {
+                tekst.push(c);
+                rest = &rest[slutt + 1..];
+            }
+            None => {
+                tekst.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    tekst.push_str(rest);
+    tekst
+}
+
+fn dekod_entitet(navn: &str) -> Option<char> {
+    match navn {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let kode = match navn.strip_prefix('#')? {
+                hex if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok()?,
+                des => des.parse().ok()?,
+            };
+            char::from_u32(kode)
+        }
+    }
+}
+
+/// Bryter `linje` ved mellomrom slik at ingen linje er lenger enn `bredde` tegn, med mindre
+/// et enkelt ord er det.
+fn bryt_linje(linje: &str, bredde: usize) -> Vec<String> {
+    let mut linjer = Vec::new();
+    let mut gjeldende = String::new();
+    for ord in linje.split(' ').filter(|ord| !ord.is_empty()) {
+        let lengde = gjeldende.chars().count();
+        if lengde > 0 && lengde + 1 + ord.chars().count() > bredde {
+            linjer.push(std::mem::take(&mut gjeldende));
+        }
+        if !gjeldende.is_empty() {
+            gjeldende.push(' ');
+        }
+        gjeldende.push_str(ord);
+    }
+    linjer.push(gjeldende);
+    linjer
+}
+
+/// Koder teksten i WinAnsiEncoding, som standardfontene bruker. Tegn som ikke finnes der blir `?`.
+fn win_ansi(tekst: &str) -> Vec<u8> {
+    tekst
+        .chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            '€' => 0x80,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::{inspiser, trekk_ut_tekst};
+
+    #[test]
+    fn html_blir_til_tekst() {
+        let html = "<html><head><title>Skjult</title><style>p { color: red }</style></head>\
+            <body><h1>Vedtak  i sak</h1><p>Saksnummer:&nbsp;2025/&lt;1&gt;</p>\
+            <ul><li>Første</li><li>Andre</li></ul></body></html>";
+        assert_eq!(
+            html_til_tekst(html),
+            "Vedtak i sak\nSaksnummer: 2025/<1>\n- Første\n- Andre"
+        );
+    }
+
+    #[test]
+    fn lager_lesbar_pdf_med_sideskift() {
+        let avsnitt = "<p>Ærlig talt – dette er et langt avsnitt.</p>".repeat(60);
+        let pdf = html_til_pdf(&format!("<h1>Sak 2025/1</h1>{avsnitt}")).unwrap();
+
+        assert_eq!(inspiser(&pdf).unwrap().antall_sider, 2);
+        let tekst = trekk_ut_tekst(&pdf).unwrap();
+        assert!(tekst.contains("Sak 2025/1"), "{tekst}");
+        assert!(tekst.contains("Ærlig talt"), "{tekst}");
+    }
+
+    #[test]
+    fn bryter_lange_linjer_ved_mellomrom() {
+        assert_eq!(
+            bryt_linje("en to tre fire", 7),
+            vec!["en to", "tre", "fire"]
+        );
+        assert_eq!(bryt_linje("", 7), vec![""]);
+    }
+}
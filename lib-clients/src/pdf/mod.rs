@@ -0,0 +1,7 @@
+pub mod inspeksjon;
+pub mod lokal;
+
+pub use inspeksjon::{
+    PdfAMerking, PdfFeil, PdfInfo, inspiser, sjekk_header, trekk_ut_tekst, verifiser_pdfa,
+};
+pub use lokal::html_til_pdf;
@@ -0,0 +1,104 @@
+use crate::error::{ApiError, Result};
+use crate::pdf::{PdfInfo, html_til_pdf, inspiser};
+use bytes::Bytes;
+use lib_schemas::skuffen::dokument::{Dokumentform, FeltVerdier};
+use lib_schemas::skuffen::mal::{HtmlMal, MalError};
+use tracing::instrument;
+
+impl From<MalError> for ApiError {
+    fn from(feil: MalError) -> Self {
+        ApiError::ValidationError(feil.to_string())
+    }
+}
+
+/// Et HTML-maldokument flettet og rendret lokalt.
+#[derive(Debug, Clone)]
+pub struct Forhandsvisning {
+    /// Malen med feltene flettet inn.
+    pub html: String,
+    pub pdf: Bytes,
+    pub pdf_info: PdfInfo,
+}
+
+/// Forhåndsviser [`Dokumentform::HtmlTemplate`]-dokumenter før de sendes til Skuffen.
+///
+/// Malen valideres mot `felter` og verdiene HTML-escapes med [`HtmlMal::flett`]. PDF-en lages
+/// med den enkle renderen i [`crate::pdf::lokal`], siden dokumentgeneratoren ikke har et kjent
+/// endepunkt for ferdig flettet HTML.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SkuffenForhandsviser;
+
+impl SkuffenForhandsviser {
+    /// Fletter `verdier` inn i `mal` for feltene `form` deklarerer, og rendrer resultatet.
+    #[instrument(name = "Forhåndsviser Skuffen-dokument", skip_all)]
+    pub fn forhandsvis(
+        &self,
+        mal: &HtmlMal,
+        form: &Dokumentform,
+        verdier: &FeltVerdier,
+    ) -> Result<Forhandsvisning> {
+        let Dokumentform::HtmlTemplate { felter, .. } = form else {
+            return Err(ApiError::ValidationError(
+                "Bare HtmlTemplate-dokumenter kan forhåndsvises".to_string(),
+            ));
+        };
+        let html = mal.flett(felter, verdier)?;
+
+        let pdf = html_til_pdf(&html)?;
+        let pdf_info = inspiser(&pdf)?;
+        Ok(Forhandsvisning {
+            html,
+            pdf: Bytes::from(pdf),
+            pdf_info,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::trekk_ut_tekst;
+    use lib_schemas::skuffen::dokument::Felt;
+    use lib_schemas::skuffen::sak::Saksnummer;
+    use uuid::Uuid;
+
+    #[test]
+    fn forhandsviser_lokalt_med_escapede_verdier() {
+        let mal = HtmlMal::new("<h1>Vedtak</h1><p>Saksnummer: {{ saksnummer }}</p>");
+        let form = Dokumentform::HtmlTemplate {
+            mal_referanse: Uuid::new_v4(),
+            felter: vec![Felt::Saksnummer],
+        };
+        let verdier = FeltVerdier::new()
+            .med(Felt::Saksnummer, Saksnummer::new("2025/<script>1").unwrap())
+            .unwrap();
+
+        let visning = SkuffenForhandsviser
+            .forhandsvis(&mal, &form, &verdier)
+            .unwrap();
+
+        assert_eq!(
+            visning.html,
+            "<h1>Vedtak</h1><p>Saksnummer: 2025/&lt;script&gt;1</p>"
+        );
+        assert_eq!(visning.pdf_info.antall_sider, 1);
+        let tekst = trekk_ut_tekst(&visning.pdf).unwrap();
+        assert!(tekst.contains("Saksnummer: 2025/<script>1"), "{tekst}");
+    }
+
+    #[test]
+    fn avviser_mal_som_ikke_matcher_felter() {
+        let form = Dokumentform::HtmlTemplate {
+            mal_referanse: Uuid::new_v4(),
+            felter: vec![Felt::Saksnummer],
+        };
+        let feil = SkuffenForhandsviser
+            .forhandsvis(
+                &HtmlMal::new("<p>Uten felt</p>"),
+                &form,
+                &FeltVerdier::new(),
+            )
+            .unwrap_err();
+        assert!(matches!(feil, ApiError::ValidationError(_)), "{feil:?}");
+    }
+}
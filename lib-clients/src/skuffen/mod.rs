@@ -0,0 +1,3 @@
+pub mod forhandsvisning;
+
+pub use forhandsvisning::{Forhandsvisning, SkuffenForhandsviser};
@@ -0,0 +1,5 @@
+pub mod response;
+pub mod tilsynshistorikk_client;
+
+pub use response::{Historikkhendelse, Tidslinje, Tidslinjefeil, Tidslinjeinnslag};
+pub use tilsynshistorikk_client::TilsynshistorikkClient;
@@ -0,0 +1,320 @@
+use crate::arkiv::response::ArkivClientSak;
+use crate::ejb::aktive_begrensninger::{AktivBegrensning, AktiveBegrensninger, norsk_dato};
+use crate::ejb::tilfelle::Tilfelle;
+use crate::tilsynskvittering::response::{
+    Noarksakreferanse, TidligereTilsynskvitteringInfo, TilsynsobjektKvittering,
+};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Tilsynshistorikken for ett tilsynsobjekt, sortert med nyeste innslag først.
+///
+/// Innslag fra kilder som ikke svarte mangler, og årsaken ligger i `kilde_feil`.
+#[derive(Debug, Serialize)]
+pub struct Tidslinje {
+    pub tilsynsobjekt_id: String,
+    pub innslag: Vec<Tidslinjeinnslag>,
+    pub kilde_feil: Vec<Tidslinjefeil>,
+}
+
+/// Ett innslag i tidslinjen. Innslag uten dato sorteres sist.
+#[derive(Debug, Serialize)]
+pub struct Tidslinjeinnslag {
+    pub dato: Option<NaiveDate>,
+    pub hendelse: Historikkhendelse,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Historikkhendelse {
+    /// Et tidligere tilsyn, med arkivsaken kvitteringen er journalført på når den kunne hentes.
+    Tilsyn {
+        kvittering: TidligereTilsynskvitteringInfo,
+        arkivsak: Option<ArkivClientSak>,
+    },
+    Sykdomstilfelle(Tilfelle),
+    /// En begrensning som gjelder i dag.
+    Begrensning(AktivBegrensning),
+}
+
+/// Feil fra én av kildene som ikke stoppet oppslaget.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub enum Tidslinjefeil {
+    Tilsynskvittering(String),
+    Tilfeller(String),
+    Begrensninger(String),
+    Arkivsak {
+        sak: Noarksakreferanse,
+        feil: String,
+    },
+}
+
+impl Tidslinje {
+    /// Returnerer `true` når alle kildene svarte.
+    pub fn er_komplett(&self) -> bool {
+        self.kilde_feil.is_empty()
+    }
+
+    /// Innslagene fra og med `dato`.
+    pub fn siden(&self, dato: NaiveDate) -> impl Iterator<Item = &Tidslinjeinnslag> {
+        self.innslag
+            .iter()
+            .filter(move |innslag| innslag.dato.is_some_and(|d| d >= dato))
+    }
+
+    pub(crate) fn fra_kilder(
+        tilsynsobjekt_id: &str,
+        kvitteringer: Result<Vec<TilsynsobjektKvittering>, Tidslinjefeil>,
+        tilfeller: Result<Vec<Tilfelle>, Tidslinjefeil>,
+        begrensninger: Result<AktiveBegrensninger, Tidslinjefeil>,
+        arkivsaker: HashMap<Noarksakreferanse, Result<ArkivClientSak, String>>,
+    ) -> Self {
+        let mut innslag = Vec::new();
+        let mut kilde_feil = Vec::new();
+
+        match kvitteringer {
+            Ok(kvitteringer) => {
+                innslag.extend(
+                    kvitteringer
+                        .into_iter()
+                        .flat_map(|k| k.tilsyns_kvitteringer)
+                        .map(|kvittering| Tidslinjeinnslag {
+                            dato: kvittering.tilsynsdato,
+                            hendelse: Historikkhendelse::Tilsyn {
+                                arkivsak: kvittering
+                                    .noarksak()
+                                    .and_then(|sak| arkivsaker.get(&sak)?.as_ref().ok().cloned()),
+                                kvittering,
+                            },
+                        }),
+                );
+            }
+            Err(feil) => kilde_feil.push(feil),
+        }
+
+        match tilfeller {
+            Ok(tilfeller) => {
+                innslag.extend(tilfeller.into_iter().map(|tilfelle| Tidslinjeinnslag {
+                    dato: tilfelle.tidslinje.first().map(|h| norsk_dato(h.dato)),
+                    hendelse: Historikkhendelse::Sykdomstilfelle(tilfelle),
+                }))
+            }
+            Err(feil) => kilde_feil.push(feil),
+        }
+
+        match begrensninger {
+            Ok(aktive) => {
+                innslag.extend(
+                    aktive
+                        .begrensninger
+                        .into_iter()
+                        .map(|aktiv| Tidslinjeinnslag {
+                            dato: aktiv.begrensning.fradato.map(norsk_dato),
+                            hendelse: Historikkhendelse::Begrensning(aktiv),
+                        }),
+                )
+            }
+            Err(feil) => kilde_feil.push(feil),
+        }
+
+        let mut arkivfeil: Vec<(Noarksakreferanse, String)> = arkivsaker
+            .into_iter()
+            .filter_map(|(sak, resultat)| resultat.err().map(|feil| (sak, feil)))
+            .collect();
+        arkivfeil.sort();
+        kilde_feil.extend(
+            arkivfeil
+                .into_iter()
+                .map(|(sak, feil)| Tidslinjefeil::Arkivsak { sak, feil }),
+        );
+
+        // `Option` sorterer `None` først, så synkende rekkefølge legger innslag uten dato sist.
+        innslag.sort_by_key(|innslag| Reverse(innslag.dato));
+
+        Tidslinje {
+            tilsynsobjekt_id: tilsynsobjekt_id.to_string(),
+            innslag,
+            kilde_feil,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arkiv::response::Kodeverk;
+    use crate::ejb::response_begrensninger::Begrensning;
+    use crate::ejb::tilfelle::{Diagnosestatus, Kode, TilfelleHendelse};
+    use crate::kodeverk::response::Kodenavn;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    fn dag(dag: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, dag).unwrap()
+    }
+
+    fn kvitteringer(tilsyn: &[(u32, Option<&str>)]) -> Vec<TilsynsobjektKvittering> {
+        let tilsyn: Vec<_> = tilsyn
+            .iter()
+            .map(|(d, sak)| {
+                json!({
+                    "externalTilsynsobjektId": "to-1",
+                    "noarksakAar": sak.map(|_| "2025"),
+                    "noarksakSekvensnummer": sak,
+                    "status": "ARKIVERT",
+                    "tilsynsdato": dag(*d),
+                    "ansattNavn": "Ola Nordmann",
+                    "antallBilder": 0,
+                    "antallKontrollpunkter": 1,
+                    "unntattOffentlighet": false
+                })
+            })
+            .collect();
+        serde_json::from_value(json!([{
+            "tilsynsobjektId": "to-1",
+            "tilsynskvitteringer": tilsyn
+        }]))
+        .unwrap()
+    }
+
+    fn tilfelle(registrert: u32) -> Tilfelle {
+        Tilfelle {
+            id: "T1".to_string(),
+            tilsynsobjekt_id: "to-1".to_string(),
+            virksomhet_id: "V1".to_string(),
+            tilfelletype: Kode {
+                id: "TILFELLETYPE$SYKDOM".to_string(),
+                navn: None,
+            },
+            status: Diagnosestatus::Registrert,
+            diagnose: None,
+            mistenkt_sykdom: None,
+            artkategori: None,
+            kommunenummer: None,
+            matrikkelnummer: None,
+            produsentnummer: None,
+            tidslinje: vec![TilfelleHendelse {
+                status: Diagnosestatus::Registrert,
+                dato: Utc.from_utc_datetime(&dag(registrert).and_hms_opt(8, 0, 0).unwrap()),
+            }],
+            ugyldig: false,
+        }
+    }
+
+    fn begrensninger(fra: u32) -> AktiveBegrensninger {
+        let begrensning = Begrensning {
+            idstring: "B1".to_string(),
+            version: 1,
+            typeid: "BEGRENSNINGTYPE$FORBUD".to_string(),
+            fradato: Some(Utc.from_utc_datetime(&dag(fra).and_hms_opt(0, 0, 0).unwrap())),
+            tildato: None,
+            handlingsloepref: None,
+            soeknadref: None,
+            begrensningsaarsakid: "BEGRENSNINGSAARSAK$SMITTE".to_string(),
+            createddate: None,
+            lastmodifieddate: None,
+            gbrnummerref: Some("GBR1".to_string()),
+            aarsakid: "AARSAK$AI".to_string(),
+            beskrivelse: String::new(),
+        };
+        AktiveBegrensninger::beregn(vec![begrensning], dag(28), &Kodenavn::default())
+    }
+
+    fn arkivsak(sekvensnummer: &str) -> ArkivClientSak {
+        let kode = Kodeverk {
+            id: "SAKSSTATUS$B".to_string(),
+            beskrivelse: "Under behandling".to_string(),
+        };
+        ArkivClientSak {
+            noarkaar: "2025".to_string(),
+            noarksaksnummer: sekvensnummer.to_string(),
+            saksbehandler_id: None,
+            ordningsverdi: "233".to_string(),
+            tittel: "Tilsyn".to_string(),
+            skjermingshjemmel: None,
+            tilgangskode: None,
+            status: kode,
+            lukket: false,
+            enhet_id: "E1".to_string(),
+        }
+    }
+
+    fn sak(sekvensnummer: &str) -> Noarksakreferanse {
+        Noarksakreferanse {
+            aar: "2025".to_string(),
+            sekvensnummer: sekvensnummer.to_string(),
+        }
+    }
+
+    #[test]
+    fn slaar_sammen_kildene_med_nyeste_forst() {
+        let tidslinje = Tidslinje::fra_kilder(
+            "to-1",
+            Ok(kvitteringer(&[(2, Some("10")), (20, None)])),
+            Ok(vec![tilfelle(10)]),
+            Ok(begrensninger(12)),
+            HashMap::from([(sak("10"), Ok(arkivsak("10")))]),
+        );
+
+        assert!(tidslinje.er_komplett());
+        let datoer: Vec<_> = tidslinje.innslag.iter().map(|i| i.dato).collect();
+        assert_eq!(
+            datoer,
+            vec![Some(dag(20)), Some(dag(12)), Some(dag(10)), Some(dag(2))]
+        );
+        match &tidslinje.innslag[3].hendelse {
+            Historikkhendelse::Tilsyn { arkivsak, .. } => {
+                assert_eq!(arkivsak.as_ref().unwrap().noarksaksnummer, "10")
+            }
+            annet => panic!("forventet tilsyn, fikk {annet:?}"),
+        }
+        assert_eq!(tidslinje.siden(dag(11)).count(), 2);
+    }
+
+    #[test]
+    fn tilfeller_dateres_i_norsk_tid() {
+        let mut sent_paa_kvelden = tilfelle(10);
+        sent_paa_kvelden.tidslinje[0].dato =
+            Utc.from_utc_datetime(&dag(10).and_hms_opt(23, 30, 0).unwrap());
+
+        let tidslinje = Tidslinje::fra_kilder(
+            "to-1",
+            Ok(kvitteringer(&[])),
+            Ok(vec![sent_paa_kvelden]),
+            Err(Tidslinjefeil::Begrensninger("503".to_string())),
+            HashMap::new(),
+        );
+
+        assert_eq!(tidslinje.innslag.len(), 1);
+        assert_eq!(tidslinje.innslag[0].dato, Some(dag(11)));
+    }
+
+    #[test]
+    fn beholder_resten_naar_kilder_feiler() {
+        let tidslinje = Tidslinje::fra_kilder(
+            "to-1",
+            Ok(kvitteringer(&[(2, Some("10"))])),
+            Err(Tidslinjefeil::Tilfeller("503".to_string())),
+            Err(Tidslinjefeil::Begrensninger(
+                "tilfellene kunne ikke hentes".to_string(),
+            )),
+            HashMap::from([(sak("10"), Err("404".to_string()))]),
+        );
+
+        assert!(!tidslinje.er_komplett());
+        assert_eq!(tidslinje.innslag.len(), 1);
+        assert!(matches!(
+            tidslinje.innslag[0].hendelse,
+            Historikkhendelse::Tilsyn { arkivsak: None, .. }
+        ));
+        assert_eq!(
+            tidslinje.kilde_feil.last(),
+            Some(&Tidslinjefeil::Arkivsak {
+                sak: sak("10"),
+                feil: "404".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crate::arkiv::arkiv_client::ArkivClient;
+use crate::ejb::aktive_begrensninger::{AktiveBegrensninger, norsk_dato};
+use crate::ejb::ejb_client::EjbClient;
+use crate::ejb::filter::{FilterFelt, TilfelleFelt};
+use crate::ejb::response_tilfeller::Sykdomstilfelle;
+use crate::ejb::tilfelle::Tilfelle;
+use crate::error::{ApiError, Result};
+use crate::kodeverk::response::Kodenavn;
+use crate::tilsynshistorikk::response::{Tidslinje, Tidslinjefeil};
+use crate::tilsynskvittering::response::Noarksakreferanse;
+use crate::tilsynskvittering::tilsynskvittering_client::TilsynskvitteringClient;
+use chrono::{NaiveDate, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::{BTreeSet, HashMap};
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+/// Antall tilfeller per side fra EJB.
+const SIDESTORRELSE_TILFELLER: u16 = 200;
+/// Antall arkivsaker som hentes samtidig.
+const SAMTIDIGE_ARKIVOPPSLAG: usize = 4;
+
+/// Samler tilsynshistorikken for et tilsynsobjekt fra tilsynskvittering, EJB og arkivet.
+pub struct TilsynshistorikkClient {
+    tilsynskvittering_client: TilsynskvitteringClient,
+    ejb_client: EjbClient,
+    arkiv_client: ArkivClient,
+}
+
+impl TilsynshistorikkClient {
+    pub fn new(
+        tilsynskvittering_client: TilsynskvitteringClient,
+        ejb_client: EjbClient,
+        arkiv_client: ArkivClient,
+    ) -> Self {
+        TilsynshistorikkClient {
+            tilsynskvittering_client,
+            ejb_client,
+            arkiv_client,
+        }
+    }
+
+    /// Henter tidslinjen for et tilsynsobjekt, med begrensningene som gjelder i dag.
+    ///
+    /// Tilsynskvittering og EJB spørres samtidig, og arkivsakene hentes når kvitteringene er
+    /// kjent. Feiler bare dersom verken tilsynskvittering eller EJB svarer; delvise feil ligger
+    /// i `Tidslinje::kilde_feil`.
+    #[instrument(
+        name = "Henter tilsynshistorikk",
+        skip(self, kodenavn),
+        fields(request_id = %Uuid::new_v4())
+    )]
+    pub async fn hent_tidslinje(
+        &self,
+        tilsynsobjekt_id: &str,
+        kodenavn: &Kodenavn,
+    ) -> Result<Tidslinje> {
+        let dato = norsk_dato(Utc::now());
+        let (kvitteringer, (tilfeller, begrensninger)) = tokio::join!(
+            self.tilsynskvittering_client
+                .hent_info_tildligere_tilsyn(vec![tilsynsobjekt_id.to_string()]),
+            self.hent_fra_ejb(tilsynsobjekt_id, dato, kodenavn),
+        );
+
+        let kvitteringer =
+            kvitteringer.map_err(|e| Tidslinjefeil::Tilsynskvittering(e.to_string()));
+        if let (Err(kvittering_feil), Err(tilfelle_feil)) = (&kvitteringer, &tilfeller) {
+            return Err(ApiError::ClientError {
+                resource: "tilsynshistorikk".to_string(),
+                error_message: format!(
+                    "Fant ingen historikk for {tilsynsobjekt_id}. tilsynskvittering: {kvittering_feil:?}, ejb: {tilfelle_feil:?}"
+                ),
+            });
+        }
+
+        let saker: BTreeSet<Noarksakreferanse> = kvitteringer
+            .iter()
+            .flatten()
+            .flat_map(|k| &k.tilsyns_kvitteringer)
+            .filter_map(|k| k.noarksak())
+            .collect();
+        let arkivsaker: HashMap<_, _> = stream::iter(saker)
+            .map(|sak| async move {
+                let resultat = self
+                    .arkiv_client
+                    .get_arkiv_sak(&sak.aar, &sak.sekvensnummer)
+                    .await
+                    .map_err(|e| e.to_string());
+                (sak, resultat)
+            })
+            .buffer_unordered(SAMTIDIGE_ARKIVOPPSLAG)
+            .collect()
+            .await;
+
+        let tidslinje = Tidslinje::fra_kilder(
+            tilsynsobjekt_id,
+            kvitteringer,
+            tilfeller,
+            begrensninger,
+            arkivsaker,
+        );
+        if !tidslinje.er_komplett() {
+            warn!(
+                "Tilsynshistorikk for {tilsynsobjekt_id} er ufullstendig: {:?}",
+                tidslinje.kilde_feil
+            );
+        }
+        Ok(tidslinje)
+    }
+
+    /// Tilfellene på tilsynsobjektet og begrensningene på produksjonsplassene deres.
+    async fn hent_fra_ejb(
+        &self,
+        tilsynsobjekt_id: &str,
+        dato: NaiveDate,
+        kodenavn: &Kodenavn,
+    ) -> (
+        std::result::Result<Vec<Tilfelle>, Tidslinjefeil>,
+        std::result::Result<AktiveBegrensninger, Tidslinjefeil>,
+    ) {
+        let sykdomstilfeller: Vec<Sykdomstilfelle> = match self
+            .ejb_client
+            .hent_alle_tilfeller(
+                TilfelleFelt::Tilsynsobjektref.eq(tilsynsobjekt_id),
+                SIDESTORRELSE_TILFELLER,
+            )
+            .try_collect()
+            .await
+        {
+            Ok(tilfeller) => tilfeller,
+            Err(e) => {
+                return (
+                    Err(Tidslinjefeil::Tilfeller(e.to_string())),
+                    Err(Tidslinjefeil::Begrensninger(
+                        "tilfellene kunne ikke hentes".to_string(),
+                    )),
+                );
+            }
+        };
+
+        let begrensninger = self
+            .ejb_client
+            .hent_aktive_begrensninger_for_tilfeller(&sykdomstilfeller, dato, kodenavn)
+            .await
+            .map_err(|e| Tidslinjefeil::Begrensninger(e.to_string()));
+        let tilfeller = sykdomstilfeller
+            .iter()
+            .map(|tilfelle| tilfelle.til_tilfelle(kodenavn))
+            .collect();
+        (Ok(tilfeller), begrensninger)
+    }
+}
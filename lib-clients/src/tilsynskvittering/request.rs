@@ -0,0 +1,67 @@
+use crate::tilsynskvittering::response::Vurdering;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Et nytt utkast til tilsynskvittering.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NyTilsynskvittering {
+    pub tilsynsobjekt_id: String,
+    pub tilsynsdato: NaiveDate,
+    pub unntatt_offentlighet: bool,
+}
+
+impl NyTilsynskvittering {
+    pub fn new(tilsynsobjekt_id: impl Into<String>, tilsynsdato: NaiveDate) -> Self {
+        Self {
+            tilsynsobjekt_id: tilsynsobjekt_id.into(),
+            tilsynsdato,
+            unntatt_offentlighet: false,
+        }
+    }
+
+    pub fn unntatt_offentlighet(mut self) -> Self {
+        self.unntatt_offentlighet = true;
+        self
+    }
+}
+
+/// Et kontrollpunkt som skal legges til et utkast.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NyttKontrollpunkt {
+    pub beskrivelse: String,
+    pub vurdering: Vurdering,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kommentar: Option<String>,
+}
+
+impl NyttKontrollpunkt {
+    pub fn new(beskrivelse: impl Into<String>, vurdering: Vurdering) -> Self {
+        Self {
+            beskrivelse: beskrivelse.into(),
+            vurdering,
+            kommentar: None,
+        }
+    }
+
+    pub fn kommentar(mut self, kommentar: impl Into<String>) -> Self {
+        self.kommentar = Some(kommentar.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kontrollpunkt_uten_kommentar() {
+        let kontrollpunkt = NyttKontrollpunkt::new("Renhold", Vurdering::IkkeEtterlevd);
+
+        assert_eq!(
+            serde_json::to_value(&kontrollpunkt).unwrap(),
+            serde_json::json!({ "beskrivelse": "Renhold", "vurdering": "IKKE_ETTERLEVD" })
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TilsynsobjektKvittering {
@@ -17,11 +18,9 @@ pub struct TidligereTilsynskvitteringInfo {
     pub noarksak_aar: Option<String>,
     #[serde(rename = "noarksakSekvensnummer")]
     pub noarksak_sekvensnummer: Option<String>,
-    #[serde(rename = "feilmelding")]
-    pub feilmelding: Option<String>,
-    #[serde(rename = "feilmeldingKode")]
-    pub feilmelding_kode: Option<i32>,
-    pub status: Option<String>,
+    #[serde(flatten, with = "feilfelter")]
+    pub feil: Option<Arkiveringsfeil>,
+    pub status: Option<Kvitteringsstatus>,
     pub tilsynsdato: Option<NaiveDate>,
     #[serde(rename = "tilsynskvitteringId")]
     pub tilsynskvittering_id: Option<i64>,
@@ -36,3 +35,257 @@ pub struct TidligereTilsynskvitteringInfo {
     #[serde(rename = "unntattOffentlighet")]
     pub unntatt_offentlighet: bool,
 }
+
+impl TidligereTilsynskvitteringInfo {
+    pub fn noarksak(&self) -> Option<Noarksakreferanse> {
+        Noarksakreferanse::fra(&self.noarksak_aar, &self.noarksak_sekvensnummer)
+    }
+}
+
+/// Hvor langt en tilsynskvittering har kommet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Kvitteringsstatus {
+    /// Under arbeid. Kontrollpunkter og bilder kan legges til.
+    Utkast,
+    /// Ferdigstilt og klar til arkivering.
+    Ferdigstilt,
+    /// Arkivering er startet, men ikke bekreftet av arkivet.
+    UnderArkivering,
+    Arkivert,
+    /// Arkiveringen feilet. Se [`Arkiveringsfeil`] på kvitteringen.
+    ArkiveringFeilet,
+    /// Status vi ikke kjenner fra api-et, med verdien slik den kom.
+    #[serde(untagged)]
+    Ukjent(String),
+}
+
+impl Kvitteringsstatus {
+    /// Om kvitteringen fortsatt kan endres.
+    pub fn er_utkast(&self) -> bool {
+        *self == Kvitteringsstatus::Utkast
+    }
+}
+
+/// Feilen tilsynskvittering-api-et rapporterer når arkiveringen av en kvittering feiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arkiveringsfeil {
+    pub kode: Option<i32>,
+    pub melding: String,
+}
+
+impl fmt::Display for Arkiveringsfeil {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kode {
+            Some(kode) => write!(f, "{} (kode {kode})", self.melding),
+            None => f.write_str(&self.melding),
+        }
+    }
+}
+
+/// Api-et sender feilen som de flate feltene `feilmelding` og `feilmeldingKode`.
+mod feilfelter {
+    use super::Arkiveringsfeil;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Feilfelter {
+        #[serde(default)]
+        feilmelding: Option<String>,
+        #[serde(default)]
+        feilmelding_kode: Option<i32>,
+    }
+
+    pub fn serialize<S>(feil: &Option<Arkiveringsfeil>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Feilfelter {
+            feilmelding: feil.as_ref().map(|f| f.melding.clone()),
+            feilmelding_kode: feil.as_ref().and_then(|f| f.kode),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Arkiveringsfeil>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let felter = Feilfelter::deserialize(deserializer)?;
+        Ok(match (felter.feilmelding, felter.feilmelding_kode) {
+            (None, None) => None,
+            (melding, kode) => Some(Arkiveringsfeil {
+                kode,
+                melding: melding.unwrap_or_default(),
+            }),
+        })
+    }
+}
+
+/// Saken i arkivet en tilsynskvittering er journalført på.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Noarksakreferanse {
+    pub aar: String,
+    pub sekvensnummer: String,
+}
+
+impl Noarksakreferanse {
+    fn fra(aar: &Option<String>, sekvensnummer: &Option<String>) -> Option<Self> {
+        match (aar.as_deref(), sekvensnummer.as_deref()) {
+            (Some(aar), Some(sekvensnummer)) if !aar.is_empty() && !sekvensnummer.is_empty() => {
+                Some(Noarksakreferanse {
+                    aar: aar.to_string(),
+                    sekvensnummer: sekvensnummer.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Noarksakreferanse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.aar, self.sekvensnummer)
+    }
+}
+
+/// Hvordan et kontrollpunkt ble vurdert under tilsynet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Vurdering {
+    Etterlevd,
+    IkkeEtterlevd,
+    IkkeVurdert,
+    IkkeRelevant,
+    /// Vurdering vi ikke kjenner fra api-et, med verdien slik den kom.
+    #[serde(untagged)]
+    Ukjent(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Kontrollpunkt {
+    pub id: i64,
+    pub beskrivelse: String,
+    pub vurdering: Vurdering,
+    pub kommentar: Option<String>,
+    /// Bilder som dokumenterer akkurat dette kontrollpunktet.
+    #[serde(default)]
+    pub bilde_ids: Vec<String>,
+}
+
+/// Et bilde fra bilde-api-et som er lagt ved kvitteringen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KvitteringBilde {
+    pub bilde_id: String,
+    pub kontrollpunkt_id: Option<i64>,
+}
+
+/// En hel tilsynskvittering med kontrollpunkter og bilder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tilsynskvittering {
+    #[serde(rename = "tilsynskvitteringId")]
+    pub id: i64,
+    pub tilsynsobjekt_id: String,
+    pub status: Kvitteringsstatus,
+    pub tilsynsdato: Option<NaiveDate>,
+    pub user_id: Option<String>,
+    pub ansatt_navn: Option<String>,
+    #[serde(default)]
+    pub unntatt_offentlighet: bool,
+    pub noarksak_aar: Option<String>,
+    pub noarksak_sekvensnummer: Option<String>,
+    #[serde(flatten, with = "feilfelter")]
+    pub feil: Option<Arkiveringsfeil>,
+    #[serde(default)]
+    pub kontrollpunkter: Vec<Kontrollpunkt>,
+    #[serde(default)]
+    pub bilder: Vec<KvitteringBilde>,
+    pub ferdigstilt: Option<DateTime<Utc>>,
+    pub arkivert: Option<DateTime<Utc>>,
+}
+
+impl Tilsynskvittering {
+    pub fn noarksak(&self) -> Option<Noarksakreferanse> {
+        Noarksakreferanse::fra(&self.noarksak_aar, &self.noarksak_sekvensnummer)
+    }
+
+    /// Kontrollpunktene som ikke er etterlevd.
+    pub fn avvik(&self) -> impl Iterator<Item = &Kontrollpunkt> {
+        self.kontrollpunkter
+            .iter()
+            .filter(|k| k.vurdering == Vurdering::IkkeEtterlevd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tidligere_tilsyn_med_feil_og_ukjent_status() {
+        let info: TidligereTilsynskvitteringInfo = serde_json::from_value(json!({
+            "externalTilsynsobjektId": "to-1",
+            "noarksakAar": "2024",
+            "noarksakSekvensnummer": "1234",
+            "feilmelding": "Arkivet svarte ikke",
+            "feilmeldingKode": 503,
+            "status": "NOE_NYTT",
+            "tilsynsdato": "2024-03-01",
+            "tilsynskvitteringId": 42,
+            "userId": "ola",
+            "ansattNavn": "Ola Nordmann",
+            "antallBilder": 2,
+            "antallKontrollpunkter": 3,
+            "unntattOffentlighet": false
+        }))
+        .unwrap();
+
+        assert_eq!(
+            info.status,
+            Some(Kvitteringsstatus::Ukjent("NOE_NYTT".to_string()))
+        );
+        assert_eq!(
+            info.feil,
+            Some(Arkiveringsfeil {
+                kode: Some(503),
+                melding: "Arkivet svarte ikke".to_string()
+            })
+        );
+        assert_eq!(info.noarksak().unwrap().to_string(), "2024/1234");
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["status"], "NOE_NYTT");
+        assert_eq!(json["feilmelding"], "Arkivet svarte ikke");
+        assert_eq!(json["feilmeldingKode"], 503);
+    }
+
+    #[test]
+    fn kvittering_uten_feil_og_noarksak() {
+        let kvittering: Tilsynskvittering = serde_json::from_value(json!({
+            "tilsynskvitteringId": 7,
+            "tilsynsobjektId": "to-1",
+            "status": "UTKAST",
+            "noarksakAar": null,
+            "kontrollpunkter": [
+                { "id": 1, "beskrivelse": "Renhold", "vurdering": "IKKE_ETTERLEVD",
+                  "kommentar": "Skitten gulvflate", "bildeIds": ["b-1"] },
+                { "id": 2, "beskrivelse": "Merking", "vurdering": "ETTERLEVD" }
+            ],
+            "bilder": [{ "bildeId": "b-1", "kontrollpunktId": 1 }]
+        }))
+        .unwrap();
+
+        assert!(kvittering.status.er_utkast());
+        assert_eq!(kvittering.feil, None);
+        assert_eq!(kvittering.noarksak(), None);
+        assert_eq!(
+            kvittering.avvik().map(|k| k.id).collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+}
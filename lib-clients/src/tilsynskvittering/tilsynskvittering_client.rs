@@ -1,9 +1,16 @@
-use crate::client::ApiClient;
-use crate::error::ApiError;
-use crate::tilsynskvittering::response::TilsynsobjektKvittering;
+use crate::client::{ApiClient, les_json};
+use crate::error::{ApiError, Result};
+use crate::tilsynskvittering::request::{NyTilsynskvittering, NyttKontrollpunkt};
+use crate::tilsynskvittering::response::{
+    Kontrollpunkt, KvitteringBilde, Tilsynskvittering, TilsynsobjektKvittering,
+};
+use reqwest::StatusCode;
 use reqwest::header::{CONTENT_TYPE, HeaderMap};
 use serde::Deserialize;
-use tracing::info;
+use tracing::{error, info};
+use uuid::Uuid;
+
+const RESSURS: &str = "tilsynskvittering-api";
 
 pub struct TilsynskvitteringClient {
     api_client: ApiClient,
@@ -31,7 +38,7 @@ impl TilsynskvitteringClient {
     pub async fn hent_info_tildligere_tilsyn(
         &self,
         tilsynsobjekt_ids: Vec<String>,
-    ) -> Result<Vec<TilsynsobjektKvittering>, ApiError> {
+    ) -> Result<Vec<TilsynsobjektKvittering>> {
         let url = format!(
             "{}/v1/tilsynskvitteringer/tilsynsobjekter/info-tidligere-tilsyn",
             self.api_client.get_base_url(),
@@ -66,11 +73,152 @@ impl TilsynskvitteringClient {
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             Err(ApiError::ClientError {
-                resource: "tilsynskvittering-api".to_string(),
+                resource: RESSURS.to_string(),
                 error_message: format!(
                     "Failed to fetch info. HTTP Status: {status}, response: {error_message}"
                 ),
             })
         }
     }
+
+    fn kvittering_url(&self, id: i64) -> String {
+        format!(
+            "{}/v1/tilsynskvitteringer/{}",
+            self.api_client.get_base_url(),
+            id
+        )
+    }
+
+    /// Henter en hel tilsynskvittering med kontrollpunkter og bilder. Gir `Ok(None)` når
+    /// kvitteringen ikke finnes.
+    #[tracing::instrument(
+        name = "Henter tilsynskvittering",
+        skip(self),
+        fields(request_id = %Uuid::new_v4(), id = %id)
+    )]
+    pub async fn hent_tilsynskvittering(&self, id: i64) -> Result<Option<Tilsynskvittering>> {
+        let request = self
+            .api_client
+            .get_client()
+            .get(self.kvittering_url(id))
+            .bearer_auth(self.api_client.get_token().await);
+        let response = self.api_client.send_request_with_refresh(request).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            info!("Fant ikke tilsynskvittering {id}");
+            return Ok(None);
+        }
+        les_json(response, RESSURS, "Failed to fetch tilsynskvittering")
+            .await
+            .map(Some)
+    }
+
+    /// Oppretter et utkast som kontrollpunkter og bilder kan legges til på.
+    #[tracing::instrument(
+        name = "Oppretter utkast til tilsynskvittering",
+        skip(self),
+        fields(request_id = %Uuid::new_v4(), tilsynsobjekt_id = %ny.tilsynsobjekt_id)
+    )]
+    pub async fn opprett_utkast(&self, ny: &NyTilsynskvittering) -> Result<Tilsynskvittering> {
+        let url = format!("{}/v1/tilsynskvitteringer", self.api_client.get_base_url());
+        let request = self
+            .api_client
+            .get_client()
+            .post(url)
+            .bearer_auth(self.api_client.get_token().await)
+            .json(ny);
+        let response = self.api_client.send_request_with_refresh(request).await?;
+
+        les_json(response, RESSURS, "Failed to create tilsynskvittering").await
+    }
+
+    /// Legger et kontrollpunkt til et utkast.
+    #[tracing::instrument(
+        name = "Legger til kontrollpunkt",
+        skip(self),
+        fields(request_id = %Uuid::new_v4(), id = %id)
+    )]
+    pub async fn legg_til_kontrollpunkt(
+        &self,
+        id: i64,
+        kontrollpunkt: &NyttKontrollpunkt,
+    ) -> Result<Kontrollpunkt> {
+        let url = format!("{}/kontrollpunkter", self.kvittering_url(id));
+        let request = self
+            .api_client
+            .get_client()
+            .post(url)
+            .bearer_auth(self.api_client.get_token().await)
+            .json(kontrollpunkt);
+        let response = self.api_client.send_request_with_refresh(request).await?;
+
+        les_json(response, RESSURS, "Failed to add kontrollpunkt").await
+    }
+
+    /// Legger ved et bilde som allerede er lastet opp til bilde-api-et, eventuelt knyttet
+    /// til ett av kontrollpunktene.
+    #[tracing::instrument(
+        name = "Legger til bilde på tilsynskvittering",
+        skip(self),
+        fields(request_id = %Uuid::new_v4(), id = %id)
+    )]
+    pub async fn legg_til_bilde(
+        &self,
+        id: i64,
+        bilde_id: &str,
+        kontrollpunkt_id: Option<i64>,
+    ) -> Result<KvitteringBilde> {
+        let url = format!("{}/bilder", self.kvittering_url(id));
+        let request = self
+            .api_client
+            .get_client()
+            .post(url)
+            .bearer_auth(self.api_client.get_token().await)
+            .json(&KvitteringBilde {
+                bilde_id: bilde_id.to_string(),
+                kontrollpunkt_id,
+            });
+        let response = self.api_client.send_request_with_refresh(request).await?;
+
+        les_json(response, RESSURS, "Failed to add bilde").await
+    }
+
+    /// Ferdigstiller et utkast. Etter dette kan kvitteringen ikke endres.
+    pub async fn ferdigstill(&self, id: i64) -> Result<Tilsynskvittering> {
+        self.endre_status(id, "ferdigstill").await
+    }
+
+    /// Sender en ferdigstilt kvittering til arkivet. Arkiveringen kan gå asynkront, så
+    /// status kan være [`Kvitteringsstatus::UnderArkivering`] når kallet returnerer.
+    ///
+    /// [`Kvitteringsstatus::UnderArkivering`]: crate::tilsynskvittering::response::Kvitteringsstatus::UnderArkivering
+    pub async fn arkiver(&self, id: i64) -> Result<Tilsynskvittering> {
+        self.endre_status(id, "arkiver").await
+    }
+
+    #[tracing::instrument(
+        name = "Endrer status på tilsynskvittering",
+        skip(self),
+        fields(request_id = %Uuid::new_v4(), id = %id, handling = %handling)
+    )]
+    async fn endre_status(&self, id: i64, handling: &str) -> Result<Tilsynskvittering> {
+        let url = format!("{}/{}", self.kvittering_url(id), handling);
+        let request = self
+            .api_client
+            .get_client()
+            .post(url)
+            .bearer_auth(self.api_client.get_token().await);
+        let response = self.api_client.send_request_with_refresh(request).await?;
+
+        let kvittering: Tilsynskvittering = les_json(
+            response,
+            RESSURS,
+            &format!("Failed to {handling} tilsynskvittering"),
+        )
+        .await?;
+        if let Some(feil) = &kvittering.feil {
+            error!("Tilsynskvittering {id} har feil etter {handling}: {feil}");
+        }
+        Ok(kvittering)
+    }
 }
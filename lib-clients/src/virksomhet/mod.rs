@@ -1,2 +1,3 @@
 pub mod response;
+pub mod sok;
 pub mod virksomhet_client;
@@ -1,7 +1,8 @@
 use chrono::{DateTime, Utc};
+use lib_schemas::typer::organisasjonsnummer::Organisasjonsnummer;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Virksomhet {
     #[serde(rename = "organisasjonsnummer")]
     pub organisasjonsnummer: Option<String>,
@@ -31,12 +32,57 @@ pub struct Virksomhet {
     pub overordnet_info: Option<OverordnetInfo>,
 }
 
+impl Virksomhet {
+    /// Organisasjonsnummeret, hvis det finnes og er gyldig.
+    pub fn orgnr(&self) -> Option<Organisasjonsnummer> {
+        Organisasjonsnummer::new(self.organisasjonsnummer.as_deref()?).ok()
+    }
+
+    /// Organisasjonsnummeret til overordnet enhet, for underenheter.
+    pub fn overordnet_orgnr(&self) -> Option<Organisasjonsnummer> {
+        let overordnet = self
+            .overordnet_info
+            .as_ref()?
+            .organisasjonsnummer
+            .as_deref()?;
+        Organisasjonsnummer::new(overordnet).ok()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VirksomhetFilter {
     pub orgnummer: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl VirksomhetFilter {
+    pub fn orgnummer<'a>(orgnummer: impl IntoIterator<Item = &'a Organisasjonsnummer>) -> Self {
+        Self {
+            orgnummer: Some(
+                orgnummer
+                    .into_iter()
+                    .map(|orgnr| orgnr.as_str().to_string())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Resultatet av et oppslag på mange organisasjonsnummer.
+#[derive(Debug, Default)]
+pub struct VirksomhetOppslag {
+    pub funnet: Vec<Virksomhet>,
+    /// Organisasjonsnummer virksomhet-api-et ikke kjenner til.
+    pub ikke_funnet: Vec<Organisasjonsnummer>,
+}
+
+/// En hovedenhet med alle underenhetene sine.
+#[derive(Debug, Clone)]
+pub struct Enhetstre {
+    pub hovedenhet: Virksomhet,
+    pub underenheter: Vec<Virksomhet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DagligLeder {
     pub rolle: Option<String>,
     pub navn: Option<String>,
@@ -49,7 +95,7 @@ pub struct Adresse {
     pub adresse: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OverordnetInfo {
     pub organisasjonsnummer: Option<String>,
     pub telefonnummer: Option<String>,
@@ -68,3 +114,26 @@ pub struct Underenhet {
     #[serde(rename = "overordnetEnhet")]
     pub overordnet_enhet: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_og_overordnet_orgnr() {
+        let orgnr = Organisasjonsnummer::new("995298775").unwrap();
+        assert_eq!(
+            serde_json::to_value(VirksomhetFilter::orgnummer([&orgnr])).unwrap(),
+            serde_json::json!({ "orgnummer": ["995298775"] })
+        );
+
+        let underenhet: Virksomhet = serde_json::from_value(serde_json::json!({
+            "organisasjonsnummer": "974760673",
+            "virksomhetNavn": "Avdeling",
+            "overordnetInfo": { "organisasjonsnummer": "995298775" }
+        }))
+        .unwrap();
+        assert_eq!(underenhet.overordnet_orgnr(), Some(orgnr));
+        assert_eq!(underenhet.orgnr().unwrap().as_str(), "974760673");
+    }
+}
@@ -0,0 +1,304 @@
+use crate::virksomhet::response::{Adresse, OverordnetInfo, Virksomhet};
+use chrono::{DateTime, NaiveDate, Utc};
+use lib_schemas::typer::kommunenummer::Kommunenummer;
+use lib_schemas::typer::naeringskode::Naeringskode;
+use serde::Deserialize;
+
+/// Søk etter virksomheter. Kriteriene kombineres med OG.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VirksomhetSok {
+    pub navn: Option<String>,
+    /// Tillat stavefeil og delvise treff på navn.
+    pub fuzzy: bool,
+    pub postnummer: Option<String>,
+    pub poststed: Option<String>,
+    pub kommunenummer: Option<Kommunenummer>,
+    /// Kode for organisasjonsform, for eksempel `AS` eller `ENK`.
+    pub organisasjonsform: Option<String>,
+    pub naeringskode: Option<Naeringskode>,
+    /// Ta med slettede virksomheter.
+    pub inkluder_slettede: bool,
+}
+
+impl VirksomhetSok {
+    /// Fuzzy søk på navn.
+    pub fn navn(navn: impl Into<String>) -> Self {
+        Self {
+            navn: Some(navn.into()),
+            fuzzy: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn eksakt_navn(mut self) -> Self {
+        self.fuzzy = false;
+        self
+    }
+
+    pub fn postnummer(mut self, postnummer: impl Into<String>) -> Self {
+        self.postnummer = Some(postnummer.into());
+        self
+    }
+
+    pub fn poststed(mut self, poststed: impl Into<String>) -> Self {
+        self.poststed = Some(poststed.into());
+        self
+    }
+
+    pub fn kommunenummer(mut self, kommunenummer: Kommunenummer) -> Self {
+        self.kommunenummer = Some(kommunenummer);
+        self
+    }
+
+    pub fn organisasjonsform(mut self, organisasjonsform: impl Into<String>) -> Self {
+        self.organisasjonsform = Some(organisasjonsform.into());
+        self
+    }
+
+    pub fn naeringskode(mut self, naeringskode: Naeringskode) -> Self {
+        self.naeringskode = Some(naeringskode);
+        self
+    }
+
+    pub fn inkluder_slettede(mut self) -> Self {
+        self.inkluder_slettede = true;
+        self
+    }
+
+    /// Om søket har minst ett kriterium. Api-et avviser søk uten kriterier.
+    pub fn har_kriterier(&self) -> bool {
+        self.navn.as_deref().is_some_and(|n| !n.trim().is_empty())
+            || self.postnummer.is_some()
+            || self.poststed.is_some()
+            || self.kommunenummer.is_some()
+            || self.organisasjonsform.is_some()
+            || self.naeringskode.is_some()
+    }
+
+    /// Query-parametrene for søket, uten paginering.
+    pub(crate) fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(navn) = &self.navn {
+            query.push(("navn", navn.trim().to_string()));
+            query.push(("fuzzy", self.fuzzy.to_string()));
+        }
+        let mut legg_til = |navn, verdi: Option<&str>| {
+            if let Some(verdi) = verdi {
+                query.push((navn, verdi.trim().to_string()));
+            }
+        };
+        legg_til("postnummer", self.postnummer.as_deref());
+        legg_til("poststed", self.poststed.as_deref());
+        legg_til(
+            "kommunenummer",
+            self.kommunenummer.as_ref().map(Kommunenummer::as_str),
+        );
+        legg_til("organisasjonsform", self.organisasjonsform.as_deref());
+        legg_til(
+            "naeringskode",
+            self.naeringskode.as_ref().map(Naeringskode::as_str),
+        );
+        if self.inkluder_slettede {
+            query.push(("inkluderSlettede", "true".to_string()));
+        }
+        query
+    }
+}
+
+/// Én side med søkeresultater.
+#[derive(Debug, Clone)]
+pub struct VirksomhetSide {
+    pub virksomheter: Vec<Virksomhet>,
+    /// Sidenummeret, fra 0.
+    pub side: u32,
+    pub antall_per_side: u32,
+    pub totalt_antall: u64,
+    pub antall_sider: u32,
+}
+
+impl VirksomhetSide {
+    pub fn er_siste_side(&self) -> bool {
+        self.side + 1 >= self.antall_sider
+    }
+}
+
+/// Svaret fra `virksomheter/sok`, i HAL-format.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SokResponse {
+    #[serde(rename = "_embedded", default)]
+    embedded: SokEmbedded,
+    page: SokPage,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SokEmbedded {
+    #[serde(default)]
+    virksomheter: Vec<SokTreff>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SokPage {
+    size: u32,
+    total_elements: u64,
+    total_pages: u32,
+    number: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SokTreff {
+    organisasjonsnummer: String,
+    navn: Option<String>,
+    organisasjonsform: Option<Kodebeskrivelse>,
+    beliggenhetsadresse: Option<SokAdresse>,
+    postadresse: Option<SokAdresse>,
+    overordnet_enhet: Option<String>,
+    slettedato: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Kodebeskrivelse {
+    kode: Option<String>,
+    beskrivelse: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SokAdresse {
+    adresse: Option<Vec<String>>,
+    postnummer: Option<String>,
+    poststed: Option<String>,
+}
+
+impl From<SokAdresse> for Adresse {
+    fn from(adresse: SokAdresse) -> Self {
+        Adresse {
+            postnummer: adresse.postnummer,
+            poststed: adresse.poststed,
+            adresse: adresse.adresse,
+        }
+    }
+}
+
+impl From<SokTreff> for Virksomhet {
+    fn from(treff: SokTreff) -> Self {
+        let (organisasjonsform_kode, organisasjonsform) = treff
+            .organisasjonsform
+            .map(|f| (f.kode, f.beskrivelse))
+            .unwrap_or_default();
+        Virksomhet {
+            organisasjonsnummer: Some(treff.organisasjonsnummer),
+            virksomhet_navn: treff.navn,
+            beliggenhetsadresse: treff.beliggenhetsadresse.map(Adresse::from),
+            postadresse: treff.postadresse.map(Adresse::from),
+            kontaktperson: None,
+            organisasjonsform,
+            organisasjonsform_kode,
+            slettedato: treff
+                .slettedato
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| DateTime::<Utc>::from_naive_utc_and_offset(d, Utc)),
+            overordnet_info: treff.overordnet_enhet.map(|orgnr| OverordnetInfo {
+                organisasjonsnummer: Some(orgnr),
+                telefonnummer: None,
+                mobiltelefonnummer: None,
+                epostadresse: None,
+                hjemmesideadresse: None,
+            }),
+        }
+    }
+}
+
+impl From<SokResponse> for VirksomhetSide {
+    fn from(response: SokResponse) -> Self {
+        VirksomhetSide {
+            virksomheter: response
+                .embedded
+                .virksomheter
+                .into_iter()
+                .map(Virksomhet::from)
+                .collect(),
+            side: response.page.number,
+            antall_per_side: response.page.size,
+            totalt_antall: response.page.total_elements,
+            antall_sider: response.page.total_pages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_med_alle_kriterier() {
+        let sok = VirksomhetSok::navn(" Brumunddal kjøtt ")
+            .poststed("Brumunddal")
+            .kommunenummer(Kommunenummer::new("3411").unwrap())
+            .organisasjonsform("AS")
+            .naeringskode(Naeringskode::new("10.110").unwrap());
+
+        assert_eq!(
+            sok.query(),
+            vec![
+                ("navn", "Brumunddal kjøtt".to_string()),
+                ("fuzzy", "true".to_string()),
+                ("poststed", "Brumunddal".to_string()),
+                ("kommunenummer", "3411".to_string()),
+                ("organisasjonsform", "AS".to_string()),
+                ("naeringskode", "10.110".to_string()),
+            ]
+        );
+        assert!(!VirksomhetSok::default().har_kriterier());
+    }
+
+    #[test]
+    fn side_mappes_til_virksomhet() {
+        let response: SokResponse = serde_json::from_value(serde_json::json!({
+            "_embedded": { "virksomheter": [{
+                "organisasjonsnummer": "974760673",
+                "navn": "BRUMUNDDAL KJØTT AS",
+                "organisasjonsform": { "kode": "AS", "beskrivelse": "Aksjeselskap" },
+                "beliggenhetsadresse": {
+                    "adresse": ["Tårnvegen 41"], "postnummer": "2380", "poststed": "BRUMUNDDAL"
+                },
+                "overordnetEnhet": "995298775",
+                "slettedato": null
+            }]},
+            "page": { "size": 20, "totalElements": 21, "totalPages": 2, "number": 0 }
+        }))
+        .unwrap();
+
+        let side = VirksomhetSide::from(response);
+
+        assert!(!side.er_siste_side());
+        let virksomhet = &side.virksomheter[0];
+        assert_eq!(
+            virksomhet.virksomhet_navn.as_deref(),
+            Some("BRUMUNDDAL KJØTT AS")
+        );
+        assert_eq!(virksomhet.organisasjonsform_kode.as_deref(), Some("AS"));
+        assert_eq!(
+            virksomhet
+                .beliggenhetsadresse
+                .as_ref()
+                .and_then(|a| a.poststed.as_deref()),
+            Some("BRUMUNDDAL")
+        );
+        assert_eq!(
+            virksomhet.overordnet_orgnr().map(|o| o.to_string()),
+            Some("995298775".to_string())
+        );
+    }
+
+    #[test]
+    fn tom_side_uten_embedded() {
+        let response: SokResponse = serde_json::from_value(serde_json::json!({
+            "page": { "size": 20, "totalElements": 0, "totalPages": 0, "number": 0 }
+        }))
+        .unwrap();
+        let side = VirksomhetSide::from(response);
+        assert!(side.virksomheter.is_empty());
+        assert!(side.er_siste_side());
+    }
+}
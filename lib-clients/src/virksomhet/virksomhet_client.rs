@@ -1,13 +1,27 @@
-use crate::client::ApiClient;
-use crate::error::ApiError;
-use crate::virksomhet::response::{Underenhet, Virksomhet};
+use crate::client::{ApiClient, les_json};
+use crate::error::{ApiError, Result};
+use crate::virksomhet::response::{
+    Enhetstre, Underenhet, Virksomhet, VirksomhetFilter, VirksomhetOppslag,
+};
+use crate::virksomhet::sok::{SokResponse, VirksomhetSide, VirksomhetSok};
+use futures::{Stream, TryStreamExt, stream};
+use lib_schemas::typer::organisasjonsnummer::Organisasjonsnummer;
+use reqwest::StatusCode;
+use std::collections::HashSet;
+use tracing::info;
+use uuid::Uuid;
+
+const RESSURS: &str = "virksomhet-api";
+
+/// Antall organisasjonsnummer per kall ved batch-oppslag.
+const MAKS_ORGNUMMER_PER_KALL: usize = 100;
+/// Hvor mange nivåer opp vi følger overordnet enhet før vi gir opp.
+const MAKS_NIVAAER: usize = 5;
 
-#[allow(dead_code)]
 pub struct VirksomhetClient {
     api_client: ApiClient,
 }
 
-#[allow(dead_code)]
 impl VirksomhetClient {
     pub async fn new() -> Self {
         VirksomhetClient {
@@ -15,7 +29,13 @@ impl VirksomhetClient {
         }
     }
 
-    pub async fn get_virksomhet(&self, orgnr: String) -> Result<Virksomhet, ApiError> {
+    /// Henter en virksomhet. Gir `Ok(None)` når orgnummeret ikke finnes.
+    #[tracing::instrument(
+        name = "Henter virksomhet",
+        skip(self),
+        fields(request_id = %Uuid::new_v4(), orgnr = %orgnr)
+    )]
+    pub async fn get_virksomhet(&self, orgnr: &Organisasjonsnummer) -> Result<Option<Virksomhet>> {
         let url = format!(
             "{}/virksomheter/orgnummer/{}",
             self.api_client.get_base_url(),
@@ -29,32 +49,60 @@ impl VirksomhetClient {
             .bearer_auth(self.api_client.get_token().await);
         let response = self.api_client.send_request_with_refresh(request).await?;
 
-        if response.status().is_success() {
-            let virksomhet: Virksomhet =
-                response.json().await.map_err(|e| ApiError::ClientError {
-                    resource: "reqwest".to_string(),
-                    error_message: e.to_string(),
-                })?;
-            Ok(virksomhet)
-        } else {
-            let status = response.status();
-            let error_message = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(ApiError::ClientError {
-                resource: "virksomhet-api".to_string(),
-                error_message: format!(
-                    "Failed to fetch virksomhet. HTTP Status: {status}, response: {error_message}"
-                ),
-            })
+        if response.status() == StatusCode::NOT_FOUND {
+            info!("Fant ikke virksomhet {orgnr}");
+            return Ok(None);
+        }
+        les_json(response, RESSURS, "Failed to fetch virksomhet")
+            .await
+            .map(Some)
+    }
+
+    /// Henter mange virksomheter med [`VirksomhetFilter`], i bolker på
+    /// [`MAKS_ORGNUMMER_PER_KALL`]. Ukjente orgnummer havner i `ikke_funnet`.
+    #[tracing::instrument(
+        name = "Henter virksomheter",
+        skip(self, orgnummer),
+        fields(request_id = %Uuid::new_v4(), antall = orgnummer.len())
+    )]
+    pub async fn hent_virksomheter(
+        &self,
+        orgnummer: &[Organisasjonsnummer],
+    ) -> Result<VirksomhetOppslag> {
+        let url = format!("{}/virksomheter/orgnummer", self.api_client.get_base_url());
+        let mut funnet = Vec::with_capacity(orgnummer.len());
+
+        for bolk in orgnummer.chunks(MAKS_ORGNUMMER_PER_KALL) {
+            let request = self
+                .api_client
+                .get_client()
+                .post(&url)
+                .bearer_auth(self.api_client.get_token().await)
+                .json(&VirksomhetFilter::orgnummer(bolk));
+            let response = self.api_client.send_request_with_refresh(request).await?;
+            let virksomheter: Vec<Virksomhet> =
+                les_json(response, RESSURS, "Failed to fetch virksomheter").await?;
+            funnet.extend(virksomheter);
         }
+
+        let funnet_orgnr: HashSet<Organisasjonsnummer> =
+            funnet.iter().filter_map(Virksomhet::orgnr).collect();
+        let ikke_funnet = orgnummer
+            .iter()
+            .filter(|orgnr| !funnet_orgnr.contains(orgnr))
+            .cloned()
+            .collect();
+
+        Ok(VirksomhetOppslag {
+            funnet,
+            ikke_funnet,
+        })
     }
 
     pub async fn hent_underenheter_paa_virksomhet(
         &self,
-        orgnr: String,
-    ) -> Result<Vec<Underenhet>, ApiError> {
+        orgnr: &Organisasjonsnummer,
+    ) -> Result<Vec<Underenhet>> {
         let url = format!(
             "{}/virksomheter/{}/underenheter",
             self.api_client.get_base_url(),
@@ -68,25 +116,119 @@ impl VirksomhetClient {
             .bearer_auth(self.api_client.get_token().await);
         let response = self.api_client.send_request_with_refresh(request).await?;
 
-        if response.status().is_success() {
-            let underenheter: Vec<Underenhet> =
-                response.json().await.map_err(|e| ApiError::ClientError {
-                    resource: "reqwest".to_string(),
-                    error_message: e.to_string(),
-                })?;
-            Ok(underenheter)
+        les_json(response, RESSURS, "Failed to fetch underenheter").await
+    }
+
+    /// Henter hovedenheten til en underenhet. Gir `Ok(None)` hvis `orgnr` ikke finnes eller
+    /// ikke har noen overordnet enhet.
+    pub async fn hent_hovedenhet(&self, orgnr: &Organisasjonsnummer) -> Result<Option<Virksomhet>> {
+        let Some(virksomhet) = self.get_virksomhet(orgnr).await? else {
+            return Ok(None);
+        };
+        match virksomhet.overordnet_orgnr() {
+            Some(overordnet) => self.get_virksomhet(&overordnet).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Henter hele enheten `orgnr` hører til: øverste hovedenhet og alle underenhetene.
+    /// Virker både for hovedenheter og underenheter.
+    #[tracing::instrument(
+        name = "Henter enhetstre",
+        skip(self),
+        fields(request_id = %Uuid::new_v4(), orgnr = %orgnr)
+    )]
+    pub async fn hent_enhetstre(&self, orgnr: &Organisasjonsnummer) -> Result<Option<Enhetstre>> {
+        let Some(mut hovedenhet) = self.get_virksomhet(orgnr).await? else {
+            return Ok(None);
+        };
+
+        for _ in 0..MAKS_NIVAAER {
+            let Some(overordnet) = hovedenhet.overordnet_orgnr() else {
+                break;
+            };
+            match self.get_virksomhet(&overordnet).await? {
+                Some(virksomhet) => hovedenhet = virksomhet,
+                None => break,
+            }
+        }
+
+        let Some(hovedenhet_orgnr) = hovedenhet.orgnr() else {
+            return Ok(Some(Enhetstre {
+                hovedenhet,
+                underenheter: Vec::new(),
+            }));
+        };
+        let underenhet_orgnr: Vec<Organisasjonsnummer> = self
+            .hent_underenheter_paa_virksomhet(&hovedenhet_orgnr)
+            .await?
+            .into_iter()
+            .filter_map(|u| Organisasjonsnummer::new(u.organisasjonsnummer?).ok())
+            .collect();
+        let underenheter = if underenhet_orgnr.is_empty() {
+            Vec::new()
         } else {
-            let status = response.status();
-            let error_message = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(ApiError::ClientError {
-                resource: "virksomhet-api".to_string(),
-                error_message: format!(
-                    "Failed to fetch underenheter. HTTP Status: {status}, response: {error_message}"
-                ),
-            })
+            self.hent_virksomheter(&underenhet_orgnr).await?.funnet
+        };
+
+        Ok(Some(Enhetstre {
+            hovedenhet,
+            underenheter,
+        }))
+    }
+
+    /// Henter én side med virksomheter som matcher søket. `side` starter på 0.
+    #[tracing::instrument(
+        name = "Søker etter virksomheter",
+        skip(self),
+        fields(request_id = %Uuid::new_v4())
+    )]
+    pub async fn sok_virksomheter(
+        &self,
+        sok: &VirksomhetSok,
+        side: u32,
+        antall_per_side: u32,
+    ) -> Result<VirksomhetSide> {
+        if !sok.har_kriterier() {
+            return Err(ApiError::ValidationError(
+                "Søk etter virksomheter må ha minst ett kriterium".to_string(),
+            ));
         }
+        let url = format!("{}/virksomheter/sok", self.api_client.get_base_url());
+        let mut query = sok.query();
+        query.push(("side", side.to_string()));
+        query.push(("antall", antall_per_side.to_string()));
+
+        let request = self
+            .api_client
+            .get_client()
+            .get(&url)
+            .query(&query)
+            .bearer_auth(self.api_client.get_token().await);
+        let response = self.api_client.send_request_with_refresh(request).await?;
+
+        let side: SokResponse =
+            les_json(response, RESSURS, "Failed to search virksomheter").await?;
+        Ok(side.into())
+    }
+
+    /// Alle virksomheter som matcher søket, side for side med `antall_per_side` per kall.
+    pub fn sok_alle_virksomheter<'a>(
+        &'a self,
+        sok: &'a VirksomhetSok,
+        antall_per_side: u32,
+    ) -> impl Stream<Item = Result<Virksomhet>> + 'a {
+        let antall_per_side = antall_per_side.max(1);
+        stream::try_unfold(Some(0), move |side| async move {
+            let Some(side) = side else {
+                return Ok::<_, ApiError>(None);
+            };
+            let resultat = self.sok_virksomheter(sok, side, antall_per_side).await?;
+            let neste = (!resultat.er_siste_side()).then_some(side + 1);
+            let virksomheter =
+                stream::iter(resultat.virksomheter.into_iter().map(Ok::<_, ApiError>));
+            Ok(Some((virksomheter, neste)))
+        })
+        .try_flatten()
     }
 }
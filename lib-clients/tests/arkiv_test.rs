@@ -0,0 +1,63 @@
+// Tests for ArkivClient against a local stand-in serving fixtures from tests/fixtures/arkiv.
+
+#![cfg(feature = "arkiv")]
+
+mod support;
+
+#[cfg(test)]
+mod offline {
+    use crate::support;
+    use lib_clients::arkiv::response::ArkiverDokument;
+    use lib_clients::error::ApiError;
+
+    fn dokument(innhold: &[u8]) -> ArkiverDokument {
+        ArkiverDokument::new(
+            "2026".to_string(),
+            "123".to_string(),
+            "Tilsynsrapport".to_string(),
+            "Tilsynsrapport".to_string(),
+            "X".to_string(),
+            "olanor".to_string(),
+            innhold.to_vec(),
+            "tilsynsrapport.pdf".to_string(),
+            vec![],
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn arkivkall(server: &support::StandInServer) -> usize {
+        server
+            .requests()
+            .iter()
+            .filter(|request| request.path == "/arkiv/fil")
+            .count()
+    }
+
+    #[tokio::test]
+    async fn arkiverer_pdf_med_standard_sjekk() {
+        let (server, client) = support::arkiv().await;
+
+        let kvittering = client
+            .legg_til_journalpost_paa_sak(&dokument(b"%PDF-1.7\n%%EOF\n"))
+            .await
+            .unwrap();
+
+        assert_eq!(kvittering.journalpost_id, "JP-1");
+        assert_eq!(arkivkall(&server), 1);
+    }
+
+    #[tokio::test]
+    async fn avviser_html_uten_aa_kalle_arkivet() {
+        let (server, client) = support::arkiv().await;
+
+        let feil = client
+            .legg_til_journalpost_paa_sak(&dokument(b"<!DOCTYPE html><html>Feil</html>"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(feil, ApiError::ValidationError(_)), "{feil:?}");
+        assert_eq!(arkivkall(&server), 0);
+    }
+}
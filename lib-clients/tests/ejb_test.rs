@@ -0,0 +1,68 @@
+// Tests for EjbClient against a local stand-in serving fixtures from tests/fixtures/ejb.
+
+#![cfg(feature = "ejb")]
+
+mod support;
+
+#[cfg(test)]
+mod offline {
+    use crate::support;
+    use futures::TryStreamExt;
+    use lib_clients::ejb::filter::{FilterFelt, TilfelleFelt};
+    use lib_clients::ejb::response_tilfeller::Sykdomstilfelle;
+    use lib_clients::error::ApiError;
+
+    fn offsets(server: &support::StandInServer) -> Vec<String> {
+        server
+            .requests()
+            .into_iter()
+            .filter(|request| request.path == "/v1/tilfeller")
+            .map(|request| request.query["offset"].clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn alle_tilfeller_stopper_etter_kort_side() {
+        let (server, client) = support::ejb().await;
+
+        let tilfeller: Vec<Sykdomstilfelle> = client
+            .hent_alle_tilfeller(TilfelleFelt::Kommunenummer.eq("3411"), 2)
+            .try_collect()
+            .await
+            .unwrap();
+
+        let ider: Vec<&str> = tilfeller.iter().map(|t| t.idstring.as_str()).collect();
+        assert_eq!(ider, ["TILFELLE-1", "TILFELLE-2", "TILFELLE-3"]);
+        assert_eq!(offsets(&server), ["0", "2"]);
+    }
+
+    #[tokio::test]
+    async fn alle_tilfeller_stopper_etter_tom_side() {
+        let (server, client) = support::ejb().await;
+
+        let tilfeller: Vec<Sykdomstilfelle> = client
+            .hent_alle_tilfeller(TilfelleFelt::Kommunenummer.eq("3403"), 2)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(tilfeller.len(), 2);
+        assert_eq!(offsets(&server), ["0", "2"]);
+    }
+
+    #[tokio::test]
+    async fn deserialiseringsfeil_inneholder_ikke_verdier_fra_payload() {
+        let (_server, client) = support::ejb().await;
+
+        let feil = client
+            .hent_tilfelle(TilfelleFelt::Kommunenummer.eq("9999"), 10)
+            .await
+            .unwrap_err();
+
+        let ApiError::DeserializeError { path, .. } = &feil else {
+            panic!("forventet DeserializeError, fikk {feil:?}");
+        };
+        assert_eq!(path, "results[0].version");
+        assert!(!feil.to_string().contains("Ola Nordmann"), "{feil}");
+    }
+}
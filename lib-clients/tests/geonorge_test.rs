@@ -1,5 +1,207 @@
-// Live integration test for the Geonorge address API via GeoNorgeClient.
-// This test hits the real API and may be flaky due to network; it's ignored by default.
+// Tests for GeoNorgeClient. The `offline` tests run against a local stand-in serving fixtures
+// from tests/fixtures/geonorge; the `integration` test hits the real API and is ignored by default.
+
+mod support;
+
+#[cfg(test)]
+mod offline {
+    use crate::support;
+    use lib_clients::geonorge::{
+        AdresseForesporsel, Epsg, Geokoding, GeokodingOppsett, GeonorgeError, Koordinater, Sone,
+        Soner,
+    };
+    use lib_schemas::typer::kommunenummer::Kommunenummer;
+    use lib_schemas::typer::matrikkelnummer::Matrikkelnummer;
+
+    #[tokio::test]
+    async fn koordinater_fra_adresse_med_postnummer_og_poststed() {
+        let (_server, client) = support::geonorge().await;
+
+        let koordinater = client
+            .get_koordinater_fra_adresse("Tårnvegen 41", "2380", "Brumunddal")
+            .await
+            .unwrap();
+
+        assert_eq!(koordinater, Some((60.87750554180073, 10.92918075347748)))
+    }
+
+    #[tokio::test]
+    async fn koordinater_fra_fritekstsok() {
+        let (server, client) = support::geonorge().await;
+
+        let koordinater = client
+            .get_koordinater("Karl Johans gate 1, Oslo")
+            .await
+            .unwrap();
+
+        assert_eq!(koordinater, Some((59.91115371698211, 10.750519275165197)));
+        assert_eq!(server.requests()[0].path, "/adresser/v1/sok");
+    }
+
+    #[tokio::test]
+    async fn ukjent_adresse_gir_no_results() {
+        let (_server, client) = support::geonorge().await;
+
+        let resultat = client.get_koordinater("Finnes ikke 999").await;
+
+        assert!(matches!(resultat, Err(GeonorgeError::NoResults(_))));
+    }
+
+    #[tokio::test]
+    async fn adresse_og_kommune_fra_koordinater() {
+        let (server, client) = support::geonorge().await;
+        let koordinater = Koordinater::new(60.8775, 10.9291);
+
+        let adresse = client
+            .get_addresse_fra_koordinater(&koordinater)
+            .await
+            .unwrap()
+            .unwrap();
+        let kommune = client
+            .get_kommune_og_fylke_from_koordinat(&koordinater)
+            .await
+            .unwrap();
+
+        assert_eq!(adresse.address_text, "Tårnvegen 41");
+        assert_eq!(kommune.kommunenummer, "3411");
+        let punkt = &server.requests()[1];
+        assert_eq!(punkt.path, "/kommuneinfo/v1/punkt");
+        assert_eq!(
+            punkt.query.get("koordsys").map(String::as_str),
+            Some("4258")
+        );
+    }
+
+    #[tokio::test]
+    async fn utm_koordinater_normaliseres_til_euref89() {
+        let (server, client) = support::geonorge().await;
+        let koordinater = Koordinater::utm(Epsg::Utm33, 6_756_003.791, 279_076.907);
+
+        let kommune = client
+            .get_kommune_og_fylke_from_koordinat(&koordinater)
+            .await
+            .unwrap();
+
+        assert_eq!(kommune.kommunenummer, "3411");
+        let punkt = &server.requests()[0];
+        let nord: f64 = punkt.query["nord"].parse().unwrap();
+        let ost: f64 = punkt.query["ost"].parse().unwrap();
+        assert!((nord - 60.87750554180073).abs() < 1e-5, "nord var {nord}");
+        assert!((ost - 10.92918075347748).abs() < 1e-5, "ost var {ost}");
+        assert_eq!(punkt.query["koordsys"], "4258");
+    }
+
+    #[tokio::test]
+    async fn batch_geokoding_rapporterer_treff_tvetydige_og_ukjente() {
+        let (_server, client) = support::geonorge().await;
+        let foresporsler = vec![
+            AdresseForesporsel::new("Tårnveien 41")
+                .postnummer("2380")
+                .poststed("Brumunddal"),
+            AdresseForesporsel::new("Storgata 1"),
+            AdresseForesporsel::new("Finnes ikke 999"),
+            AdresseForesporsel::new("  "),
+        ];
+
+        let resultater = client
+            .geokod_adresser(&foresporsler, &GeokodingOppsett::default())
+            .await;
+
+        assert_eq!(resultater.len(), 4);
+        let treff = resultater[0].1.as_ref().unwrap().treff().unwrap();
+        assert_eq!(treff.adresse.address_text, "Tårnvegen 41");
+        assert_eq!(
+            treff.koordinater(),
+            Some((60.87750554180073, 10.92918075347748))
+        );
+        assert!(treff.score.total > 0.9, "score var {}", treff.score.total);
+        assert!(matches!(
+            resultater[1].1,
+            Ok(Geokoding::Tvetydig { ref kandidater }) if kandidater.len() == 2
+        ));
+        assert!(matches!(
+            resultater[2].1,
+            Ok(Geokoding::IngenTreff { ref kandidater }) if kandidater.is_empty()
+        ));
+        assert!(matches!(
+            resultater[3].1,
+            Err(GeonorgeError::InvalidAddress(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn adresser_og_punkt_fra_matrikkel() {
+        let (_server, client) = support::geonorge().await;
+        let knr = Kommunenummer::new("3411").unwrap();
+        let matrikkel = Matrikkelnummer::new(knr, "46", "130").unwrap();
+
+        let resultat = client.get_adresser_fra_matrikkel(&matrikkel).await.unwrap();
+
+        assert_eq!(resultat.adresser.len(), 2);
+        let punkt = resultat.representasjonspunkt.unwrap();
+        assert!((punkt.latitude - 60.8776).abs() < 1e-9);
+        assert!((punkt.longitude - 10.9293).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn kommune_fra_kommunenummer() {
+        let (_server, client) = support::geonorge().await;
+
+        let kommune = client
+            .get_kommune(&Kommunenummer::new("3411").unwrap())
+            .await
+            .unwrap();
+        let ukjent = client
+            .get_kommune(&Kommunenummer::new("9999").unwrap())
+            .await;
+
+        assert_eq!(kommune.kommunenavn, "Ringsaker");
+        assert_eq!(kommune.fylkesnavn, "Innlandet");
+        let boks = kommune.avgrensningsboks.unwrap();
+        assert!(boks.inneholder(&Koordinater::new(60.8775, 10.9291)));
+        assert!(matches!(ukjent, Err(GeonorgeError::NoResults(_))));
+    }
+
+    #[tokio::test]
+    async fn adresser_innenfor_radius_har_avstand() {
+        let (_server, client) = support::geonorge().await;
+        let senter = Koordinater::new(60.8865, 10.9291);
+
+        let adresser = client
+            .get_adresser_innenfor_radius(&senter, 2_000.0)
+            .await
+            .unwrap();
+
+        assert_eq!(adresser.len(), 1);
+        assert_eq!(adresser[0].objekt.address_text, "Tårnvegen 41");
+        assert!((adresser[0].avstand_meter - 1_000.0).abs() < 10.0);
+        assert!(
+            client
+                .get_adresser_innenfor_radius(&senter, 500.0)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn adresser_i_soner_rundt_utbrudd() {
+        let (server, client) = support::geonorge().await;
+        let utbrudd = Koordinater::new(60.8865, 10.9291);
+
+        let plasseringer = client
+            .get_adresser_i_soner(&utbrudd, &Soner::default())
+            .await
+            .unwrap();
+
+        // 10 km deles opp i flere søk, og samme adresse fra flere fliser telles én gang.
+        assert!(server.requests().len() > 1);
+        assert_eq!(plasseringer.len(), 1);
+        assert_eq!(plasseringer[0].objekt.address_text, "Tårnvegen 41");
+        assert_eq!(plasseringer[0].sone, Sone::Beskyttelsessone);
+        assert!((plasseringer[0].avstand_meter - 1_000.0).abs() < 10.0);
+    }
+}
 
 #[cfg(test)]
 mod integration {
@@ -0,0 +1,168 @@
+//! Lokal stand-in for eksterne api-er, slik at klientene kan testes uten nett.
+//!
+//! Serveren svarer med JSON-fixtures fra `tests/fixtures/<navn>/`. Hver fixture-fil beskriver
+//! hvilken path og hvilke query-parametre den svarer på:
+//!
+//! ```json
+//! { "path": "/adresser/v1/sok", "query": { "sok": "Karl Johans gate 1" }, "body": { ... } }
+//! ```
+//!
+//! Fixturen med flest matchende query-parametre vinner. Requests uten fixture får 404.
+#![allow(dead_code)]
+
+use axum::Router;
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+#[cfg(feature = "arkiv")]
+use lib_clients::arkiv::arkiv_client::ArkivClient;
+#[cfg(feature = "ejb")]
+use lib_clients::ejb::ejb_client::EjbClient;
+use lib_clients::geonorge::GeoNorgeClient;
+#[cfg(any(feature = "arkiv", feature = "ejb"))]
+use lib_clients::{client::ApiClient, config::ClientConfiguration};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Fixture {
+    path: String,
+    #[serde(default)]
+    query: HashMap<String, String>,
+    #[serde(default = "ok_status")]
+    status: u16,
+    body: Value,
+}
+
+fn ok_status() -> u16 {
+    200
+}
+
+#[derive(Debug, Clone)]
+pub struct MottattRequest {
+    pub path: String,
+    pub query: HashMap<String, String>,
+}
+
+#[derive(Clone)]
+struct StandInState {
+    fixtures: Arc<Vec<Fixture>>,
+    requests: Arc<Mutex<Vec<MottattRequest>>>,
+}
+
+pub struct StandInServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<MottattRequest>>>,
+}
+
+impl StandInServer {
+    /// Starter en server på en ledig port med alle fixtures i `tests/fixtures/<navn>/`.
+    pub async fn start(navn: &str) -> Self {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(navn);
+        let mut fixtures = Vec::new();
+        for entry in std::fs::read_dir(&dir).expect("fixture-katalogen finnes") {
+            let path = entry.expect("kan lese fixture-katalogen").path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let innhold = std::fs::read_to_string(&path).expect("kan lese fixture");
+                let fixture: Fixture = serde_json::from_str(&innhold)
+                    .unwrap_or_else(|e| panic!("ugyldig fixture {}: {e}", path.display()));
+                fixtures.push(fixture);
+            }
+        }
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let state = StandInState {
+            fixtures: Arc::new(fixtures),
+            requests: requests.clone(),
+        };
+        let app = Router::new().fallback(svar).with_state(state);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        StandInServer {
+            base_url: format!("http://{addr}"),
+            requests,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Alle requests serveren har mottatt, i rekkefølge.
+    pub fn requests(&self) -> Vec<MottattRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn svar(
+    State(state): State<StandInState>,
+    uri: Uri,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let path = uri.path().to_string();
+    state.requests.lock().unwrap().push(MottattRequest {
+        path: path.clone(),
+        query: query.clone(),
+    });
+
+    let fixture = state
+        .fixtures
+        .iter()
+        .filter(|f| f.path == path && f.query.iter().all(|(k, v)| query.get(k) == Some(v)))
+        .max_by_key(|f| f.query.len());
+
+    match fixture {
+        Some(fixture) => Response::builder()
+            .status(fixture.status)
+            .header("content-type", "application/json")
+            .body(Body::from(fixture.body.to_string()))
+            .unwrap(),
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("Ingen fixture for {path} med {query:?}"),
+        )
+            .into_response(),
+    }
+}
+
+/// Stand-in for GeoNorge sine adresse- og kommuneinfo-api, med en klient som peker mot den.
+pub async fn geonorge() -> (StandInServer, GeoNorgeClient) {
+    let server = StandInServer::start("geonorge").await;
+    let client = GeoNorgeClient::builder()
+        .adresser_url(server.url("/adresser/v1"))
+        .kommuneinfo_url(server.url("/kommuneinfo/v1"))
+        .max_retries(0)
+        .build()
+        .unwrap();
+    (server, client)
+}
+
+/// Stand-in for EJB, med en klient som henter token fra `/token` på samme server.
+#[cfg(feature = "ejb")]
+pub async fn ejb() -> (StandInServer, EjbClient) {
+    let server = StandInServer::start("ejb").await;
+    let config = ClientConfiguration::new("ejb", &server.url("/token"), "hemmelig").await;
+    let api_client = ApiClient::from_config(server.url(""), &config).await;
+    (server, EjbClient::from_api_client(api_client))
+}
+
+/// Stand-in for arkivet, med en klient som henter token fra `/token` på samme server.
+#[cfg(feature = "arkiv")]
+pub async fn arkiv() -> (StandInServer, ArkivClient) {
+    let server = StandInServer::start("arkiv").await;
+    let config = ClientConfiguration::new("arkiv", &server.url("/token"), "hemmelig").await;
+    let api_client = ApiClient::from_config(server.url(""), &config).await;
+    (server, ArkivClient::from_api_client(api_client))
+}
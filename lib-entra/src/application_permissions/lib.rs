@@ -3,7 +3,10 @@ use secrecy::{ExposeSecret, SecretString};
 
 use crate::{
     error::EntraError,
-    types::{GRAPH_USER_SELECT_FIELDS, GraphUser, GraphUserSearchResponse, Result},
+    graph::fetch_member_of,
+    types::{
+        GRAPH_USER_SELECT_FIELDS, GraphUser, GraphUserMemberOf, GraphUserSearchResponse, Result,
+    },
 };
 
 pub async fn get_user_from_employee_id(
@@ -50,3 +53,17 @@ pub async fn get_user_from_employee_id(
         None => Err(EntraError::NoSuchEmployeeId(employee_id.to_string())),
     }
 }
+
+/// (Krever *application permission*)
+///
+/// Henter id-ene til alle gruppene brukeren med gitt objekt-id er medlem av.
+pub async fn get_user_groups(
+    access_token: SecretString,
+    user_id: &str,
+) -> Result<Vec<GraphUserMemberOf>> {
+    fetch_member_of(
+        &access_token,
+        format!("https://graph.microsoft.com/v1.0/users/{user_id}/memberOf?$select=id"),
+    )
+    .await
+}
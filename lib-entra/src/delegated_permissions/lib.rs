@@ -2,11 +2,11 @@ use reqwest::{StatusCode, header};
 
 use crate::{
     error::EntraError,
-    types::{GraphUser, GraphUserMemberOf, OboConfig, Result},
+    graph::fetch_member_of,
+    types::{GraphUser, OboConfig, Result},
 };
 use base64::{Engine as _, engine::general_purpose};
 use secrecy::{ExposeSecret, SecretString};
-use serde::Deserialize;
 use serde_json;
 use tracing::{self, instrument};
 
@@ -22,51 +22,6 @@ pub async fn get_user(user_token: &str, include_groups: bool) -> Result<GraphUse
     get_user_profile(&graph_token, include_groups).await
 }
 
-#[derive(Deserialize)]
-struct MemberOfPage {
-    value: Vec<GraphUserMemberOf>,
-    #[serde(rename = "@odata.nextLink")]
-    next_link: Option<String>,
-}
-
-async fn fetch_member_of(token: &SecretString) -> Result<Vec<GraphUserMemberOf>> {
-    let client = reqwest::Client::new();
-    let mut url = "https://graph.microsoft.com/v1.0/me/memberOf?$select=id".to_string();
-    let mut all: Vec<GraphUserMemberOf> = Vec::new();
-
-    loop {
-        let resp = client
-            .get(&url)
-            .bearer_auth(token.expose_secret())
-            .send()
-            .await
-            .map_err(|e| EntraError::Network(e.to_string()))?;
-
-        let status = resp.status();
-        let body = resp
-            .text()
-            .await
-            .map_err(|e| EntraError::Network(e.to_string()))?;
-
-        if !status.is_success() {
-            return Err(EntraError::UnexpectedResponse { status, body });
-        }
-
-        let page: MemberOfPage =
-            serde_json::from_str(&body).map_err(|e| EntraError::Deserialize(e.to_string()))?;
-
-        all.extend(page.value);
-
-        if let Some(next) = page.next_link {
-            url = next;
-        } else {
-            break;
-        }
-    }
-
-    Ok(all)
-}
-
 #[instrument(name = "Henter fra graphAPIet", skip(token), level = "info")]
 async fn get_user_profile(token: &SecretString, include_groups: bool) -> Result<GraphUser> {
     tracing::info!("Henter brukerinformasjon fra graphAPIet");
@@ -103,7 +58,12 @@ async fn get_user_profile(token: &SecretString, include_groups: bool) -> Result<
     };
 
     if include_groups {
-        match fetch_member_of(token).await {
+        match fetch_member_of(
+            token,
+            "https://graph.microsoft.com/v1.0/me/memberOf?$select=id".to_string(),
+        )
+        .await
+        {
             Ok(groups) => {
                 user.groups = Some(groups);
             }
@@ -0,0 +1,62 @@
+use reqwest::StatusCode;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::{
+    error::EntraError,
+    types::{GraphUserMemberOf, Result},
+};
+
+#[derive(Deserialize)]
+struct MemberOfPage {
+    value: Vec<GraphUserMemberOf>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+/// Henter alle sidene fra et `memberOf`-endepunkt ved å følge `@odata.nextLink`.
+pub(crate) async fn fetch_member_of(
+    token: &SecretString,
+    url: String,
+) -> Result<Vec<GraphUserMemberOf>> {
+    let client = reqwest::Client::new();
+    let mut url = url;
+    let mut all: Vec<GraphUserMemberOf> = Vec::new();
+
+    loop {
+        let response = client
+            .get(&url)
+            .bearer_auth(token.expose_secret())
+            .send()
+            .await
+            .map_err(|e| EntraError::Network(e.to_string()))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| EntraError::Network(e.to_string()))?;
+
+        let page = match status {
+            StatusCode::OK => serde_json::from_str::<MemberOfPage>(&body)
+                .map_err(|e| EntraError::Deserialize(e.to_string()))?,
+            StatusCode::UNAUTHORIZED => return Err(EntraError::Unauthorized),
+            StatusCode::FORBIDDEN => return Err(EntraError::Forbidden),
+            other => {
+                return Err(EntraError::UnexpectedResponse {
+                    status: other,
+                    body,
+                });
+            }
+        };
+
+        all.extend(page.value);
+
+        match page.next_link {
+            Some(next) => url = next,
+            None => break,
+        }
+    }
+
+    Ok(all)
+}
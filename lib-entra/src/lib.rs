@@ -3,4 +3,6 @@ pub mod application_permissions;
 #[cfg(feature = "delegated_permissions")]
 pub mod delegated_permissions;
 pub mod error;
+#[cfg(any(feature = "application_permissions", feature = "delegated_permissions"))]
+mod graph;
 pub mod types;
@@ -40,6 +40,7 @@ pub struct GraphUserSearchResponse {
     pub value: Vec<GraphUser>,
 }
 
+#[cfg(feature = "delegated_permissions")]
 #[derive(Debug, Clone)]
 pub(crate) struct OboConfig {
     pub tenant_id: String,
@@ -47,6 +48,7 @@ pub(crate) struct OboConfig {
     pub client_secret: String,
 }
 
+#[cfg(feature = "delegated_permissions")]
 impl OboConfig {
     pub fn from_env() -> Result<Self> {
         let tenant_id = std::env::var("AZURE_TENANT_ID")
@@ -0,0 +1,92 @@
+//! The `bekymringsmeldinger_all` stream, which collects bekymringsmeldinger from every source
+//! stream, built on the generic helpers in [`crate::consumer`].
+
+use async_nats::jetstream::stream::Stream;
+use async_nats::jetstream::{Context, Message};
+
+use crate::consumer;
+use crate::error::Result;
+use crate::topology::{SourceSpec, StreamSpec};
+
+pub const STREAM_NAME: &str = "bekymringsmeldinger_all";
+pub const NUM_REPLICAS: usize = 3;
+
+/// `(source stream, filter subjects)` for every source of [`STREAM_NAME`]. Only the schema
+/// versions listed here are sourced.
+pub const SOURCES: [(&str, &[&str]); 3] = [
+    (
+        "bekymringsmeldinger_rodtkjott",
+        &[
+            "bekymringsmeldinger.rodtkjott.v1.>",
+            "bekymringsmeldinger.rodtkjott.v2.>",
+        ],
+    ),
+    (
+        "bekymringsmeldinger_publikum",
+        &["bekymringsmeldinger.publikum.v1.>"],
+    ),
+    (
+        "bekymringsmeldinger_hvittkjott",
+        &["bekymringsmeldinger.hvittkjott.v1.>"],
+    ),
+];
+
+/// One source per stream. A single filter is sent as `filter_subject`, several as subject
+/// transforms.
+pub fn sources() -> Vec<SourceSpec> {
+    SOURCES
+        .iter()
+        .map(|(name, filter_subjects)| match filter_subjects {
+            [filter_subject] => SourceSpec::new(name, Some(filter_subject)),
+            _ => SourceSpec::with_filter_subjects(name, filter_subjects),
+        })
+        .collect()
+}
+
+/// The stream as a [`StreamSpec`], for services that declare it in their [`crate::topology::Topology`].
+pub fn stream_spec() -> StreamSpec {
+    StreamSpec {
+        name: STREAM_NAME.to_string(),
+        sources: sources(),
+        num_replicas: NUM_REPLICAS,
+        ..Default::default()
+    }
+}
+
+pub async fn get_or_create_sourced_stream(context: &Context) -> Result<Stream> {
+    let sources: Vec<_> = sources().iter().map(SourceSpec::to_source).collect();
+    consumer::get_or_create_sourced_stream(context, STREAM_NAME, &sources, NUM_REPLICAS).await
+}
+
+pub async fn get_all_messages(jetstream: &Context, subject: String) -> Result<Vec<Message>> {
+    consumer::get_all_messages_from_subject_and_stream(jetstream, subject, STREAM_NAME).await
+}
+
+pub async fn get_last_message(jetstream: &Context, subject: String) -> Result<Message> {
+    consumer::get_last_message_from_subject_and_stream(jetstream, subject, STREAM_NAME).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::topology::Topology;
+
+    #[test]
+    fn every_source_stream_is_sourced_once() {
+        let sources = sources();
+        let names: HashSet<_> = sources.iter().map(|source| source.name.as_str()).collect();
+        assert_eq!(names.len(), sources.len());
+
+        let rodtkjott = sources[0].to_source();
+        assert_eq!(rodtkjott.filter_subject, None);
+        assert_eq!(rodtkjott.subject_transforms.len(), 2);
+
+        let topology = Topology {
+            streams: vec![stream_spec()],
+            ..Default::default()
+        };
+        assert!(topology.validate().is_ok());
+    }
+}
@@ -1,8 +1,9 @@
+use crate::bekymringsmeldinger;
 use crate::error::{Error, Result};
 use async_nats::jetstream::consumer::PullConsumer;
-use async_nats::jetstream::stream::{self, Source, Stream};
+use async_nats::jetstream::stream::{self, Stream};
 use async_nats::jetstream::{Context, Message, consumer};
-use futures::{StreamExt, TryStreamExt};
+use futures::TryStreamExt;
 use std::time::Duration;
 
 pub async fn get_or_create_durable_consumer(
@@ -117,42 +118,30 @@ pub async fn create_ephemeral_consumer_all_per_subject(
     Ok(consumer)
 }
 
-pub async fn get_or_create_bm_sourced_stream(context: &Context) -> Result<Stream> {
+/// Creates a stream without subjects of its own that collects messages from `sources`.
+pub async fn get_or_create_sourced_stream(
+    context: &Context,
+    stream_name: &str,
+    sources: &[stream::Source],
+    num_replicas: usize,
+) -> Result<Stream> {
     let stream = context
         .get_or_create_stream(stream::Config {
-            name: "bekymringsmeldinger_all".to_string(),
-            num_replicas: 3,
+            name: stream_name.to_string(),
+            num_replicas,
             subjects: vec![],
-            // Configure sources
-            sources: vec![
-                Source {
-                    name: "bekymringsmeldinger_rodtkjott".to_string(),
-                    filter_subject: Some("bekymringsmeldinger.rodtkjott.v1.>".to_string()),
-                    ..Default::default()
-                },
-                Source {
-                    name: "bekymringsmeldinger_publikum".to_string(),
-                    filter_subject: Some("bekymringsmeldinger.publikum.v1.>".to_string()),
-                    ..Default::default()
-                },
-                Source {
-                    name: "bekymringsmeldinger_rodtkjott".to_string(),
-                    filter_subject: Some("bekymringsmeldinger.rodtkjott.v2.>".to_string()),
-                    ..Default::default()
-                },
-                Source {
-                    name: "bekymringsmeldinger_hvittkjott".to_string(),
-                    filter_subject: Some("bekymringsmeldinger.hvittkjott.v1.>".to_string()),
-                    ..Default::default()
-                },
-            ]
-            .into(),
+            sources: Some(sources.to_vec()),
             ..Default::default()
         })
         .await?;
     Ok(stream)
 }
 
+#[deprecated(note = "use lib_nats::bekymringsmeldinger::get_or_create_sourced_stream")]
+pub async fn get_or_create_bm_sourced_stream(context: &Context) -> Result<Stream> {
+    bekymringsmeldinger::get_or_create_sourced_stream(context).await
+}
+
 pub async fn get_consumer_from_stream(
     jetstream: &Context,
     consumer_name: &str,
@@ -239,35 +228,12 @@ pub async fn get_or_create_durable_max_deliveries_consumer(
     Ok(consumer)
 }
 
+#[deprecated(note = "use lib_nats::bekymringsmeldinger::get_all_messages")]
 pub async fn get_all_messages_from_subject(
     jetstream: &Context,
     subject: String,
 ) -> Result<Vec<Message>> {
-    let mut all_messages = Vec::new();
-    let consumer =
-        create_ephemeral_consumer_all_per_subject(jetstream, "bekymringsmeldinger_all", subject)
-            .await?;
-
-    loop {
-        let mut messages = consumer
-            .fetch()
-            .max_messages(100)
-            .messages()
-            .await
-            .map_err(|err| Error::StreamError(err.to_string()))?;
-
-        let mut found_messages = false;
-
-        while let Ok(Some(message)) = messages.try_next().await {
-            found_messages = true;
-            all_messages.push(message);
-        }
-
-        if !found_messages {
-            break;
-        }
-    }
-    Ok(all_messages)
+    bekymringsmeldinger::get_all_messages(jetstream, subject).await
 }
 
 pub async fn get_all_messages_from_subject_and_stream(
@@ -300,13 +266,12 @@ pub async fn get_all_messages_from_subject_and_stream(
     Ok(all_messages)
 }
 
-pub async fn get_last_message_from_subject(
+pub async fn get_last_message_from_subject_and_stream(
     jetstream: &Context,
     subject: String,
+    stream: &str,
 ) -> Result<Message> {
-    let consumer =
-        create_ephemeral_consumer_last_per_subject(jetstream, "bekymringsmeldinger_all", subject)
-            .await?;
+    let consumer = create_ephemeral_consumer_last_per_subject(jetstream, stream, subject).await?;
 
     let mut messages = consumer
         .fetch()
@@ -326,36 +291,20 @@ pub async fn get_last_message_from_subject(
     }
 }
 
+#[deprecated(note = "use lib_nats::bekymringsmeldinger::get_last_message")]
+pub async fn get_last_message_from_subject(
+    jetstream: &Context,
+    subject: String,
+) -> Result<Message> {
+    bekymringsmeldinger::get_last_message(jetstream, subject).await
+}
+
+#[deprecated(note = "use lib_nats::bekymringsmeldinger::get_all_messages")]
 pub async fn get_all_messages_from_bm_stream_subject(
     jetstream: &Context,
     subject: String,
 ) -> Result<Vec<Message>> {
-    let mut all_messages = Vec::new();
-    let consumer =
-        create_ephemeral_consumer_all_per_subject(jetstream, "bekymringsmeldinger_all", subject)
-            .await?;
-
-    loop {
-        let mut messages = consumer
-            .fetch()
-            .max_messages(100)
-            .messages()
-            .await
-            .map_err(|err| Error::StreamError(err.to_string()))?
-            .take(100);
-
-        let mut found_messages = false;
-
-        while let Ok(Some(message)) = messages.try_next().await {
-            found_messages = true;
-            all_messages.push(message);
-        }
-
-        if !found_messages {
-            break;
-        }
-    }
-    Ok(all_messages)
+    bekymringsmeldinger::get_all_messages(jetstream, subject).await
 }
 
 pub async fn create_kv_bucket(
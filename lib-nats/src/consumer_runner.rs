@@ -0,0 +1,276 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use async_nats::HeaderMap;
+use async_nats::jetstream::consumer::PullConsumer;
+use async_nats::jetstream::consumer::pull::{MessagesError, MessagesErrorKind};
+use async_nats::jetstream::{AckKind, Message};
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use tokio::time::{Instant, interval_at};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::error::{Error, Result};
+
+const DEFAULT_MAX_CONCURRENCY: usize = 10;
+const DEFAULT_BATCH_SIZE: usize = 20;
+// Godt under ack_wait på 30 sekunder i get_or_create_durable_max_deliveries_consumer
+const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy)]
+pub struct RunnerConfig {
+    pub max_concurrency: usize,
+    pub batch_size: usize,
+    /// How often a running handler extends the ack deadline with `AckKind::Progress`.
+    /// Must be shorter than the consumer's `ack_wait`.
+    pub progress_interval: Duration,
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            batch_size: DEFAULT_BATCH_SIZE,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+        }
+    }
+}
+
+/// What to do with a message once the handler is done with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandlerOutcome {
+    /// Processed, ack it.
+    Ok,
+    /// Transient failure, nak it so it is redelivered after the delay.
+    Retry(Duration),
+    /// The message can never be processed, term it so it is not redelivered. Termed messages
+    /// are not moved by [`crate::dead_letter::forward_dead_letters`].
+    Fatal(String),
+}
+
+impl HandlerOutcome {
+    pub fn ack_kind(&self) -> AckKind {
+        match self {
+            HandlerOutcome::Ok => AckKind::Ack,
+            HandlerOutcome::Retry(delay) => AckKind::Nak(Some(*delay)),
+            HandlerOutcome::Fatal(_) => AckKind::Term,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Delivery<T> {
+    pub payload: T,
+    pub subject: String,
+    pub headers: Option<HeaderMap>,
+    /// Delivery attempt, starting at 1. Both `delivered` and `stream_sequence` are 0 if the
+    /// message has no JetStream ack subject.
+    pub delivered: i64,
+    pub stream_sequence: u64,
+}
+
+/// Runs a handler for every message on a pull consumer, deserializing the JSON payload to `T`.
+///
+/// Payloads that fail to deserialize are termed. Messages the handler is still working on
+/// when the cancellation token fires are finished and acked before `run` returns.
+pub struct ConsumerRunner<T> {
+    consumer: PullConsumer,
+    config: RunnerConfig,
+    payload: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> ConsumerRunner<T> {
+    pub fn new(consumer: PullConsumer) -> Self {
+        Self::with_config(consumer, RunnerConfig::default())
+    }
+
+    pub fn with_config(consumer: PullConsumer, config: RunnerConfig) -> Self {
+        Self {
+            consumer,
+            config,
+            payload: PhantomData,
+        }
+    }
+
+    pub async fn run<F, Fut>(&self, shutdown: CancellationToken, handler: F) -> Result<()>
+    where
+        F: Fn(Delivery<T>) -> Fut,
+        Fut: Future<Output = HandlerOutcome>,
+    {
+        let messages = self
+            .consumer
+            .stream()
+            .max_messages_per_batch(self.config.batch_size)
+            .messages()
+            .await
+            .map_err(|err| Error::PullError(err.to_string()))?;
+
+        let handler = &handler;
+        drive(messages, shutdown, self.config.max_concurrency, |message| {
+            self.process(message, handler)
+        })
+        .await
+    }
+
+    async fn process<F, Fut>(&self, message: Message, handler: &F)
+    where
+        F: Fn(Delivery<T>) -> Fut,
+        Fut: Future<Output = HandlerOutcome>,
+    {
+        let outcome = match serde_json::from_slice::<T>(&message.payload) {
+            Ok(payload) => {
+                let (delivered, stream_sequence) = message
+                    .info()
+                    .map(|info| (info.delivered, info.stream_sequence))
+                    .unwrap_or_default();
+                let delivery = Delivery {
+                    payload,
+                    subject: message.subject.to_string(),
+                    headers: message.headers.clone(),
+                    delivered,
+                    stream_sequence,
+                };
+                self.with_progress(&message, handler(delivery)).await
+            }
+            Err(err) => HandlerOutcome::Fatal(format!("Failed to deserialize payload: {err}")),
+        };
+
+        if let HandlerOutcome::Fatal(reason) = &outcome {
+            warn!("Terminating message on {}: {reason}", message.subject);
+        }
+        if let Err(err) = message.ack_with(outcome.ack_kind()).await {
+            warn!(
+                "Failed to send {:?} for message on {}: {err}",
+                outcome.ack_kind(),
+                message.subject
+            );
+        }
+    }
+
+    async fn with_progress<Fut>(&self, message: &Message, handling: Fut) -> HandlerOutcome
+    where
+        Fut: Future<Output = HandlerOutcome>,
+    {
+        let interval = self.config.progress_interval;
+        let mut progress = interval_at(Instant::now() + interval, interval);
+        let mut handling = std::pin::pin!(handling);
+        loop {
+            tokio::select! {
+                outcome = &mut handling => return outcome,
+                _ = progress.tick() => {
+                    if let Err(err) = message.ack_with(AckKind::Progress).await {
+                        warn!("Failed to send progress for message on {}: {err}", message.subject);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Processes `messages` until `shutdown` fires or the consumer is deleted, finishing the
+/// messages already in flight. Other pull errors are logged and skipped.
+async fn drive<M, S, P, Fut>(
+    messages: S,
+    shutdown: CancellationToken,
+    max_concurrency: usize,
+    process: P,
+) -> Result<()>
+where
+    S: Stream<Item = core::result::Result<M, MessagesError>>,
+    P: Fn(M) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    messages
+        .take_until(shutdown.cancelled())
+        .filter_map(|message| async move {
+            match message {
+                Ok(message) => Some(Ok(message)),
+                Err(err) if err.kind() == MessagesErrorKind::ConsumerDeleted => {
+                    Some(Err(Error::PullError(err.to_string())))
+                }
+                Err(err) => {
+                    warn!("Failed to pull message, continuing: {err}");
+                    None
+                }
+            }
+        })
+        .try_for_each_concurrent(max_concurrency.max(1), |message| {
+            let processing = process(message);
+            async move {
+                processing.await;
+                Ok(())
+            }
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures::stream;
+
+    use super::*;
+
+    #[test]
+    fn outcome_maps_to_ack_kind() {
+        assert!(matches!(HandlerOutcome::Ok.ack_kind(), AckKind::Ack));
+        assert!(matches!(
+            HandlerOutcome::Retry(Duration::from_secs(5)).ack_kind(),
+            AckKind::Nak(Some(delay)) if delay == Duration::from_secs(5)
+        ));
+        assert!(matches!(
+            HandlerOutcome::Fatal("bad payload".to_string()).ack_kind(),
+            AckKind::Term
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancellation_finishes_in_flight_messages() {
+        let shutdown = CancellationToken::new();
+        let processed = Arc::new(Mutex::new(Vec::new()));
+        let messages = stream::iter([Ok(1), Ok(2)]).chain(stream::pending());
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            drive(messages, shutdown.clone(), 1, |message: u32| {
+                let shutdown = shutdown.clone();
+                let processed = processed.clone();
+                async move {
+                    if message == 2 {
+                        shutdown.cancel();
+                    }
+                    tokio::task::yield_now().await;
+                    processed.lock().unwrap().push(message);
+                }
+            }),
+        )
+        .await
+        .expect("drive should stop when cancelled");
+
+        assert!(result.is_ok());
+        assert_eq!(*processed.lock().unwrap(), [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn deleted_consumer_stops_with_pull_error() {
+        let processed = Arc::new(Mutex::new(Vec::new()));
+        let messages = stream::iter([
+            Err(MessagesError::new(MessagesErrorKind::MissingHeartbeat)),
+            Ok(1),
+            Err(MessagesError::new(MessagesErrorKind::ConsumerDeleted)),
+            Ok(2),
+        ]);
+
+        let result = drive(messages, CancellationToken::new(), 1, |message: u32| {
+            let processed = processed.clone();
+            async move { processed.lock().unwrap().push(message) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(Error::PullError(_))), "{result:?}");
+        assert_eq!(*processed.lock().unwrap(), [1]);
+    }
+}
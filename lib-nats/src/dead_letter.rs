@@ -0,0 +1,506 @@
+use std::time::Duration;
+
+use async_nats::HeaderMap;
+use async_nats::jetstream::consumer::{self, PullConsumer};
+use async_nats::jetstream::stream::{self, RawMessageErrorKind, Stream};
+use async_nats::jetstream::{Context, message::StreamMessage};
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt};
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::error::{Error, Result};
+
+pub const HEADER_DLQ_ORIGINAL_SUBJECT: &str = "X-Dlq-Original-Subject";
+pub const HEADER_DLQ_ORIGINAL_STREAM: &str = "X-Dlq-Original-Stream";
+pub const HEADER_DLQ_ORIGINAL_SEQUENCE: &str = "X-Dlq-Original-Sequence";
+pub const HEADER_DLQ_ORIGINAL_TIMESTAMP: &str = "X-Dlq-Original-Timestamp";
+pub const HEADER_DLQ_CONSUMER: &str = "X-Dlq-Consumer";
+pub const HEADER_DLQ_DELIVERIES: &str = "X-Dlq-Deliveries";
+pub const HEADER_DLQ_ADVISORY_TIMESTAMP: &str = "X-Dlq-Advisory-Timestamp";
+pub const HEADER_DLQ_REPLAYED_FROM_SEQUENCE: &str = "X-Dlq-Replayed-From-Sequence";
+const HEADER_DLQ_PREFIX: &str = "X-Dlq-";
+const HEADER_NATS_MSG_ID: &str = "Nats-Msg-Id";
+// Nats-Expected-Stream, Nats-Expected-Last-Sequence osv. gjelder bare for den opprinnelige publiseringen
+const HEADER_NATS_EXPECTED_PREFIX: &str = "Nats-Expected-";
+
+const DEFAULT_DLQ_STREAM: &str = "dead_letters";
+const DEFAULT_DLQ_SUBJECT_PREFIX: &str = "dlq";
+const DEFAULT_DLQ_NUM_REPLICAS: usize = 3;
+// 30 dager
+const DEFAULT_DLQ_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 3600);
+const REPLAY_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct DeadLetterConfig {
+    pub stream_name: String,
+    /// Dead letters from `<stream>`/`<consumer>` go to `<subject_prefix>.<stream>.<consumer>`.
+    pub subject_prefix: String,
+    pub num_replicas: usize,
+    pub max_age: Duration,
+}
+
+impl Default for DeadLetterConfig {
+    fn default() -> Self {
+        Self {
+            stream_name: DEFAULT_DLQ_STREAM.to_string(),
+            subject_prefix: DEFAULT_DLQ_SUBJECT_PREFIX.to_string(),
+            num_replicas: DEFAULT_DLQ_NUM_REPLICAS,
+            max_age: DEFAULT_DLQ_MAX_AGE,
+        }
+    }
+}
+
+impl DeadLetterConfig {
+    pub fn subject(&self, stream_name: &str, consumer_name: &str) -> String {
+        format!("{}.{}.{}", self.subject_prefix, stream_name, consumer_name)
+    }
+}
+
+/// `io.nats.jetstream.advisory.v1.max_deliver`, published when a message exhausts `max_deliver`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MaxDeliveriesAdvisory {
+    pub stream: String,
+    pub consumer: String,
+    pub stream_seq: u64,
+    pub deliveries: i64,
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub dlq_sequence: u64,
+    pub original_subject: String,
+    pub original_stream: String,
+    pub original_sequence: u64,
+    pub consumer: String,
+    pub deliveries: Option<i64>,
+    pub headers: HeaderMap,
+    pub payload: Bytes,
+}
+
+pub fn max_deliveries_advisory_subject(stream_name: &str, consumer_name: &str) -> String {
+    format!("$JS.EVENT.ADVISORY.CONSUMER.MAX_DELIVERIES.{stream_name}.{consumer_name}")
+}
+
+pub async fn get_or_create_dead_letter_stream(
+    context: &Context,
+    config: &DeadLetterConfig,
+) -> Result<Stream> {
+    let stream = context
+        .get_or_create_stream(stream::Config {
+            name: config.stream_name.clone(),
+            subjects: vec![format!("{}.>", config.subject_prefix)],
+            num_replicas: config.num_replicas,
+            retention: stream::RetentionPolicy::Limits,
+            discard: stream::DiscardPolicy::Old,
+            max_age: config.max_age,
+            duplicate_window: Duration::from_secs(120),
+            ..Default::default()
+        })
+        .await?;
+    Ok(stream)
+}
+
+/// Moves messages that exhaust `max_deliver` on `stream_name`/`consumer_name` to the dead letter
+/// stream until `shutdown` is cancelled.
+///
+/// Advisories are core NATS messages, so messages exhausted while this is not running are not
+/// moved. Only `MAX_DELIVERIES` is followed: a message termed with `AckKind::Term`, such as
+/// [`HandlerOutcome::Fatal`](crate::consumer_runner::HandlerOutcome::Fatal), fires
+/// `MSG_TERMINATED` instead and never reaches the dead letter stream.
+pub async fn forward_dead_letters(
+    context: &Context,
+    config: &DeadLetterConfig,
+    stream_name: &str,
+    consumer_name: &str,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    get_or_create_dead_letter_stream(context, config).await?;
+    let advisories = context
+        .client()
+        .subscribe(max_deliveries_advisory_subject(stream_name, consumer_name))
+        .await
+        .map_err(|err| Error::ConsumerError(err.to_string()))?
+        .take_until(shutdown.cancelled());
+    let mut advisories = std::pin::pin!(advisories);
+
+    while let Some(message) = advisories.next().await {
+        let advisory: MaxDeliveriesAdvisory = match serde_json::from_slice(&message.payload) {
+            Ok(advisory) => advisory,
+            Err(err) => {
+                warn!("Ignoring invalid max deliveries advisory: {err}");
+                continue;
+            }
+        };
+        if let Err(err) = dead_letter(context, config, &advisory).await {
+            warn!(
+                "Failed to dead letter message {} from {}/{}: {err}",
+                advisory.stream_seq, advisory.stream, advisory.consumer
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Copies the message the advisory refers to into the dead letter stream. Returns the sequence
+/// in the dead letter stream. Handling the same advisory twice within the duplicate window
+/// stores the message once.
+pub async fn dead_letter(
+    context: &Context,
+    config: &DeadLetterConfig,
+    advisory: &MaxDeliveriesAdvisory,
+) -> Result<u64> {
+    let original = context
+        .get_stream(&advisory.stream)
+        .await?
+        .get_raw_message(advisory.stream_seq)
+        .await
+        .map_err(|err| match err.kind() {
+            RawMessageErrorKind::NoMessageFound => Error::NotFoundError(format!(
+                "{} in {}: {err}",
+                advisory.stream_seq, advisory.stream
+            )),
+            _ => Error::FetchError(err.to_string()),
+        })?;
+
+    let headers = dead_letter_headers(&original, advisory);
+    publish(
+        context,
+        config.subject(&advisory.stream, &advisory.consumer),
+        headers,
+        original.payload,
+    )
+    .await
+}
+
+/// Republishes one dead letter to its original subject and removes it from the dead letter
+/// stream.
+pub async fn replay_dead_letter(
+    context: &Context,
+    config: &DeadLetterConfig,
+    dlq_sequence: u64,
+) -> Result<()> {
+    let stream = context.get_stream(&config.stream_name).await?;
+    let message = stream
+        .get_raw_message(dlq_sequence)
+        .await
+        .map_err(|err| match err.kind() {
+            RawMessageErrorKind::NoMessageFound => {
+                Error::NotFoundError(format!("{dlq_sequence} in {}: {err}", config.stream_name))
+            }
+            _ => Error::FetchError(err.to_string()),
+        })?;
+    let dead_letter = to_dead_letter(message)?;
+    replay(context, &stream, dead_letter).await
+}
+
+/// Replays every dead letter from `stream_name`/`consumer_name`, oldest first. Returns how many
+/// were replayed.
+pub async fn replay_dead_letters(
+    context: &Context,
+    config: &DeadLetterConfig,
+    stream_name: &str,
+    consumer_name: &str,
+) -> Result<usize> {
+    let dead_letters = list_dead_letters(context, config, stream_name, consumer_name).await?;
+    let stream = context.get_stream(&config.stream_name).await?;
+    let count = dead_letters.len();
+    for dead_letter in dead_letters {
+        replay(context, &stream, dead_letter).await?;
+    }
+    Ok(count)
+}
+
+pub async fn list_dead_letters(
+    context: &Context,
+    config: &DeadLetterConfig,
+    stream_name: &str,
+    consumer_name: &str,
+) -> Result<Vec<DeadLetter>> {
+    let consumer: PullConsumer = context
+        .get_stream(&config.stream_name)
+        .await?
+        .create_consumer(consumer::pull::Config {
+            deliver_policy: consumer::DeliverPolicy::All,
+            ack_policy: consumer::AckPolicy::None,
+            filter_subject: config.subject(stream_name, consumer_name),
+            inactive_threshold: Duration::from_secs(60),
+            ..Default::default()
+        })
+        .await?;
+
+    let pending = consumer.cached_info().num_pending as usize;
+
+    let mut dead_letters = Vec::with_capacity(pending);
+    while dead_letters.len() < pending {
+        let mut messages = consumer
+            .fetch()
+            .max_messages(REPLAY_BATCH_SIZE.min(pending - dead_letters.len()))
+            .messages()
+            .await?;
+        let mut found_messages = false;
+        while let Some(message) = messages
+            .try_next()
+            .await
+            .map_err(|err| Error::FetchError(err.to_string()))?
+        {
+            found_messages = true;
+            let info = message
+                .info()
+                .map_err(|err| Error::FetchError(err.to_string()))?;
+            dead_letters.push(to_dead_letter(StreamMessage {
+                subject: message.subject.clone(),
+                sequence: info.stream_sequence,
+                headers: message.headers.clone().unwrap_or_default(),
+                payload: message.payload.clone(),
+                time: info.published,
+            })?);
+        }
+        if !found_messages {
+            break;
+        }
+    }
+    Ok(dead_letters)
+}
+
+async fn replay(context: &Context, dlq_stream: &Stream, dead_letter: DeadLetter) -> Result<()> {
+    let headers = replay_headers(&dead_letter);
+    publish(
+        context,
+        dead_letter.original_subject,
+        headers,
+        dead_letter.payload,
+    )
+    .await?;
+    dlq_stream
+        .delete_message(dead_letter.dlq_sequence)
+        .await
+        .map_err(|err| Error::PublishError(err.to_string()))?;
+    Ok(())
+}
+
+async fn publish(
+    context: &Context,
+    subject: String,
+    headers: HeaderMap,
+    payload: Bytes,
+) -> Result<u64> {
+    let ack = context
+        .publish_with_headers(subject, headers, payload)
+        .await
+        .map_err(|err| Error::PublishError(err.to_string()))?
+        .await
+        .map_err(|err| Error::PublishError(err.to_string()))?;
+    Ok(ack.sequence)
+}
+
+/// The original headers of a dead letter, without the `X-Dlq-*` headers and the ones that only
+/// applied to the original publish.
+fn replay_headers(dead_letter: &DeadLetter) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, values) in dead_letter.headers.iter() {
+        let name_str: &str = name.as_ref();
+        if name_str.starts_with(HEADER_DLQ_PREFIX) || is_publish_header(name_str) {
+            continue;
+        }
+        for value in values {
+            headers.append(name.clone(), value.clone());
+        }
+    }
+    headers.insert(
+        HEADER_DLQ_REPLAYED_FROM_SEQUENCE,
+        dead_letter.dlq_sequence.to_string(),
+    );
+    headers
+}
+
+fn is_publish_header(name: &str) -> bool {
+    name == HEADER_NATS_MSG_ID || name.starts_with(HEADER_NATS_EXPECTED_PREFIX)
+}
+
+fn dead_letter_headers(original: &StreamMessage, advisory: &MaxDeliveriesAdvisory) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, values) in original.headers.iter() {
+        let name_str: &str = name.as_ref();
+        if is_publish_header(name_str) {
+            continue;
+        }
+        for value in values {
+            headers.append(name.clone(), value.clone());
+        }
+    }
+    headers.insert(
+        HEADER_NATS_MSG_ID,
+        format!(
+            "{}-{}-{}",
+            advisory.stream, advisory.consumer, advisory.stream_seq
+        ),
+    );
+    headers.insert(HEADER_DLQ_ORIGINAL_SUBJECT, original.subject.to_string());
+    headers.insert(HEADER_DLQ_ORIGINAL_STREAM, advisory.stream.as_str());
+    headers.insert(
+        HEADER_DLQ_ORIGINAL_SEQUENCE,
+        advisory.stream_seq.to_string(),
+    );
+    headers.insert(HEADER_DLQ_ORIGINAL_TIMESTAMP, original.time.to_string());
+    headers.insert(HEADER_DLQ_CONSUMER, advisory.consumer.as_str());
+    headers.insert(HEADER_DLQ_DELIVERIES, advisory.deliveries.to_string());
+    if let Some(timestamp) = &advisory.timestamp {
+        headers.insert(HEADER_DLQ_ADVISORY_TIMESTAMP, timestamp.as_str());
+    }
+    headers
+}
+
+fn to_dead_letter(message: StreamMessage) -> Result<DeadLetter> {
+    let header = |name: &str| {
+        message
+            .headers
+            .get(name)
+            .map(|value| value.as_str().to_string())
+    };
+    let missing = |name: &str| {
+        Error::FetchError(format!(
+            "Dead letter {} is missing header {name}",
+            message.sequence
+        ))
+    };
+
+    Ok(DeadLetter {
+        dlq_sequence: message.sequence,
+        original_subject: header(HEADER_DLQ_ORIGINAL_SUBJECT)
+            .ok_or_else(|| missing(HEADER_DLQ_ORIGINAL_SUBJECT))?,
+        original_stream: header(HEADER_DLQ_ORIGINAL_STREAM)
+            .ok_or_else(|| missing(HEADER_DLQ_ORIGINAL_STREAM))?,
+        original_sequence: header(HEADER_DLQ_ORIGINAL_SEQUENCE)
+            .and_then(|sequence| sequence.parse().ok())
+            .ok_or_else(|| missing(HEADER_DLQ_ORIGINAL_SEQUENCE))?,
+        consumer: header(HEADER_DLQ_CONSUMER).ok_or_else(|| missing(HEADER_DLQ_CONSUMER))?,
+        deliveries: header(HEADER_DLQ_DELIVERIES).and_then(|deliveries| deliveries.parse().ok()),
+        headers: message.headers,
+        payload: message.payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    fn advisory() -> MaxDeliveriesAdvisory {
+        MaxDeliveriesAdvisory {
+            stream: "orders".to_string(),
+            consumer: "worker".to_string(),
+            stream_seq: 42,
+            deliveries: 5,
+            timestamp: Some("2025-03-01T12:00:00Z".to_string()),
+        }
+    }
+
+    fn original() -> StreamMessage {
+        let mut headers = HeaderMap::new();
+        headers.insert("Trace-Id", "abc");
+        headers.insert(HEADER_NATS_MSG_ID, "order-1");
+        headers.insert("Nats-Expected-Stream", "orders");
+        headers.insert("Nats-Expected-Last-Sequence", "41");
+        StreamMessage {
+            subject: "orders.created".into(),
+            sequence: 42,
+            headers,
+            payload: Bytes::from_static(b"{}"),
+            time: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+        headers.get(name).map(|value| value.as_str())
+    }
+
+    #[test]
+    fn dead_letter_headers_drop_publish_headers() {
+        let headers = dead_letter_headers(&original(), &advisory());
+
+        assert_eq!(value(&headers, "Trace-Id"), Some("abc"));
+        assert_eq!(
+            value(&headers, HEADER_NATS_MSG_ID),
+            Some("orders-worker-42")
+        );
+        assert_eq!(value(&headers, "Nats-Expected-Stream"), None);
+        assert_eq!(value(&headers, "Nats-Expected-Last-Sequence"), None);
+        assert_eq!(
+            value(&headers, HEADER_DLQ_ORIGINAL_SUBJECT),
+            Some("orders.created")
+        );
+        assert_eq!(value(&headers, HEADER_DLQ_DELIVERIES), Some("5"));
+        assert_eq!(
+            value(&headers, HEADER_DLQ_ADVISORY_TIMESTAMP),
+            Some("2025-03-01T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn dead_letter_round_trip_restores_original_headers() {
+        let original = original();
+        let stored = StreamMessage {
+            subject: "dlq.orders.worker".into(),
+            sequence: 7,
+            headers: dead_letter_headers(&original, &advisory()),
+            payload: original.payload.clone(),
+            time: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        let dead_letter = to_dead_letter(stored).unwrap();
+        assert_eq!(dead_letter.dlq_sequence, 7);
+        assert_eq!(dead_letter.original_subject, "orders.created");
+        assert_eq!(dead_letter.original_stream, "orders");
+        assert_eq!(dead_letter.original_sequence, 42);
+        assert_eq!(dead_letter.consumer, "worker");
+        assert_eq!(dead_letter.deliveries, Some(5));
+        assert_eq!(dead_letter.payload, original.payload);
+
+        let headers = replay_headers(&dead_letter);
+        let mut names: Vec<&str> = headers.iter().map(|(name, _)| name.as_ref()).collect();
+        names.sort();
+        assert_eq!(names, ["Trace-Id", HEADER_DLQ_REPLAYED_FROM_SEQUENCE]);
+        assert_eq!(
+            value(&headers, HEADER_DLQ_REPLAYED_FROM_SEQUENCE),
+            Some("7")
+        );
+    }
+
+    #[test]
+    fn replay_headers_drop_expected_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Nats-Expected-Stream", "orders");
+        headers.insert(HEADER_NATS_MSG_ID, "orders-worker-42");
+        headers.insert(HEADER_DLQ_CONSUMER, "worker");
+        headers.insert("Trace-Id", "abc");
+        let dead_letter = DeadLetter {
+            dlq_sequence: 3,
+            original_subject: "orders.created".to_string(),
+            original_stream: "orders".to_string(),
+            original_sequence: 42,
+            consumer: "worker".to_string(),
+            deliveries: None,
+            headers,
+            payload: Bytes::new(),
+        };
+
+        let headers = replay_headers(&dead_letter);
+
+        assert_eq!(value(&headers, "Trace-Id"), Some("abc"));
+        assert_eq!(value(&headers, "Nats-Expected-Stream"), None);
+        assert_eq!(value(&headers, HEADER_NATS_MSG_ID), None);
+        assert_eq!(value(&headers, HEADER_DLQ_CONSUMER), None);
+    }
+
+    #[test]
+    fn to_dead_letter_requires_dlq_headers() {
+        let mut message = original();
+        message.headers = HeaderMap::new();
+
+        assert!(matches!(to_dead_letter(message), Err(Error::FetchError(_))));
+    }
+}
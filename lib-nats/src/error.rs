@@ -21,6 +21,7 @@ pub enum Error {
     FetchError(String),
     NotFoundError(String),
     PublishError(String),
+    PullError(String),
 }
 
 impl std::fmt::Display for Error {
@@ -33,6 +34,7 @@ impl std::fmt::Display for Error {
             Error::FetchError(e) => write!(f, "Failed to fetch message from NATS: {}", e),
             Error::NotFoundError(e) => write!(f, "Message not found {}", e),
             Error::PublishError(e) => write!(f, "Failed to publish: {}", e),
+            Error::PullError(e) => write!(f, "Failed to pull messages from NATS consumer: {}", e),
         }
     }
 }
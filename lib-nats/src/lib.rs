@@ -1,8 +1,12 @@
+pub mod bekymringsmeldinger;
 pub mod chunked_upload;
 pub mod config;
 pub mod consumer;
+pub mod consumer_runner;
+pub mod dead_letter;
 pub mod error;
 pub mod object_store;
+pub mod topology;
 
 pub use async_nats::Client;
 pub use async_nats::HeaderMap;
@@ -11,3 +15,4 @@ pub use async_nats::jetstream;
 pub use async_nats::jetstream::AckKind;
 pub use async_nats::jetstream::consumer::PullConsumer;
 pub use async_nats::jetstream::{Context, stream};
+pub use tokio_util::sync::CancellationToken;
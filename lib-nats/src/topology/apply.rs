@@ -0,0 +1,665 @@
+use std::fmt::{self, Debug, Display};
+
+use async_nats::jetstream::consumer;
+use async_nats::jetstream::context::{ConsumerInfoErrorKind, GetStreamErrorKind};
+use async_nats::jetstream::{Context, ErrorCode, stream};
+
+use crate::error::{Error, Result};
+use crate::topology::spec::{ConsumerSpec, KeyValueSpec, ObjectStoreSpec, StreamSpec, Topology};
+
+/// What `apply` does with existing resources whose config differs from the spec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DriftPolicy {
+    /// Report the differences and leave the resource as it is.
+    #[default]
+    Report,
+    /// Update the resource to match the spec.
+    Update,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Stream,
+    Consumer,
+    KeyValue,
+    ObjectStore,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub field: &'static str,
+    pub actual: String,
+    pub expected: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceStatus {
+    Created,
+    InSync,
+    Drifted(Vec<Difference>),
+    Updated(Vec<Difference>),
+    /// The server rejected the update, for example because the field cannot be changed.
+    UpdateFailed {
+        differences: Vec<Difference>,
+        error: String,
+    },
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceReport {
+    pub kind: ResourceKind,
+    /// `<stream>/<consumer>` for consumers.
+    pub name: String,
+    pub status: ResourceStatus,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApplyReport {
+    pub resources: Vec<ResourceReport>,
+}
+
+impl ApplyReport {
+    /// Resources whose config still differs from the spec after `apply`.
+    pub fn drifted(&self) -> impl Iterator<Item = &ResourceReport> {
+        self.resources.iter().filter(|resource| {
+            matches!(
+                resource.status,
+                ResourceStatus::Drifted(_) | ResourceStatus::UpdateFailed { .. }
+            )
+        })
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &ResourceReport> {
+        self.resources
+            .iter()
+            .filter(|resource| matches!(resource.status, ResourceStatus::Failed(_)))
+    }
+
+    /// True when every resource exists and matches the spec.
+    pub fn is_in_sync(&self) -> bool {
+        self.drifted().next().is_none() && self.failed().next().is_none()
+    }
+
+    fn push(&mut self, kind: ResourceKind, name: String, status: Result<ResourceStatus>) {
+        let status = status.unwrap_or_else(|err| ResourceStatus::Failed(err.to_string()));
+        self.resources.push(ResourceReport { kind, name, status });
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.actual, self.expected)
+    }
+}
+
+impl Display for ResourceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}: ", self.kind, self.name)?;
+        let differences = match &self.status {
+            ResourceStatus::Created => return write!(f, "created"),
+            ResourceStatus::InSync => return write!(f, "in sync"),
+            ResourceStatus::Failed(error) => return write!(f, "failed: {error}"),
+            ResourceStatus::Drifted(differences) => {
+                write!(f, "drifted")?;
+                differences
+            }
+            ResourceStatus::Updated(differences) => {
+                write!(f, "updated")?;
+                differences
+            }
+            ResourceStatus::UpdateFailed { differences, error } => {
+                write!(f, "update failed ({error})")?;
+                differences
+            }
+        };
+        for difference in differences {
+            write!(f, "; {difference}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates every resource in `topology` that does not exist, and compares the rest with the spec.
+///
+/// A failing resource does not stop the others; its error is in the report. Only an invalid
+/// topology makes `apply` itself fail.
+pub async fn apply(
+    context: &Context,
+    topology: &Topology,
+    drift_policy: DriftPolicy,
+) -> Result<ApplyReport> {
+    topology.validate()?;
+    let mut report = ApplyReport::default();
+
+    for spec in &topology.streams {
+        let status = apply_stream(context, spec, drift_policy).await;
+        report.push(ResourceKind::Stream, spec.name.clone(), status);
+        for consumer in &spec.consumers {
+            let status = apply_consumer(context, &spec.name, consumer, drift_policy).await;
+            report.push(
+                ResourceKind::Consumer,
+                format!("{}/{}", spec.name, consumer.name),
+                status,
+            );
+        }
+    }
+    for spec in &topology.key_value_buckets {
+        let status = apply_key_value(context, spec, drift_policy).await;
+        report.push(ResourceKind::KeyValue, spec.bucket.clone(), status);
+    }
+    for spec in &topology.object_stores {
+        let status = apply_object_store(context, spec, drift_policy).await;
+        report.push(ResourceKind::ObjectStore, spec.bucket.clone(), status);
+    }
+    Ok(report)
+}
+
+async fn apply_stream(
+    context: &Context,
+    spec: &StreamSpec,
+    drift_policy: DriftPolicy,
+) -> Result<ResourceStatus> {
+    match existing_stream_config(context, &spec.name).await? {
+        None => {
+            context.create_stream(spec.to_config()).await?;
+            Ok(ResourceStatus::Created)
+        }
+        Some(actual) => {
+            reconcile_stream(context, actual, |config| spec.overlay(config), drift_policy).await
+        }
+    }
+}
+
+async fn apply_key_value(
+    context: &Context,
+    spec: &KeyValueSpec,
+    drift_policy: DriftPolicy,
+) -> Result<ResourceStatus> {
+    match existing_stream_config(context, &spec.stream_name()).await? {
+        None => {
+            context.create_key_value(spec.to_config()).await?;
+            Ok(ResourceStatus::Created)
+        }
+        Some(actual) => {
+            reconcile_stream(context, actual, |config| spec.overlay(config), drift_policy).await
+        }
+    }
+}
+
+async fn apply_object_store(
+    context: &Context,
+    spec: &ObjectStoreSpec,
+    drift_policy: DriftPolicy,
+) -> Result<ResourceStatus> {
+    match existing_stream_config(context, &spec.stream_name()).await? {
+        None => {
+            context
+                .create_object_store(spec.to_config())
+                .await
+                .map_err(|err| Error::ConfigError(err.to_string()))?;
+            Ok(ResourceStatus::Created)
+        }
+        Some(actual) => {
+            reconcile_stream(context, actual, |config| spec.overlay(config), drift_policy).await
+        }
+    }
+}
+
+async fn apply_consumer(
+    context: &Context,
+    stream_name: &str,
+    spec: &ConsumerSpec,
+    drift_policy: DriftPolicy,
+) -> Result<ResourceStatus> {
+    let stream = context.get_stream(stream_name).await?;
+    let actual = match stream.consumer_info(&spec.name).await {
+        Ok(info) => info.config,
+        Err(err) if err.kind() == ConsumerInfoErrorKind::NotFound => {
+            stream.create_consumer(spec.to_config()).await?;
+            return Ok(ResourceStatus::Created);
+        }
+        Err(err) => return Err(Error::ConsumerError(err.to_string())),
+    };
+
+    let mut expected = actual.clone();
+    spec.overlay(&mut expected);
+    let differences = consumer_differences(&actual, &expected);
+    resolve(differences, drift_policy, || async {
+        stream
+            .update_consumer::<consumer::Config>(expected)
+            .await
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    })
+    .await
+}
+
+async fn existing_stream_config(context: &Context, name: &str) -> Result<Option<stream::Config>> {
+    match context.get_stream(name).await {
+        Ok(stream) => Ok(Some(stream.cached_info().config.clone())),
+        Err(err) => match err.kind() {
+            GetStreamErrorKind::JetStream(jetstream_err)
+                if jetstream_err.error_code() == ErrorCode::STREAM_NOT_FOUND =>
+            {
+                Ok(None)
+            }
+            _ => Err(err.into()),
+        },
+    }
+}
+
+async fn reconcile_stream(
+    context: &Context,
+    actual: stream::Config,
+    overlay: impl FnOnce(&mut stream::Config),
+    drift_policy: DriftPolicy,
+) -> Result<ResourceStatus> {
+    let mut expected = actual.clone();
+    overlay(&mut expected);
+    let differences = stream_differences(&actual, &expected);
+    resolve(differences, drift_policy, || async {
+        context
+            .update_stream(expected)
+            .await
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    })
+    .await
+}
+
+async fn resolve<F, Fut>(
+    differences: Vec<Difference>,
+    drift_policy: DriftPolicy,
+    update: F,
+) -> Result<ResourceStatus>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = std::result::Result<(), String>>,
+{
+    if differences.is_empty() {
+        return Ok(ResourceStatus::InSync);
+    }
+    match drift_policy {
+        DriftPolicy::Report => Ok(ResourceStatus::Drifted(differences)),
+        DriftPolicy::Update => match update().await {
+            Ok(()) => Ok(ResourceStatus::Updated(differences)),
+            Err(error) => Ok(ResourceStatus::UpdateFailed { differences, error }),
+        },
+    }
+}
+
+pub(crate) fn stream_differences(
+    actual: &stream::Config,
+    expected: &stream::Config,
+) -> Vec<Difference> {
+    let sources = |config: &stream::Config| {
+        let mut sources: Vec<_> = config
+            .sources
+            .iter()
+            .flatten()
+            .map(|source| {
+                let mut transforms: Vec<(String, String)> = source
+                    .subject_transforms
+                    .iter()
+                    .map(|transform| (transform.source.clone(), transform.destination.clone()))
+                    .collect();
+                transforms.sort();
+                (
+                    source.name.clone(),
+                    source.filter_subject.clone(),
+                    transforms,
+                )
+            })
+            .collect();
+        sources.sort();
+        sources
+    };
+    let subjects = |config: &stream::Config| {
+        let mut subjects = config.subjects.clone();
+        subjects.sort();
+        subjects
+    };
+
+    let mut differences = Vec::new();
+    compare(
+        &mut differences,
+        "subjects",
+        subjects(actual),
+        subjects(expected),
+    );
+    compare(
+        &mut differences,
+        "sources",
+        sources(actual),
+        sources(expected),
+    );
+    compare(
+        &mut differences,
+        "description",
+        &actual.description,
+        &expected.description,
+    );
+    compare(
+        &mut differences,
+        "num_replicas",
+        actual.num_replicas,
+        expected.num_replicas,
+    );
+    compare(
+        &mut differences,
+        "retention",
+        actual.retention,
+        expected.retention,
+    );
+    compare(
+        &mut differences,
+        "discard",
+        actual.discard,
+        expected.discard,
+    );
+    compare(
+        &mut differences,
+        "max_messages",
+        actual.max_messages,
+        expected.max_messages,
+    );
+    compare(
+        &mut differences,
+        "max_messages_per_subject",
+        actual.max_messages_per_subject,
+        expected.max_messages_per_subject,
+    );
+    compare(
+        &mut differences,
+        "max_bytes",
+        actual.max_bytes,
+        expected.max_bytes,
+    );
+    compare(
+        &mut differences,
+        "max_age",
+        actual.max_age,
+        expected.max_age,
+    );
+    compare(
+        &mut differences,
+        "duplicate_window",
+        actual.duplicate_window,
+        expected.duplicate_window,
+    );
+    compare(
+        &mut differences,
+        "allow_direct",
+        actual.allow_direct,
+        expected.allow_direct,
+    );
+    differences
+}
+
+pub(crate) fn consumer_differences(
+    actual: &consumer::Config,
+    expected: &consumer::Config,
+) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    compare(
+        &mut differences,
+        "filter_subject",
+        &actual.filter_subject,
+        &expected.filter_subject,
+    );
+    compare(
+        &mut differences,
+        "description",
+        &actual.description,
+        &expected.description,
+    );
+    compare(
+        &mut differences,
+        "deliver_policy",
+        actual.deliver_policy,
+        expected.deliver_policy,
+    );
+    compare(
+        &mut differences,
+        "ack_policy",
+        actual.ack_policy,
+        expected.ack_policy,
+    );
+    compare(
+        &mut differences,
+        "ack_wait",
+        actual.ack_wait,
+        expected.ack_wait,
+    );
+    compare(
+        &mut differences,
+        "max_deliver",
+        actual.max_deliver,
+        expected.max_deliver,
+    );
+    compare(
+        &mut differences,
+        "backoff",
+        &actual.backoff,
+        &expected.backoff,
+    );
+    compare(
+        &mut differences,
+        "max_ack_pending",
+        actual.max_ack_pending,
+        expected.max_ack_pending,
+    );
+    differences
+}
+
+fn compare<T: PartialEq + Debug>(
+    differences: &mut Vec<Difference>,
+    field: &'static str,
+    actual: T,
+    expected: T,
+) {
+    if actual != expected {
+        differences.push(Difference {
+            field,
+            actual: format!("{actual:?}"),
+            expected: format!("{expected:?}"),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::topology::spec::SourceSpec;
+
+    fn fields(differences: &[Difference]) -> Vec<&'static str> {
+        differences
+            .iter()
+            .map(|difference| difference.field)
+            .collect()
+    }
+
+    #[test]
+    fn stream_in_sync_ignores_subject_and_source_order() {
+        let actual = stream::Config {
+            subjects: vec!["b".to_string(), "a".to_string()],
+            sources: Some(vec![
+                SourceSpec::new("y", None).to_source(),
+                SourceSpec::new("x", Some("x.>")).to_source(),
+            ]),
+            ..Default::default()
+        };
+        let expected = stream::Config {
+            subjects: vec!["a".to_string(), "b".to_string()],
+            sources: Some(vec![
+                SourceSpec::new("x", Some("x.>")).to_source(),
+                SourceSpec::new("y", None).to_source(),
+            ]),
+            ..Default::default()
+        };
+
+        assert!(stream_differences(&actual, &expected).is_empty());
+    }
+
+    #[test]
+    fn stream_drift_lists_changed_fields() {
+        let actual = stream::Config {
+            name: "saker".to_string(),
+            subjects: vec!["saker.>".to_string()],
+            max_age: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let spec = StreamSpec {
+            name: "saker".to_string(),
+            subjects: vec!["saker.>".to_string()],
+            sources: vec![SourceSpec::new("gamle_saker", None)],
+            num_replicas: 3,
+            max_age_secs: Some(3600),
+            ..Default::default()
+        };
+        let mut expected = actual.clone();
+        spec.overlay(&mut expected);
+
+        let differences = stream_differences(&actual, &expected);
+
+        assert_eq!(fields(&differences), ["sources", "num_replicas", "max_age"]);
+        assert_eq!(differences[2].to_string(), "max_age: 60s -> 3600s");
+    }
+
+    #[test]
+    fn stream_drift_on_changed_filter_subjects() {
+        let actual = stream::Config {
+            name: "alle".to_string(),
+            sources: Some(vec![
+                SourceSpec::with_filter_subjects("saker", &["saker.v1.>"]).to_source(),
+            ]),
+            num_replicas: 3,
+            ..Default::default()
+        };
+        let spec = StreamSpec {
+            name: "alle".to_string(),
+            sources: vec![SourceSpec::with_filter_subjects(
+                "saker",
+                &["saker.v1.>", "saker.v2.>"],
+            )],
+            num_replicas: 3,
+            ..Default::default()
+        };
+        let mut expected = actual.clone();
+        spec.overlay(&mut expected);
+
+        assert_eq!(fields(&stream_differences(&actual, &expected)), ["sources"]);
+
+        let reordered = stream::Config {
+            sources: Some(vec![
+                SourceSpec::with_filter_subjects("saker", &["saker.v2.>", "saker.v1.>"])
+                    .to_source(),
+            ]),
+            ..expected.clone()
+        };
+        assert!(stream_differences(&reordered, &expected).is_empty());
+    }
+
+    #[test]
+    fn consumer_drift_lists_changed_fields_only() {
+        let spec = ConsumerSpec {
+            name: "arkiv".to_string(),
+            filter_subject: "saker.>".to_string(),
+            max_deliver: Some(5),
+            ..Default::default()
+        };
+        let actual = spec.to_config();
+        let mut expected = actual.clone();
+        spec.overlay(&mut expected);
+        assert!(consumer_differences(&actual, &expected).is_empty());
+
+        let changed = ConsumerSpec {
+            max_deliver: Some(10),
+            backoff_secs: Some(vec![5]),
+            ..spec
+        };
+        changed.overlay(&mut expected);
+
+        assert_eq!(
+            fields(&consumer_differences(&actual, &expected)),
+            ["max_deliver", "backoff"]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_follows_drift_policy() {
+        let differences = vec![Difference {
+            field: "max_age",
+            actual: "60s".to_string(),
+            expected: "3600s".to_string(),
+        }];
+
+        let in_sync = resolve(Vec::new(), DriftPolicy::Update, || async {
+            panic!("nothing to update")
+        })
+        .await
+        .unwrap();
+        assert_eq!(in_sync, ResourceStatus::InSync);
+
+        let drifted = resolve(differences.clone(), DriftPolicy::Report, || async {
+            panic!("report must not update")
+        })
+        .await
+        .unwrap();
+        assert_eq!(drifted, ResourceStatus::Drifted(differences.clone()));
+
+        let updated = resolve(differences.clone(), DriftPolicy::Update, || async {
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(updated, ResourceStatus::Updated(differences.clone()));
+
+        let failed = resolve(differences.clone(), DriftPolicy::Update, || async {
+            Err("retention cannot be changed".to_string())
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            failed,
+            ResourceStatus::UpdateFailed {
+                differences,
+                error: "retention cannot be changed".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn report_groups_drifted_and_failed_resources() {
+        let mut report = ApplyReport::default();
+        report.push(
+            ResourceKind::Stream,
+            "saker".to_string(),
+            Ok(ResourceStatus::InSync),
+        );
+        assert!(report.is_in_sync());
+
+        report.push(
+            ResourceKind::Consumer,
+            "saker/arkiv".to_string(),
+            Ok(ResourceStatus::Drifted(Vec::new())),
+        );
+        report.push(
+            ResourceKind::KeyValue,
+            "status".to_string(),
+            Err(Error::ConfigError("timeout".to_string())),
+        );
+
+        assert!(!report.is_in_sync());
+        let drifted: Vec<_> = report.drifted().map(|r| r.name.as_str()).collect();
+        assert_eq!(drifted, ["saker/arkiv"]);
+        let failed: Vec<_> = report.failed().map(ToString::to_string).collect();
+        assert_eq!(
+            failed,
+            ["KeyValue status: failed: Failed to configure NATS client: timeout"]
+        );
+    }
+}
@@ -0,0 +1,10 @@
+pub mod apply;
+pub mod spec;
+
+pub use apply::{
+    ApplyReport, Difference, DriftPolicy, ResourceKind, ResourceReport, ResourceStatus, apply,
+};
+pub use spec::{
+    AckPolicy, ConsumerSpec, DeliverPolicy, Discard, KeyValueSpec, ObjectStoreSpec, Retention,
+    SourceSpec, StreamSpec, Topology,
+};
@@ -0,0 +1,782 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use async_nats::jetstream::{consumer, kv, object_store, stream};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+const DEFAULT_NUM_REPLICAS: usize = 3;
+const DEFAULT_KV_HISTORY: i64 = 1;
+
+/// Streams, consumers, KV buckets and object stores a service expects to exist.
+///
+/// Fields that are `None` are left as they are on existing resources and use the server
+/// default on new ones.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Topology {
+    pub streams: Vec<StreamSpec>,
+    pub key_value_buckets: Vec<KeyValueSpec>,
+    pub object_stores: Vec<ObjectStoreSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StreamSpec {
+    pub name: String,
+    pub description: Option<String>,
+    pub subjects: Vec<String>,
+    pub sources: Vec<SourceSpec>,
+    pub num_replicas: usize,
+    pub retention: Retention,
+    pub discard: Discard,
+    pub max_messages: Option<i64>,
+    pub max_messages_per_subject: Option<i64>,
+    pub max_bytes: Option<i64>,
+    pub max_age_secs: Option<u64>,
+    pub duplicate_window_secs: Option<u64>,
+    pub allow_direct: bool,
+    pub consumers: Vec<ConsumerSpec>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceSpec {
+    pub name: String,
+    pub filter_subject: Option<String>,
+    /// Several filters on the same source stream, sent as subject transforms that keep the
+    /// subject as it is. Cannot be combined with `filter_subject`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filter_subjects: Vec<String>,
+}
+
+/// A durable pull consumer. `name` is used as both consumer name and durable name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsumerSpec {
+    pub name: String,
+    pub description: Option<String>,
+    /// Empty means every subject in the stream.
+    pub filter_subject: String,
+    pub deliver_policy: DeliverPolicy,
+    pub ack_policy: AckPolicy,
+    pub ack_wait_secs: Option<u64>,
+    pub max_deliver: Option<i64>,
+    pub backoff_secs: Option<Vec<u64>>,
+    pub max_ack_pending: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyValueSpec {
+    pub bucket: String,
+    pub description: Option<String>,
+    pub history: i64,
+    pub max_age_secs: Option<u64>,
+    pub max_bytes: Option<i64>,
+    pub num_replicas: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObjectStoreSpec {
+    pub bucket: String,
+    pub description: Option<String>,
+    pub max_age_secs: Option<u64>,
+    pub max_bytes: Option<i64>,
+    pub num_replicas: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Retention {
+    #[default]
+    Limits,
+    Interest,
+    WorkQueue,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Discard {
+    #[default]
+    Old,
+    New,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliverPolicy {
+    #[default]
+    All,
+    New,
+    Last,
+    LastPerSubject,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AckPolicy {
+    #[default]
+    Explicit,
+    All,
+    None,
+}
+
+impl Default for StreamSpec {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            description: None,
+            subjects: Vec::new(),
+            sources: Vec::new(),
+            num_replicas: DEFAULT_NUM_REPLICAS,
+            retention: Retention::default(),
+            discard: Discard::default(),
+            max_messages: None,
+            max_messages_per_subject: None,
+            max_bytes: None,
+            max_age_secs: None,
+            duplicate_window_secs: None,
+            allow_direct: false,
+            consumers: Vec::new(),
+        }
+    }
+}
+
+impl Default for KeyValueSpec {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            description: None,
+            history: DEFAULT_KV_HISTORY,
+            max_age_secs: None,
+            max_bytes: None,
+            num_replicas: DEFAULT_NUM_REPLICAS,
+        }
+    }
+}
+
+impl Default for ObjectStoreSpec {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            description: None,
+            max_age_secs: None,
+            max_bytes: None,
+            num_replicas: DEFAULT_NUM_REPLICAS,
+        }
+    }
+}
+
+impl Topology {
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(source: &str) -> Result<Self> {
+        let topology: Topology =
+            toml::from_str(source).map_err(|err| Error::ConfigError(err.to_string()))?;
+        topology.validate()?;
+        Ok(topology)
+    }
+
+    /// Checks that every resource has a valid name and that no name is used twice.
+    pub fn validate(&self) -> Result<()> {
+        let mut streams = HashSet::new();
+        for spec in &self.streams {
+            validate_name("stream", &spec.name)?;
+            if !streams.insert(spec.name.as_str()) {
+                return Err(duplicate("stream", &spec.name));
+            }
+            if spec.subjects.is_empty() && spec.sources.is_empty() {
+                return Err(Error::ConfigError(format!(
+                    "Stream {} needs at least one subject or source",
+                    spec.name
+                )));
+            }
+            if let Some(source) = spec.sources.iter().find(|source| {
+                source.filter_subject.is_some() && !source.filter_subjects.is_empty()
+            }) {
+                return Err(Error::ConfigError(format!(
+                    "Source {} of stream {} sets both filter_subject and filter_subjects",
+                    source.name, spec.name
+                )));
+            }
+            let mut consumers = HashSet::new();
+            for consumer in &spec.consumers {
+                validate_name("consumer", &consumer.name)?;
+                if !consumers.insert(consumer.name.as_str()) {
+                    return Err(duplicate(
+                        "consumer",
+                        &format!("{}/{}", spec.name, consumer.name),
+                    ));
+                }
+            }
+        }
+
+        let mut buckets = HashSet::new();
+        for bucket in &self.key_value_buckets {
+            validate_name("key value bucket", &bucket.bucket)?;
+            if !buckets.insert(bucket.bucket.as_str()) {
+                return Err(duplicate("key value bucket", &bucket.bucket));
+            }
+        }
+        let mut buckets = HashSet::new();
+        for store in &self.object_stores {
+            validate_name("object store", &store.bucket)?;
+            if !buckets.insert(store.bucket.as_str()) {
+                return Err(duplicate("object store", &store.bucket));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::ConfigError(format!("A {kind} is missing a name")));
+    }
+    if name
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '.' | '*' | '>' | '/' | '\\'))
+    {
+        return Err(Error::ConfigError(format!(
+            "Invalid {kind} name {name:?}: must not contain whitespace, '.', '*', '>', '/' or '\\'"
+        )));
+    }
+    Ok(())
+}
+
+fn duplicate(kind: &str, name: &str) -> Error {
+    Error::ConfigError(format!("The {kind} {name} is declared more than once"))
+}
+
+impl StreamSpec {
+    pub fn to_config(&self) -> stream::Config {
+        let mut config = stream::Config {
+            name: self.name.clone(),
+            ..Default::default()
+        };
+        self.overlay(&mut config);
+        config
+    }
+
+    /// Sets the fields this spec controls on `config` and leaves the rest.
+    pub fn overlay(&self, config: &mut stream::Config) {
+        config.subjects = self.subjects.clone();
+        let existing = config.sources.take().unwrap_or_default();
+        let sources: Vec<stream::Source> = self
+            .sources
+            .iter()
+            .map(|source| {
+                existing
+                    .iter()
+                    .find(|existing| source.matches(existing))
+                    .cloned()
+                    .unwrap_or_else(|| source.to_source())
+            })
+            .collect();
+        config.sources = (!sources.is_empty()).then_some(sources);
+        config.num_replicas = self.num_replicas;
+        config.retention = self.retention.into();
+        config.discard = self.discard.into();
+        config.allow_direct = self.allow_direct;
+        if let Some(description) = &self.description {
+            config.description = Some(description.clone());
+        }
+        if let Some(max_messages) = self.max_messages {
+            config.max_messages = max_messages;
+        }
+        if let Some(max_messages_per_subject) = self.max_messages_per_subject {
+            config.max_messages_per_subject = max_messages_per_subject;
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            config.max_bytes = max_bytes;
+        }
+        if let Some(max_age_secs) = self.max_age_secs {
+            config.max_age = Duration::from_secs(max_age_secs);
+        }
+        if let Some(duplicate_window_secs) = self.duplicate_window_secs {
+            config.duplicate_window = Duration::from_secs(duplicate_window_secs);
+        }
+    }
+}
+
+impl SourceSpec {
+    pub fn new(name: &str, filter_subject: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            filter_subject: filter_subject.map(str::to_string),
+            filter_subjects: Vec::new(),
+        }
+    }
+
+    pub fn with_filter_subjects(name: &str, filter_subjects: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            filter_subject: None,
+            filter_subjects: filter_subjects.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn to_source(&self) -> stream::Source {
+        stream::Source {
+            name: self.name.clone(),
+            filter_subject: self.filter_subject.clone(),
+            subject_transforms: self.subject_transforms(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn matches(&self, source: &stream::Source) -> bool {
+        self.name == source.name
+            && self.filter_subject == source.filter_subject
+            && self.subject_transforms() == source.subject_transforms
+    }
+
+    fn subject_transforms(&self) -> Vec<stream::SubjectTransform> {
+        self.filter_subjects
+            .iter()
+            .map(|subject| stream::SubjectTransform {
+                source: subject.clone(),
+                destination: subject.clone(),
+            })
+            .collect()
+    }
+}
+
+impl ConsumerSpec {
+    pub fn to_config(&self) -> consumer::Config {
+        let mut config = consumer::Config {
+            name: Some(self.name.clone()),
+            durable_name: Some(self.name.clone()),
+            ..Default::default()
+        };
+        self.overlay(&mut config);
+        config
+    }
+
+    /// Sets the fields this spec controls on `config` and leaves the rest.
+    pub fn overlay(&self, config: &mut consumer::Config) {
+        config.filter_subject = self.filter_subject.clone();
+        config.deliver_policy = self.deliver_policy.into();
+        config.ack_policy = self.ack_policy.into();
+        if let Some(description) = &self.description {
+            config.description = Some(description.clone());
+        }
+        if let Some(ack_wait_secs) = self.ack_wait_secs {
+            config.ack_wait = Duration::from_secs(ack_wait_secs);
+        }
+        if let Some(max_deliver) = self.max_deliver {
+            config.max_deliver = max_deliver;
+        }
+        if let Some(backoff_secs) = &self.backoff_secs {
+            config.backoff = backoff_secs
+                .iter()
+                .copied()
+                .map(Duration::from_secs)
+                .collect();
+        }
+        if let Some(max_ack_pending) = self.max_ack_pending {
+            config.max_ack_pending = max_ack_pending;
+        }
+    }
+}
+
+impl KeyValueSpec {
+    /// The stream JetStream stores the bucket in.
+    pub fn stream_name(&self) -> String {
+        format!("KV_{}", self.bucket)
+    }
+
+    pub fn to_config(&self) -> kv::Config {
+        let mut config = kv::Config {
+            bucket: self.bucket.clone(),
+            description: self.description.clone().unwrap_or_default(),
+            history: self.history,
+            num_replicas: self.num_replicas,
+            ..Default::default()
+        };
+        if let Some(max_age_secs) = self.max_age_secs {
+            config.max_age = Duration::from_secs(max_age_secs);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            config.max_bytes = max_bytes;
+        }
+        config
+    }
+
+    /// Sets the fields this spec controls on the config of the bucket's stream.
+    pub fn overlay(&self, config: &mut stream::Config) {
+        config.max_messages_per_subject = self.history;
+        config.num_replicas = self.num_replicas;
+        if let Some(description) = &self.description {
+            config.description = Some(description.clone());
+        }
+        if let Some(max_age_secs) = self.max_age_secs {
+            config.max_age = Duration::from_secs(max_age_secs);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            config.max_bytes = max_bytes;
+        }
+    }
+}
+
+impl ObjectStoreSpec {
+    /// The stream JetStream stores the bucket in.
+    pub fn stream_name(&self) -> String {
+        format!("OBJ_{}", self.bucket)
+    }
+
+    pub fn to_config(&self) -> object_store::Config {
+        let mut config = object_store::Config {
+            bucket: self.bucket.clone(),
+            description: self.description.clone(),
+            num_replicas: self.num_replicas,
+            ..Default::default()
+        };
+        if let Some(max_age_secs) = self.max_age_secs {
+            config.max_age = Duration::from_secs(max_age_secs);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            config.max_bytes = max_bytes;
+        }
+        config
+    }
+
+    /// Sets the fields this spec controls on the config of the bucket's stream.
+    pub fn overlay(&self, config: &mut stream::Config) {
+        config.num_replicas = self.num_replicas;
+        if let Some(description) = &self.description {
+            config.description = Some(description.clone());
+        }
+        if let Some(max_age_secs) = self.max_age_secs {
+            config.max_age = Duration::from_secs(max_age_secs);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            config.max_bytes = max_bytes;
+        }
+    }
+}
+
+impl From<Retention> for stream::RetentionPolicy {
+    fn from(retention: Retention) -> Self {
+        match retention {
+            Retention::Limits => stream::RetentionPolicy::Limits,
+            Retention::Interest => stream::RetentionPolicy::Interest,
+            Retention::WorkQueue => stream::RetentionPolicy::WorkQueue,
+        }
+    }
+}
+
+impl From<Discard> for stream::DiscardPolicy {
+    fn from(discard: Discard) -> Self {
+        match discard {
+            Discard::Old => stream::DiscardPolicy::Old,
+            Discard::New => stream::DiscardPolicy::New,
+        }
+    }
+}
+
+impl From<DeliverPolicy> for consumer::DeliverPolicy {
+    fn from(deliver_policy: DeliverPolicy) -> Self {
+        match deliver_policy {
+            DeliverPolicy::All => consumer::DeliverPolicy::All,
+            DeliverPolicy::New => consumer::DeliverPolicy::New,
+            DeliverPolicy::Last => consumer::DeliverPolicy::Last,
+            DeliverPolicy::LastPerSubject => consumer::DeliverPolicy::LastPerSubject,
+        }
+    }
+}
+
+impl From<AckPolicy> for consumer::AckPolicy {
+    fn from(ack_policy: AckPolicy) -> Self {
+        match ack_policy {
+            AckPolicy::Explicit => consumer::AckPolicy::Explicit,
+            AckPolicy::All => consumer::AckPolicy::All,
+            AckPolicy::None => consumer::AckPolicy::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(name: &str) -> StreamSpec {
+        StreamSpec {
+            name: name.to_string(),
+            subjects: vec![format!("{name}.>")],
+            ..Default::default()
+        }
+    }
+
+    fn consumer(name: &str) -> ConsumerSpec {
+        ConsumerSpec {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn config_error(result: Result<()>) -> String {
+        match result {
+            Err(Error::ConfigError(message)) => message,
+            other => panic!("expected ConfigError, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn validate_rejects_invalid_names() {
+        for name in ["", "saker.v1", "saker*", "saker>", "sa ker", "saker/v1"] {
+            let topology = Topology {
+                streams: vec![stream(name)],
+                ..Default::default()
+            };
+            assert!(topology.validate().is_err(), "{name:?}");
+        }
+
+        let mut with_consumer = stream("saker");
+        with_consumer.consumers = vec![consumer("arkiv.v1")];
+        let topology = Topology {
+            streams: vec![with_consumer],
+            ..Default::default()
+        };
+        assert!(config_error(topology.validate()).contains("consumer"));
+    }
+
+    #[test]
+    fn validate_rejects_duplicates_per_kind() {
+        let topology = Topology {
+            streams: vec![stream("saker"), stream("saker")],
+            ..Default::default()
+        };
+        assert!(config_error(topology.validate()).contains("stream saker"));
+
+        let mut with_consumers = stream("saker");
+        with_consumers.consumers = vec![consumer("arkiv"), consumer("arkiv")];
+        let topology = Topology {
+            streams: vec![with_consumers],
+            ..Default::default()
+        };
+        assert!(config_error(topology.validate()).contains("consumer saker/arkiv"));
+
+        let bucket = KeyValueSpec {
+            bucket: "saker".to_string(),
+            ..Default::default()
+        };
+        let topology = Topology {
+            key_value_buckets: vec![bucket.clone(), bucket],
+            ..Default::default()
+        };
+        assert!(config_error(topology.validate()).contains("key value bucket saker"));
+    }
+
+    #[test]
+    fn validate_allows_the_same_name_across_kinds_and_streams() {
+        let mut saker = stream("saker");
+        saker.consumers = vec![consumer("arkiv")];
+        let mut vedtak = stream("vedtak");
+        vedtak.consumers = vec![consumer("arkiv")];
+        let topology = Topology {
+            streams: vec![saker, vedtak],
+            key_value_buckets: vec![KeyValueSpec {
+                bucket: "saker".to_string(),
+                ..Default::default()
+            }],
+            object_stores: vec![ObjectStoreSpec {
+                bucket: "saker".to_string(),
+                ..Default::default()
+            }],
+        };
+        assert!(topology.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_requires_subjects_or_sources() {
+        let topology = Topology {
+            streams: vec![StreamSpec {
+                name: "saker".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(config_error(topology.validate()).contains("at least one subject or source"));
+
+        let topology = Topology {
+            streams: vec![StreamSpec {
+                name: "saker".to_string(),
+                sources: vec![SourceSpec::new("saker_v1", None)],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(topology.validate().is_ok());
+    }
+
+    #[test]
+    fn source_matches_name_and_filter() {
+        let spec = SourceSpec::new("saker", Some("saker.v1.>"));
+        let mut source = spec.to_source();
+        assert!(spec.matches(&source));
+
+        source.start_sequence = Some(10);
+        assert!(spec.matches(&source));
+
+        source.filter_subject = Some("saker.v2.>".to_string());
+        assert!(!spec.matches(&source));
+        assert!(!SourceSpec::new("saker", None).matches(&spec.to_source()));
+        assert!(!SourceSpec::new("vedtak", Some("saker.v1.>")).matches(&spec.to_source()));
+    }
+
+    #[test]
+    fn filter_subjects_become_subject_transforms() {
+        let spec = SourceSpec::with_filter_subjects("saker", &["saker.v1.>", "saker.v2.>"]);
+        let source = spec.to_source();
+        assert_eq!(source.filter_subject, None);
+        assert_eq!(
+            source.subject_transforms,
+            [
+                stream::SubjectTransform {
+                    source: "saker.v1.>".to_string(),
+                    destination: "saker.v1.>".to_string(),
+                },
+                stream::SubjectTransform {
+                    source: "saker.v2.>".to_string(),
+                    destination: "saker.v2.>".to_string(),
+                },
+            ]
+        );
+        assert!(spec.matches(&source));
+        assert!(!SourceSpec::with_filter_subjects("saker", &["saker.v1.>"]).matches(&source));
+        assert!(!SourceSpec::new("saker", None).matches(&source));
+    }
+
+    #[test]
+    fn validate_rejects_filter_subject_with_filter_subjects() {
+        let mut source = SourceSpec::with_filter_subjects("saker_v1", &["saker.v1.>"]);
+        source.filter_subject = Some("saker.>".to_string());
+        let topology = Topology {
+            streams: vec![StreamSpec {
+                name: "saker".to_string(),
+                sources: vec![source],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(
+            config_error(topology.validate()).contains("both filter_subject and filter_subjects")
+        );
+    }
+
+    #[test]
+    fn stream_overlay_keeps_unset_fields_and_matching_sources() {
+        let mut existing = stream::Config {
+            name: "alle".to_string(),
+            max_age: Duration::from_secs(60),
+            max_bytes: 1024,
+            sources: Some(vec![
+                stream::Source {
+                    start_sequence: Some(10),
+                    ..SourceSpec::new("saker", Some("saker.>")).to_source()
+                },
+                SourceSpec::new("fjernet", None).to_source(),
+            ]),
+            ..Default::default()
+        };
+        let spec = StreamSpec {
+            name: "alle".to_string(),
+            sources: vec![
+                SourceSpec::new("saker", Some("saker.>")),
+                SourceSpec::new("vedtak", None),
+            ],
+            max_bytes: Some(2048),
+            ..Default::default()
+        };
+
+        spec.overlay(&mut existing);
+
+        assert_eq!(existing.max_age, Duration::from_secs(60));
+        assert_eq!(existing.max_bytes, 2048);
+        assert_eq!(existing.num_replicas, DEFAULT_NUM_REPLICAS);
+        let sources = existing.sources.unwrap();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].name, "saker");
+        assert_eq!(sources[0].start_sequence, Some(10));
+        assert_eq!(sources[1].name, "vedtak");
+    }
+
+    #[test]
+    fn consumer_config_uses_name_as_durable_name() {
+        let spec = ConsumerSpec {
+            name: "arkiv".to_string(),
+            filter_subject: "saker.>".to_string(),
+            backoff_secs: Some(vec![5, 30]),
+            ..Default::default()
+        };
+
+        let config = spec.to_config();
+
+        assert_eq!(config.name.as_deref(), Some("arkiv"));
+        assert_eq!(config.durable_name.as_deref(), Some("arkiv"));
+        assert_eq!(config.filter_subject, "saker.>");
+        assert_eq!(
+            config.backoff,
+            [Duration::from_secs(5), Duration::from_secs(30)]
+        );
+        assert_eq!(config.ack_policy, consumer::AckPolicy::Explicit);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn parses_toml() {
+        let topology = Topology::from_toml_str(
+            r#"
+            [[streams]]
+            name = "alle"
+            max_age_secs = 3600
+            sources = [
+              { name = "saker", filter_subject = "saker.>" },
+              { name = "vedtak" },
+            ]
+
+            [[streams.consumers]]
+            name = "arkiv"
+            deliver_policy = "last_per_subject"
+            backoff_secs = [5, 30]
+
+            [[key_value_buckets]]
+            bucket = "status"
+            history = 5
+
+            [[object_stores]]
+            bucket = "vedlegg"
+            "#,
+        )
+        .unwrap();
+
+        let alle = &topology.streams[0];
+        assert_eq!(alle.max_age_secs, Some(3600));
+        assert_eq!(alle.num_replicas, DEFAULT_NUM_REPLICAS);
+        assert_eq!(
+            alle.sources,
+            [
+                SourceSpec::new("saker", Some("saker.>")),
+                SourceSpec::new("vedtak", None)
+            ]
+        );
+        assert_eq!(
+            alle.consumers[0].deliver_policy,
+            DeliverPolicy::LastPerSubject
+        );
+        assert_eq!(topology.key_value_buckets[0].history, 5);
+        assert_eq!(topology.object_stores[0].bucket, "vedlegg");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_rejects_unknown_fields_and_invalid_topologies() {
+        assert!(
+            Topology::from_toml_str("[[streams]]\nname = \"alle\"\nsubject = [\"a\"]").is_err()
+        );
+        assert!(Topology::from_toml_str("[[streams]]\nname = \"alle\"").is_err());
+    }
+}
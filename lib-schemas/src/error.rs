@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+#[cfg(feature = "skuffen")]
+use crate::skuffen::dokument::FeltError;
+#[cfg(feature = "skuffen")]
+use crate::skuffen::mal::MalError;
 #[cfg(feature = "skuffen")]
 use crate::skuffen::sak::{SaksnummerError, SakstittelError};
 
@@ -32,6 +36,14 @@ pub enum ParseError {
     #[cfg(feature = "skuffen")]
     #[error("Saksnummer Error in {0}")]
     Saksnummer(#[from] SaksnummerError),
+    /// Invalid feltverdi.
+    #[cfg(feature = "skuffen")]
+    #[error("Felt Error in {0}")]
+    Felt(#[from] FeltError),
+    /// Invalid HTML-mal.
+    #[cfg(feature = "skuffen")]
+    #[error("Mal Error in {0}")]
+    Mal(#[from] MalError),
     /// Generic parse error message.
     #[error("{0}")]
     Message(String),
@@ -56,3 +68,17 @@ impl From<SakstittelError> for SchemasError {
         SchemasError::ParseError(ParseError::Sakstittel(err))
     }
 }
+
+#[cfg(feature = "skuffen")]
+impl From<MalError> for SchemasError {
+    fn from(err: MalError) -> Self {
+        SchemasError::ParseError(ParseError::Mal(err))
+    }
+}
+
+#[cfg(feature = "skuffen")]
+impl From<FeltError> for SchemasError {
+    fn from(err: FeltError) -> Self {
+        SchemasError::ParseError(ParseError::Felt(err))
+    }
+}
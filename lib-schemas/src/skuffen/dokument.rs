@@ -1,4 +1,9 @@
+use crate::skuffen::journalpost::JournalpostId;
+use crate::skuffen::sak::Saksnummer;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use uuid::Uuid;
 
 /// Identifier for dokumenter lagret i arkivet.
@@ -43,15 +48,311 @@ pub enum Dokumentform {
 }
 
 /// Felt som kan substitueres inn i HTML-maler.
-#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Copy, Hash)]
+///
+/// Serde-kodene er en del av kontrakten med Skuffen og er satt eksplisitt, så de ikke endres
+/// om en variant får nytt navn.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize, Clone, Copy, Hash)]
 pub enum Felt {
     /// Saksnummer for saken dokumentet hører til.
+    #[serde(rename = "Saksnummer")]
     Saksnummer,
+    /// Arkivets id for journalposten dokumentet legges på.
+    #[serde(rename = "JournalpostId")]
+    JournalpostId,
+    /// Datoen dokumentet gjelder fra.
+    #[serde(rename = "Dokumentdato")]
+    Dokumentdato,
+    /// Navnet til saksbehandleren som er ansvarlig for journalposten.
+    #[serde(rename = "SaksbehandlerNavn")]
+    SaksbehandlerNavn,
+    /// Navnet til mottakeren.
+    #[serde(rename = "MottakerNavn")]
+    MottakerNavn,
+    /// Postadressen til mottakeren, én linje per adresselinje.
+    #[serde(rename = "MottakerAdresse")]
+    MottakerAdresse,
+    /// Datoen arkivet registrerte journalposten.
+    #[serde(rename = "Registreringsdato")]
+    Registreringsdato,
 }
 
+/// Maks lengde på navn i tegn.
+const NAVN_MAX_LENGTH: usize = 200;
+/// Maks lengde på en adresse i tegn, alle linjer medregnet.
+const ADRESSE_MAX_LENGTH: usize = 500;
+
+impl Felt {
+    /// Alle felt, i samme rekkefølge som varianten er deklarert.
+    pub const ALLE: [Felt; 7] = [
+        Felt::Saksnummer,
+        Felt::JournalpostId,
+        Felt::Dokumentdato,
+        Felt::SaksbehandlerNavn,
+        Felt::MottakerNavn,
+        Felt::MottakerAdresse,
+        Felt::Registreringsdato,
+    ];
+
+    /// Navnet feltet har som plassholder i HTML-maler, for eksempel `{{ saksnummer }}`.
+    pub fn plassholder(self) -> &'static str {
+        match self {
+            Felt::Saksnummer => "saksnummer",
+            Felt::JournalpostId => "journalpost_id",
+            Felt::Dokumentdato => "dokumentdato",
+            Felt::SaksbehandlerNavn => "saksbehandler_navn",
+            Felt::MottakerNavn => "mottaker_navn",
+            Felt::MottakerAdresse => "mottaker_adresse",
+            Felt::Registreringsdato => "registreringsdato",
+        }
+    }
+
+    /// Feltet med plassholder-navnet `navn`, om det finnes.
+    pub fn fra_plassholder(navn: &str) -> Option<Self> {
+        Self::ALLE
+            .into_iter()
+            .find(|felt| felt.plassholder() == navn)
+    }
+
+    /// Parse en verdi for feltet fra tekst. Datoer skrives som `YYYY-MM-DD`, og `\r\n` i
+    /// adresser blir `\n`.
+    pub fn parse_verdi(self, verdi: &str) -> std::result::Result<FeltVerdi, FeltError> {
+        let verdi = match self {
+            Felt::Saksnummer => FeltVerdi::Saksnummer(Saksnummer::new(verdi).map_err(|e| {
+                FeltError::UgyldigVerdi {
+                    felt: self,
+                    melding: e.to_string(),
+                }
+            })?),
+            Felt::JournalpostId => FeltVerdi::JournalpostId(JournalpostId(verdi.to_string())),
+            Felt::Dokumentdato | Felt::Registreringsdato => {
+                FeltVerdi::Dato(NaiveDate::parse_from_str(verdi, "%Y-%m-%d").map_err(|e| {
+                    FeltError::UgyldigVerdi {
+                        felt: self,
+                        melding: e.to_string(),
+                    }
+                })?)
+            }
+            Felt::SaksbehandlerNavn | Felt::MottakerNavn => FeltVerdi::Tekst(verdi.to_string()),
+            Felt::MottakerAdresse => FeltVerdi::Tekst(verdi.replace("\r\n", "\n")),
+        };
+        self.valider(&verdi)?;
+        Ok(verdi)
+    }
+
+    /// Sjekk at `verdi` har riktig type og innhold for feltet.
+    pub fn valider(self, verdi: &FeltVerdi) -> std::result::Result<(), FeltError> {
+        match (self, verdi) {
+            (Felt::Saksnummer, FeltVerdi::Saksnummer(_)) => Ok(()),
+            (Felt::JournalpostId, FeltVerdi::JournalpostId(id)) => {
+                valider_tekst(self, id.as_str(), NAVN_MAX_LENGTH, false)
+            }
+            (Felt::Dokumentdato | Felt::Registreringsdato, FeltVerdi::Dato(_)) => Ok(()),
+            (Felt::SaksbehandlerNavn | Felt::MottakerNavn, FeltVerdi::Tekst(navn)) => {
+                valider_tekst(self, navn, NAVN_MAX_LENGTH, false)
+            }
+            (Felt::MottakerAdresse, FeltVerdi::Tekst(adresse)) => {
+                valider_tekst(self, adresse, ADRESSE_MAX_LENGTH, true)
+            }
+            _ => Err(FeltError::FeilVerditype(self)),
+        }
+    }
+}
+
+fn valider_tekst(
+    felt: Felt,
+    tekst: &str,
+    max_length: usize,
+    flere_linjer: bool,
+) -> std::result::Result<(), FeltError> {
+    if tekst.trim().is_empty() {
+        return Err(FeltError::Tom(felt));
+    }
+    if tekst.chars().count() > max_length {
+        return Err(FeltError::ForLang { felt, max_length });
+    }
+    if tekst
+        .chars()
+        .any(|c| c.is_control() && !(flere_linjer && c == '\n'))
+    {
+        return Err(FeltError::UgyldigTegn(felt));
+    }
+    Ok(())
+}
+
+/// Verdien til et [`Felt`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeltVerdi {
+    Saksnummer(Saksnummer),
+    JournalpostId(JournalpostId),
+    Dato(NaiveDate),
+    Tekst(String),
+}
+
+impl FeltVerdi {
+    /// Verdien slik den settes inn i malen. Datoer skrives som `DD.MM.YYYY`.
+    pub fn tekst(&self) -> String {
+        match self {
+            FeltVerdi::Saksnummer(saksnummer) => saksnummer.to_string(),
+            FeltVerdi::JournalpostId(id) => id.as_str().to_string(),
+            FeltVerdi::Dato(dato) => dato.format("%d.%m.%Y").to_string(),
+            FeltVerdi::Tekst(tekst) => tekst.clone(),
+        }
+    }
+
+    /// Verdien slik den serialiseres. Datoer skrives som `YYYY-MM-DD`.
+    fn kode(&self) -> String {
+        match self {
+            FeltVerdi::Dato(dato) => dato.format("%Y-%m-%d").to_string(),
+            _ => self.tekst(),
+        }
+    }
+}
+
+impl From<Saksnummer> for FeltVerdi {
+    fn from(saksnummer: Saksnummer) -> Self {
+        FeltVerdi::Saksnummer(saksnummer)
+    }
+}
+
+impl From<JournalpostId> for FeltVerdi {
+    fn from(id: JournalpostId) -> Self {
+        FeltVerdi::JournalpostId(id)
+    }
+}
+
+impl From<NaiveDate> for FeltVerdi {
+    fn from(dato: NaiveDate) -> Self {
+        FeltVerdi::Dato(dato)
+    }
+}
+
+impl From<String> for FeltVerdi {
+    fn from(tekst: String) -> Self {
+        FeltVerdi::Tekst(tekst)
+    }
+}
+
+impl From<&str> for FeltVerdi {
+    fn from(tekst: &str) -> Self {
+        FeltVerdi::Tekst(tekst.to_string())
+    }
+}
+
+/// Verdiene til feltene i en HTML-mal, validert per felt.
+///
+/// Serialiseres som et map fra felt-kode til tekst, og valideres på nytt ved deserialisering.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "BTreeMap<Felt, String>", into = "BTreeMap<Felt, String>")]
+pub struct FeltVerdier(BTreeMap<Felt, FeltVerdi>);
+
+impl FeltVerdier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sett verdien til `felt`, etter at den er validert. Tekst parses med
+    /// [`Felt::parse_verdi`], så en dato kan gis som `"2025-03-01"`.
+    pub fn sett(
+        &mut self,
+        felt: Felt,
+        verdi: impl Into<FeltVerdi>,
+    ) -> std::result::Result<(), FeltError> {
+        let verdi = match verdi.into() {
+            FeltVerdi::Tekst(tekst) => felt.parse_verdi(&tekst)?,
+            verdi => {
+                felt.valider(&verdi)?;
+                verdi
+            }
+        };
+        self.0.insert(felt, verdi);
+        Ok(())
+    }
+
+    /// Som [`FeltVerdier::sett`], for kjeding.
+    pub fn med(
+        mut self,
+        felt: Felt,
+        verdi: impl Into<FeltVerdi>,
+    ) -> std::result::Result<Self, FeltError> {
+        self.sett(felt, verdi)?;
+        Ok(self)
+    }
+
+    pub fn get(&self, felt: Felt) -> Option<&FeltVerdi> {
+        self.0.get(&felt)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Felt, &FeltVerdi)> {
+        self.0.iter().map(|(felt, verdi)| (*felt, verdi))
+    }
+
+    /// Sjekk at alle `felter` har en verdi.
+    pub fn valider(&self, felter: &[Felt]) -> std::result::Result<(), FeltError> {
+        match felter.iter().find(|felt| !self.0.contains_key(felt)) {
+            Some(felt) => Err(FeltError::Mangler(*felt)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TryFrom<BTreeMap<Felt, String>> for FeltVerdier {
+    type Error = FeltError;
+
+    fn try_from(verdier: BTreeMap<Felt, String>) -> std::result::Result<Self, Self::Error> {
+        verdier
+            .into_iter()
+            .map(|(felt, verdi)| Ok((felt, felt.parse_verdi(&verdi)?)))
+            .collect::<std::result::Result<_, _>>()
+            .map(FeltVerdier)
+    }
+}
+
+impl From<FeltVerdier> for BTreeMap<Felt, String> {
+    fn from(verdier: FeltVerdier) -> Self {
+        verdier
+            .0
+            .into_iter()
+            .map(|(felt, verdi)| (felt, verdi.kode()))
+            .collect()
+    }
+}
+
+/// Errors relatert til feltverdier.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum FeltError {
+    Mangler(Felt),
+    FeilVerditype(Felt),
+    Tom(Felt),
+    ForLang { felt: Felt, max_length: usize },
+    UgyldigTegn(Felt),
+    UgyldigVerdi { felt: Felt, melding: String },
+}
+
+impl fmt::Display for FeltError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeltError::Mangler(felt) => write!(f, "Mangler verdi for {felt:?}."),
+            FeltError::FeilVerditype(felt) => write!(f, "Feil type verdi for {felt:?}."),
+            FeltError::Tom(felt) => write!(f, "{felt:?} er tom."),
+            FeltError::ForLang { felt, max_length } => {
+                write!(f, "{felt:?} er for lang. Max lengde: {max_length}")
+            }
+            FeltError::UgyldigTegn(felt) => write!(f, "{felt:?} inneholder ugyldige tegn."),
+            FeltError::UgyldigVerdi { felt, melding } => {
+                write!(f, "Ugyldig verdi for {felt:?}: {melding}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FeltError {}
+
 #[cfg(test)]
 mod tests {
-    use super::{Dokument, Dokumentform, Felt};
+    use super::{Dokument, Dokumentform, Felt, FeltError, FeltVerdi, FeltVerdier};
+    use crate::skuffen::journalpost::JournalpostId;
+    use crate::skuffen::sak::Saksnummer;
+    use chrono::NaiveDate;
     use uuid::Uuid;
 
     #[test]
@@ -125,4 +426,134 @@ mod tests {
         let err = serde_json::from_value::<Dokument>(json).expect_err("unknown felt variant");
         assert!(err.to_string().contains("unknown variant"));
     }
+
+    #[test]
+    fn gammel_payload_med_bare_saksnummer_deserialiseres() {
+        let mal_referanse = Uuid::new_v4();
+        let json = serde_json::json!({
+            "client_reference": Uuid::new_v4(),
+            "tittel": "Vedtak",
+            "form": { "HtmlTemplate": { "mal_referanse": mal_referanse, "felter": ["Saksnummer"] } }
+        });
+
+        let dokument: Dokument = serde_json::from_value(json).expect("deserialize gammel payload");
+        assert_eq!(
+            dokument.form,
+            Dokumentform::HtmlTemplate {
+                mal_referanse,
+                felter: vec![Felt::Saksnummer],
+            }
+        );
+    }
+
+    #[test]
+    fn felt_har_stabile_serde_koder() {
+        let koder: Vec<_> = Felt::ALLE
+            .iter()
+            .map(|felt| serde_json::to_value(felt).unwrap())
+            .collect();
+        assert_eq!(
+            koder,
+            [
+                "Saksnummer",
+                "JournalpostId",
+                "Dokumentdato",
+                "SaksbehandlerNavn",
+                "MottakerNavn",
+                "MottakerAdresse",
+                "Registreringsdato",
+            ]
+        );
+        for felt in Felt::ALLE {
+            assert_eq!(Felt::fra_plassholder(felt.plassholder()), Some(felt));
+        }
+    }
+
+    #[test]
+    fn felt_verdier_valideres_per_felt() {
+        let verdier = FeltVerdier::new()
+            .med(Felt::Saksnummer, Saksnummer::new("2025/12").unwrap())
+            .unwrap()
+            .med(Felt::JournalpostId, JournalpostId("2025000123".to_string()))
+            .unwrap()
+            .med(
+                Felt::Dokumentdato,
+                NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            )
+            .unwrap()
+            .med(Felt::MottakerAdresse, "Storgata 1\r\n0155 Oslo")
+            .unwrap()
+            .med(Felt::Registreringsdato, "2025-03-02")
+            .unwrap();
+
+        assert_eq!(
+            verdier.get(Felt::Dokumentdato).map(FeltVerdi::tekst),
+            Some("01.03.2025".to_string())
+        );
+        assert_eq!(
+            verdier.get(Felt::Registreringsdato),
+            Some(&FeltVerdi::Dato(
+                NaiveDate::from_ymd_opt(2025, 3, 2).unwrap()
+            ))
+        );
+        assert_eq!(
+            verdier.get(Felt::MottakerAdresse),
+            Some(&FeltVerdi::Tekst("Storgata 1\n0155 Oslo".to_string()))
+        );
+        assert_eq!(
+            verdier.valider(&[Felt::Saksnummer, Felt::MottakerNavn]),
+            Err(FeltError::Mangler(Felt::MottakerNavn))
+        );
+        assert!(matches!(
+            verdier.clone().med(Felt::Dokumentdato, "i går"),
+            Err(FeltError::UgyldigVerdi {
+                felt: Felt::Dokumentdato,
+                ..
+            })
+        ));
+        assert_eq!(
+            verdier
+                .clone()
+                .med(Felt::Dokumentdato, JournalpostId("2025000123".to_string())),
+            Err(FeltError::FeilVerditype(Felt::Dokumentdato))
+        );
+        assert_eq!(
+            verdier.clone().med(Felt::MottakerNavn, "  "),
+            Err(FeltError::Tom(Felt::MottakerNavn))
+        );
+        assert_eq!(
+            verdier.clone().med(Felt::MottakerNavn, "Ola\nNordmann"),
+            Err(FeltError::UgyldigTegn(Felt::MottakerNavn))
+        );
+        assert!(matches!(
+            verdier
+                .clone()
+                .med(Felt::SaksbehandlerNavn, "x".repeat(201)),
+            Err(FeltError::ForLang { .. })
+        ));
+    }
+
+    #[test]
+    fn felt_verdier_serialiseres_som_map_og_valideres_ved_deserialisering() {
+        let verdier = FeltVerdier::new()
+            .med(Felt::Saksnummer, Saksnummer::new("2025/12").unwrap())
+            .unwrap()
+            .med(
+                Felt::Registreringsdato,
+                NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            )
+            .unwrap();
+
+        let json = serde_json::to_value(&verdier).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "Saksnummer": "2025/12", "Registreringsdato": "2025-03-01" })
+        );
+        let roundtrip: FeltVerdier = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, verdier);
+
+        let err = serde_json::from_value::<FeltVerdier>(serde_json::json!({ "Saksnummer": "12" }))
+            .expect_err("ugyldig saksnummer");
+        assert!(err.to_string().contains("Saksnummer"), "{err}");
+    }
 }
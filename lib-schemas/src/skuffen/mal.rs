@@ -0,0 +1,207 @@
+use crate::skuffen::dokument::{Felt, FeltVerdier};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// HTML-mal for [`Dokumentform::HtmlTemplate`](crate::skuffen::dokument::Dokumentform::HtmlTemplate),
+/// med en plassholder per deklarert felt.
+///
+/// Plassholdersyntaksen `{{ navn }}`, med navnet fra [`Felt::plassholder`], er definert av
+/// dette biblioteket og ikke avstemt mot Skuffens egen fletting. Flettingen her er derfor en
+/// forhåndsvisning for produsentene, ikke en garanti for hva Skuffen arkiverer.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct HtmlMal(String);
+
+impl HtmlMal {
+    pub fn new(html: impl Into<String>) -> Self {
+        Self(html.into())
+    }
+
+    /// Rå HTML, uten flettede verdier.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Navnene på alle plassholderne i malen.
+    pub fn plassholdere(&self) -> Result<BTreeSet<String>, MalError> {
+        Ok(self.deler()?.into_iter().filter_map(Del::navn).collect())
+    }
+
+    /// Sjekk at malen bruker nøyaktig feltene i `felter`: ingen ukjente plassholdere, ingen
+    /// plassholdere for felt som ikke er deklarert, og ingen deklarerte felt som ikke brukes.
+    pub fn valider(&self, felter: &[Felt]) -> Result<(), MalError> {
+        let deklarert: BTreeSet<Felt> = felter.iter().copied().collect();
+        let mut brukt = BTreeSet::new();
+
+        for navn in self.plassholdere()? {
+            let felt = Felt::fra_plassholder(&navn).ok_or(MalError::UkjentPlassholder(navn))?;
+            if !deklarert.contains(&felt) {
+                return Err(MalError::IkkeDeklarert(felt));
+            }
+            brukt.insert(felt);
+        }
+
+        match deklarert.difference(&brukt).next() {
+            Some(ubrukt) => Err(MalError::Ubrukt(*ubrukt)),
+            None => Ok(()),
+        }
+    }
+
+    /// Valider malen mot `felter` og flett inn `verdier`, HTML-escapet. Linjeskift i verdiene
+    /// blir `<br>`.
+    pub fn flett(&self, felter: &[Felt], verdier: &FeltVerdier) -> Result<String, MalError> {
+        self.valider(felter)?;
+        if let Some(mangler) = felter.iter().find(|felt| verdier.get(**felt).is_none()) {
+            return Err(MalError::ManglerVerdi(*mangler));
+        }
+
+        let mut html = String::with_capacity(self.0.len());
+        for del in self.deler()? {
+            match del {
+                Del::Tekst(tekst) => html.push_str(tekst),
+                Del::Plassholder(navn) => {
+                    let felt = Felt::fra_plassholder(navn).expect("validert over");
+                    let verdi = verdier.get(felt).expect("validert over").tekst();
+                    html.push_str(&escape_html(&verdi).replace('\n', "<br>"));
+                }
+            }
+        }
+        Ok(html)
+    }
+
+    fn deler(&self) -> Result<Vec<Del<'_>>, MalError> {
+        let mut deler = Vec::new();
+        let mut rest = self.0.as_str();
+        let mut posisjon = 0;
+
+        while let Some(start) = rest.find("{{") {
+            deler.push(Del::Tekst(&rest[..start]));
+            let etter = &rest[start + 2..];
+            let slutt = etter.find("}}").ok_or(MalError::UavsluttetPlassholder {
+                posisjon: posisjon + start,
+            })?;
+            deler.push(Del::Plassholder(etter[..slutt].trim()));
+            let lest = start + 2 + slutt + 2;
+            posisjon += lest;
+            rest = &rest[lest..];
+        }
+        deler.push(Del::Tekst(rest));
+        Ok(deler)
+    }
+}
+
+enum Del<'a> {
+    Tekst(&'a str),
+    Plassholder(&'a str),
+}
+
+impl Del<'_> {
+    fn navn(self) -> Option<String> {
+        match self {
+            Del::Tekst(_) => None,
+            Del::Plassholder(navn) => Some(navn.to_string()),
+        }
+    }
+}
+
+/// Escape tegnene som har betydning i HTML-tekst og attributtverdier.
+pub fn escape_html(verdi: &str) -> String {
+    let mut escaped = String::with_capacity(verdi.len());
+    for c in verdi.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Errors relatert til HTML-maler.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum MalError {
+    UavsluttetPlassholder { posisjon: usize },
+    UkjentPlassholder(String),
+    IkkeDeklarert(Felt),
+    Ubrukt(Felt),
+    ManglerVerdi(Felt),
+}
+
+impl fmt::Display for MalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MalError::UavsluttetPlassholder { posisjon } => {
+                write!(f, "Plassholder ved posisjon {posisjon} mangler '}}}}'.")
+            }
+            MalError::UkjentPlassholder(navn) => write!(f, "Ukjent plassholder: {navn}."),
+            MalError::IkkeDeklarert(felt) => {
+                write!(f, "Malen bruker {felt:?}, men feltet er ikke deklarert.")
+            }
+            MalError::Ubrukt(felt) => {
+                write!(f, "Feltet {felt:?} er deklarert, men brukes ikke i malen.")
+            }
+            MalError::ManglerVerdi(felt) => write!(f, "Mangler verdi for {felt:?}."),
+        }
+    }
+}
+
+impl std::error::Error for MalError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skuffen::sak::Saksnummer;
+
+    #[test]
+    fn finner_plassholdere_med_og_uten_mellomrom() {
+        let mal = HtmlMal::new("<p>{{saksnummer}}</p><p>{{ saksnummer }}</p>");
+        assert_eq!(
+            mal.plassholdere().unwrap(),
+            BTreeSet::from(["saksnummer".to_string()])
+        );
+        assert_eq!(mal.valider(&[Felt::Saksnummer]), Ok(()));
+    }
+
+    #[test]
+    fn avviser_avvik_mellom_mal_og_felter() {
+        assert_eq!(
+            HtmlMal::new("<p>{{ saksnummer }}</p>").valider(&[]),
+            Err(MalError::IkkeDeklarert(Felt::Saksnummer))
+        );
+        assert_eq!(
+            HtmlMal::new("<p>Ingen felt</p>").valider(&[Felt::Saksnummer]),
+            Err(MalError::Ubrukt(Felt::Saksnummer))
+        );
+        assert_eq!(
+            HtmlMal::new("<p>{{ tittel }}</p>").valider(&[]),
+            Err(MalError::UkjentPlassholder("tittel".to_string()))
+        );
+        assert_eq!(
+            HtmlMal::new("<p>{{ saksnummer</p>").valider(&[Felt::Saksnummer]),
+            Err(MalError::UavsluttetPlassholder { posisjon: 3 })
+        );
+    }
+
+    #[test]
+    fn fletter_inn_escapede_verdier() {
+        let mal = HtmlMal::new("<h1>Sak {{ saksnummer }}</h1><p>{{ mottaker_adresse }}</p>");
+        let felter = [Felt::Saksnummer, Felt::MottakerAdresse];
+        let verdier = FeltVerdier::new()
+            .med(Felt::Saksnummer, Saksnummer::new("2025/<b>1</b>").unwrap())
+            .unwrap()
+            .med(Felt::MottakerAdresse, "Storgata 1\n0155 Oslo")
+            .unwrap();
+
+        assert_eq!(
+            mal.flett(&felter, &verdier).unwrap(),
+            "<h1>Sak 2025/&lt;b&gt;1&lt;/b&gt;</h1><p>Storgata 1<br>0155 Oslo</p>"
+        );
+        assert_eq!(
+            mal.flett(&felter, &FeltVerdier::new()),
+            Err(MalError::ManglerVerdi(Felt::Saksnummer))
+        );
+    }
+}
@@ -2,6 +2,7 @@
 pub mod command;
 pub mod dokument;
 pub mod journalpost;
+pub mod mal;
 pub mod query;
 pub mod sak;
 pub mod status;
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Norsk kommunenummer (4 digits), der de to første sifrene er fylkesnummeret.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Kommunenummer(String);
+
+impl Kommunenummer {
+    /// Lag et validert kommunenummer.
+    pub fn new(knr: impl Into<String>) -> Result<Self, &'static str> {
+        let knr = knr.into().trim().to_string();
+        if knr.len() != 4 || !knr.chars().all(|c| c.is_ascii_digit()) {
+            return Err("ugyldig kommunenummer");
+        }
+        Ok(Self(knr))
+    }
+
+    /// Returner raw number string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Fylkesnummeret kommunen tilhører (de to første sifrene).
+    pub fn fylkesnummer(&self) -> &str {
+        &self.0[..2]
+    }
+}
+
+impl fmt::Display for Kommunenummer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<String> for Kommunenummer {
+    type Error = &'static str;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Kommunenummer> for String {
+    fn from(value: Kommunenummer) -> Self {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gyldig_kommunenummer() {
+        let knr = Kommunenummer::new("3403").unwrap();
+        assert_eq!(knr.fylkesnummer(), "34");
+    }
+
+    #[test]
+    fn ugyldig_kommunenummer() {
+        assert!(Kommunenummer::new("403").is_err());
+        assert!(Kommunenummer::new("34O3").is_err());
+    }
+}
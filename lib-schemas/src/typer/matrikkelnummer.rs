@@ -0,0 +1,63 @@
+use crate::typer::kommunenummer::Kommunenummer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Matrikkelnummer for en grunneiendom: kommunenummer, gårdsnummer og bruksnummer.
+///
+/// Vises på standardformen `knr-gnr/bnr`, for eksempel `3403-12/5`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Matrikkelnummer {
+    pub kommunenummer: Kommunenummer,
+    pub gaardsnummer: u32,
+    pub bruksnummer: u32,
+}
+
+impl Matrikkelnummer {
+    /// Lag et matrikkelnummer fra gårds- og bruksnummer slik de ligger i fagsystemene (som tekst).
+    pub fn new(
+        kommunenummer: Kommunenummer,
+        gaardsnummer: &str,
+        bruksnummer: &str,
+    ) -> Result<Self, &'static str> {
+        let gaardsnummer = Self::parse_nummer(gaardsnummer).ok_or("ugyldig gårdsnummer")?;
+        let bruksnummer = Self::parse_nummer(bruksnummer).ok_or("ugyldig bruksnummer")?;
+        Ok(Self {
+            kommunenummer,
+            gaardsnummer,
+            bruksnummer,
+        })
+    }
+
+    fn parse_nummer(nummer: &str) -> Option<u32> {
+        nummer.trim().parse().ok().filter(|n| *n > 0)
+    }
+}
+
+impl fmt::Display for Matrikkelnummer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}/{}",
+            self.kommunenummer, self.gaardsnummer, self.bruksnummer
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formaterer_som_knr_gnr_bnr() {
+        let knr = Kommunenummer::new("3403").unwrap();
+        let matrikkel = Matrikkelnummer::new(knr, " 12", "5").unwrap();
+        assert_eq!(matrikkel.to_string(), "3403-12/5");
+    }
+
+    #[test]
+    fn avviser_ugyldig_gaardsnummer() {
+        let knr = Kommunenummer::new("3403").unwrap();
+        assert!(Matrikkelnummer::new(knr.clone(), "0", "5").is_err());
+        assert!(Matrikkelnummer::new(knr, "tolv", "5").is_err());
+    }
+}
@@ -1,4 +1,8 @@
 //! Common identifier/value types brukt i schemas.
+pub mod kommunenummer;
+pub mod matrikkelnummer;
+pub mod naeringskode;
 pub mod organisasjonsnummer;
 pub mod personnummer;
+pub mod produsentnummer;
 pub mod uuid_id;
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// NACE-næringskode (SN2007), for eksempel `10.110` for bearbeiding av kjøtt.
+///
+/// Kortere koder som `10` eller `10.1` er gyldige og betyr hele grupperingen.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Naeringskode(String);
+
+impl Naeringskode {
+    /// Lag en validert næringskode på formen `NN`, `NN.N`, `NN.NN` eller `NN.NNN`.
+    pub fn new(kode: impl Into<String>) -> Result<Self, &'static str> {
+        let kode = kode.into().trim().to_string();
+        let (hovedomraade, rest) = kode.split_once('.').unwrap_or((&kode, ""));
+        let gyldig = hovedomraade.len() == 2
+            && hovedomraade.chars().all(|c| c.is_ascii_digit())
+            && rest.len() <= 3
+            && rest.chars().all(|c| c.is_ascii_digit())
+            && !kode.ends_with('.');
+        if !gyldig {
+            return Err("ugyldig næringskode");
+        }
+        Ok(Self(kode))
+    }
+
+    /// Returner raw kode string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Om `kode` ligger innenfor denne (eller er lik), for eksempel `10.110` innenfor `10.1`.
+    pub fn omfatter(&self, kode: &Naeringskode) -> bool {
+        kode.0.starts_with(&self.0)
+    }
+}
+
+impl fmt::Display for Naeringskode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<String> for Naeringskode {
+    type Error = &'static str;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Naeringskode> for String {
+    fn from(value: Naeringskode) -> Self {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gyldige_og_ugyldige_koder() {
+        assert!(Naeringskode::new("10.110").is_ok());
+        assert!(Naeringskode::new("01").is_ok());
+        assert!(Naeringskode::new("10.").is_err());
+        assert!(Naeringskode::new("10.1101").is_err());
+        assert!(Naeringskode::new("1.110").is_err());
+    }
+
+    #[test]
+    fn overordnet_kode_omfatter_underliggende() {
+        let gruppe = Naeringskode::new("10.1").unwrap();
+        assert!(gruppe.omfatter(&Naeringskode::new("10.110").unwrap()));
+        assert!(!gruppe.omfatter(&Naeringskode::new("10.2").unwrap()));
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 /// Norsk organization number (9 digits).
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(try_from = "String", into = "String")]
 pub struct Organisasjonsnummer(String);
 
@@ -36,6 +37,12 @@ impl Organisasjonsnummer {
     }
 }
 
+impl fmt::Display for Organisasjonsnummer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl TryFrom<String> for Organisasjonsnummer {
     type Error = &'static str;
     fn try_from(value: String) -> Result<Self, Self::Error> {
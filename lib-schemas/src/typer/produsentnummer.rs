@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Produsentnummer for et landbruksforetak (kun sifre).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Produsentnummer(String);
+
+impl Produsentnummer {
+    /// Lag et validert produsentnummer.
+    pub fn new(produsentnummer: impl Into<String>) -> Result<Self, &'static str> {
+        let produsentnummer = produsentnummer.into().trim().to_string();
+        if produsentnummer.is_empty() || !produsentnummer.chars().all(|c| c.is_ascii_digit()) {
+            return Err("ugyldig produsentnummer");
+        }
+        Ok(Self(produsentnummer))
+    }
+
+    /// Returner raw number string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Produsentnummer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<String> for Produsentnummer {
+    type Error = &'static str;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Produsentnummer> for String {
+    fn from(value: Produsentnummer) -> Self {
+        value.0
+    }
+}